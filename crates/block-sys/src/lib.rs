@@ -130,6 +130,20 @@ pub const BLOCK_HAS_SIGNATURE: block_flags = 1 << 30;
 /// compiler
 pub const BLOCK_HAS_EXTENDED_LAYOUT: block_flags = 1 << 31;
 
+/// The byref structure contains copy and dispose helpers.
+///
+/// Same value as [`BLOCK_HAS_COPY_DISPOSE`], but used in the flags of
+/// [`Block_byref_header`].
+pub const BLOCK_BYREF_HAS_COPY_DISPOSE: block_flags = 1 << 25;
+
+#[cfg(any(doc, feature = "apple"))]
+/// Mask for the layout of the byref structure.
+pub const BLOCK_BYREF_LAYOUT_MASK: block_flags = 0xf << 28;
+
+#[cfg(any(doc, feature = "apple"))]
+/// The byref structure contains a layout string.
+pub const BLOCK_BYREF_LAYOUT_EXTENDED: block_flags = 1 << 28;
+
 /// Flags used in the final argument to _Block_object_assign() and
 /// _Block_object_dispose().  These indicate the type of copy or dispose to
 /// perform.
@@ -239,18 +253,21 @@ extern "C" {
     // #[cfg(any(doc, feature = "apple"))]
     // pub fn _Block_isDeallocating(block: *const c_void) -> bool;
 
-    // indicates whether block was compiled with compiler that sets the ABI
-    // related metadata bits
-    // macOS 10.7
-    // #[cfg(any(doc, feature = "apple", feature = "gnustep-1-7"))]
-    // pub fn _Block_has_signature(block: *mut c_void) -> bool;
+    /// Indicates whether block was compiled with compiler that sets the ABI
+    /// related metadata bits.
+    ///
+    /// macOS 10.7
+    #[cfg(any(doc, feature = "apple", feature = "gnustep-1-7"))]
+    pub fn _Block_has_signature(block: *mut c_void) -> bool;
 
-    // Returns a string describing the block's parameter and return types.
-    // The encoding scheme is the same as Objective-C @encode.
-    // Returns NULL for blocks compiled with some compilers.
-    // macOS 10.7
-    // #[cfg(any(doc, feature = "apple", feature = "gnustep-1-7"))]
-    // pub fn _Block_signature(block: *mut c_void) -> *const c_char;
+    /// Returns a string describing the block's parameter and return types.
+    /// The encoding scheme is the same as Objective-C @encode.
+    ///
+    /// Returns NULL for blocks compiled with some compilers.
+    ///
+    /// macOS 10.7
+    #[cfg(any(doc, feature = "apple", feature = "gnustep-1-7"))]
+    pub fn _Block_signature(block: *mut c_void) -> *const c_char;
 }
 
 #[repr(C)]
@@ -1,10 +1,19 @@
+#[cfg(feature = "apple")]
+use core::ffi::c_void;
 use core::marker::PhantomData;
 use core::mem;
+use std::ffi::CStr;
+use std::os::raw::c_char;
 
+use objc2::declare::{CopyPropertyObject, PropertyObject};
 use objc2::encode::__unstable::EncodeReturn;
 use objc2::encode::{Encode, Encoding, RefEncode};
+#[cfg(doc)]
+use objc2::rc::Id;
+use objc2::Message;
 
 use crate::ffi;
+use crate::signature::{self, VerificationError};
 
 /// Types that may be used as the arguments of an Objective-C block.
 ///
@@ -17,6 +26,10 @@ use crate::ffi;
 /// This is a sealed trait, and should not need to be implemented. Open an
 /// issue if you know a use-case where this restrition should be lifted!
 pub unsafe trait BlockArguments: Sized {
+    /// The encodings of the arguments.
+    #[doc(hidden)]
+    const __ENCODINGS: &'static [Encoding];
+
     /// Calls the given method the block and arguments.
     #[doc(hidden)]
     unsafe fn __call_block<R: EncodeReturn>(
@@ -29,6 +42,8 @@ pub unsafe trait BlockArguments: Sized {
 macro_rules! block_args_impl {
     ($($a:ident: $t:ident),*) => (
         unsafe impl<$($t: Encode),*> BlockArguments for ($($t,)*) {
+            const __ENCODINGS: &'static [Encoding] = &[$($t::ENCODING),*];
+
             #[inline]
             unsafe fn __call_block<R: EncodeReturn>(
                 invoke: unsafe extern "C" fn(),
@@ -84,9 +99,34 @@ block_args_impl!(
     k: K,
     l: L
 );
+// Used by method implementations, which take the receiver in addition to 12
+// arguments.
+block_args_impl!(
+    a: A,
+    b: B,
+    c: C,
+    d: D,
+    e: E,
+    f: F,
+    g: G,
+    h: H,
+    i: I,
+    j: J,
+    k: K,
+    l: L,
+    m: M
+);
 
 /// An Objective-C block that takes arguments of `A` when called and
 /// returns a value of `R`.
+///
+/// Blocks are Objective-C objects, so this implements [`Message`], which
+/// means that [`Id<Block<A, R>>`][Id] can be used to store blocks in
+/// collections, instance variables and similar places that expect an
+/// object. Use the [`From`] implementations on [`RcBlock`] to convert
+/// between the two.
+///
+/// [`RcBlock`]: crate::RcBlock
 #[repr(C)]
 pub struct Block<A, R> {
     _inner: [u8; 0],
@@ -102,7 +142,132 @@ unsafe impl<A: BlockArguments, R: EncodeReturn> RefEncode for Block<A, R> {
     const ENCODING_REF: Encoding = Encoding::Block;
 }
 
+// SAFETY: Blocks are Objective-C objects, which respond to the memory
+// management messages like any other object.
+//
+// Retaining a block that lives on the stack does not copy it to the heap
+// though, so anyone creating an `Id` to a block must make sure that it is a
+// heap or global block (which is the case for blocks returned from methods).
+unsafe impl<A: BlockArguments, R: EncodeReturn> Message for Block<A, R> {}
+
+// SAFETY: Blocks are immutable, so retaining them again is fine. They may
+// live on the stack though, so they must be copied when set.
+unsafe impl<A: BlockArguments, R: EncodeReturn> PropertyObject for Block<A, R> {
+    const REQUIRES_COPY: bool = true;
+}
+
+// SAFETY: Copying a block returns a block with the same signature.
+unsafe impl<A: BlockArguments, R: EncodeReturn> CopyPropertyObject for Block<A, R> {}
+
 impl<A: BlockArguments, R: EncodeReturn> Block<A, R> {
+    /// The NUL-terminated signature of blocks with these argument and return
+    /// types.
+    const SIGNATURE: [u8; signature::MAX_LEN] =
+        signature::signature(&R::ENCODING_RETURN, A::__ENCODINGS);
+
+    /// Pointer to [`Self::SIGNATURE`].
+    pub(crate) const SIGNATURE_PTR: *const c_char = Self::SIGNATURE.as_ptr().cast();
+
+    /// Whether the return value is passed through a hidden pointer.
+    ///
+    /// This uses the same rules as `objc2` uses for choosing between
+    /// `objc_msgSend` and `objc_msgSend_stret`, except that only structs and
+    /// unions are considered, since those are the only types that blocks
+    /// created by Clang ever return indirectly.
+    const USE_STRET: bool = {
+        let size = mem::size_of::<R>();
+        let is_composite = matches!(
+            R::ENCODING_RETURN,
+            Encoding::Struct(_, _) | Encoding::Union(_, _)
+        );
+        if !is_composite {
+            false
+        } else if cfg!(target_arch = "x86_64") {
+            size > 16
+        } else if cfg!(all(target_arch = "x86", feature = "apple")) {
+            !matches!(size, 0 | 1 | 2 | 4 | 8)
+        } else if cfg!(target_arch = "x86") {
+            true
+        } else if cfg!(target_arch = "arm") {
+            size > 4
+        } else {
+            false
+        }
+    };
+
+    /// The flags that blocks with these argument and return types must set.
+    ///
+    /// `BLOCK_USE_STRET` is only meaningful together with
+    /// `BLOCK_HAS_SIGNATURE`, and is used by e.g. `imp_implementationWithBlock`
+    /// to pick the correct trampoline.
+    pub(crate) const SIGNATURE_FLAGS: ffi::block_flags = if Self::USE_STRET {
+        ffi::BLOCK_HAS_SIGNATURE | ffi::BLOCK_USE_STRET
+    } else {
+        ffi::BLOCK_HAS_SIGNATURE
+    };
+
+    /// The type-encoding signature of the block, if it has one.
+    ///
+    /// This is the encoding of the return type, followed by `@?` (the block
+    /// itself), followed by the encodings of each argument, optionally
+    /// interspersed with stack offsets. For example, Clang gives the block
+    /// `int32_t (^)(int32_t)` the signature `"i12@?0i8"`.
+    ///
+    /// Blocks created by this crate always contain a signature.
+    pub fn signature(&self) -> Option<&str> {
+        let ptr: *const Self = self;
+        let layout = unsafe { ptr.cast::<ffi::Block_layout>().as_ref().unwrap_unchecked() };
+        if layout.flags & ffi::BLOCK_HAS_SIGNATURE == 0 {
+            return None;
+        }
+
+        #[cfg(feature = "apple")]
+        let encoding = if layout.flags & ffi::BLOCK_SMALL_DESCRIPTOR != 0 {
+            // Small descriptors store relative offsets, let the runtime
+            // handle that for us.
+            unsafe { ffi::_Block_signature(ptr as *mut c_void) }
+        } else {
+            // SAFETY: The flags ensure that the descriptor has a signature
+            unsafe { descriptor_signature(layout) }
+        };
+        #[cfg(not(feature = "apple"))]
+        // SAFETY: The flags ensure that the descriptor has a signature
+        let encoding = unsafe { descriptor_signature(layout) };
+
+        if encoding.is_null() {
+            None
+        } else {
+            // SAFETY: The signature is a valid C-string, which lives at least
+            // as long as the block itself.
+            unsafe { CStr::from_ptr(encoding) }.to_str().ok()
+        }
+    }
+
+    /// Verify that the block's signature matches the argument and return
+    /// types `A` and `R`.
+    ///
+    /// This is useful for checking blocks received from Objective-C, before
+    /// calling them.
+    ///
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the block has no signature, if the signature
+    /// could not be parsed, or if the encodings differ.
+    ///
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use block2::ConcreteBlock;
+    /// let block = ConcreteBlock::new(|a: i32, b: i32| a + b);
+    /// assert_eq!(block.signature(), Some("i@?ii"));
+    /// assert!(block.verify().is_ok());
+    /// ```
+    pub fn verify(&self) -> Result<(), VerificationError> {
+        signature::verify_signature(self.signature(), A::__ENCODINGS, &R::ENCODING_RETURN)
+    }
+
     /// Call self with the given arguments.
     ///
     /// # Safety
@@ -121,3 +286,21 @@ impl<A: BlockArguments, R: EncodeReturn> Block<A, R> {
         unsafe { A::__call_block(invoke, ptr as *mut Self, args) }
     }
 }
+
+/// Read the signature from the block's descriptor.
+///
+/// # Safety
+///
+/// The block must have the `BLOCK_HAS_SIGNATURE` flag set, and must not use
+/// a small descriptor.
+unsafe fn descriptor_signature(layout: &ffi::Block_layout) -> *const c_char {
+    if layout.flags & ffi::BLOCK_HAS_COPY_DISPOSE != 0 {
+        let descriptor = layout
+            .descriptor
+            .cast::<ffi::Block_descriptor_with_signature>();
+        unsafe { (*descriptor).encoding }
+    } else {
+        let descriptor = layout.descriptor.cast::<ffi::Block_descriptor_basic>();
+        unsafe { (*descriptor).encoding }
+    }
+}
@@ -0,0 +1,278 @@
+use core::ffi::c_void;
+use core::marker::PhantomData;
+use core::mem::{self, ManuallyDrop};
+use core::ptr::{self, NonNull};
+
+use crate::ffi;
+
+/// The layout of a `__block` variable, as emitted by Clang when the variable
+/// needs copy and dispose helpers.
+#[repr(C)]
+pub(crate) struct ByRefLayout<T> {
+    pub(crate) byref: ffi::Block_byref,
+    pub(crate) value: T,
+}
+
+/// Move the value into the heap copy of the byref structure.
+unsafe extern "C" fn byref_keep<T>(dst: *mut c_void, src: *mut c_void) {
+    let dst = dst.cast::<ByRefLayout<T>>();
+    let src = src.cast::<ByRefLayout<T>>();
+    // SAFETY: The runtime passes the newly allocated heap copy in `dst`, and
+    // the original structure in `src`, which is never used again.
+    //
+    // On some runtimes, the memory has already been copied, in which case
+    // this is a no-op.
+    unsafe {
+        ptr::copy(
+            ptr::addr_of!((*src).value),
+            ptr::addr_of_mut!((*dst).value),
+            1,
+        )
+    };
+}
+
+/// Drop the value when the last reference to the byref structure is gone.
+unsafe extern "C" fn byref_destroy<T>(byref: *mut c_void) {
+    let byref = byref.cast::<ByRefLayout<T>>();
+    // SAFETY: The runtime only calls this once, on the heap copy.
+    unsafe { ptr::drop_in_place(ptr::addr_of_mut!((*byref).value)) };
+}
+
+/// A `__block` variable, i.e. storage that is shared between blocks and
+/// their creator.
+///
+/// This is a reference-counted handle to a structure with the same layout as
+/// the one Clang emits for variables declared with the `__block` storage
+/// qualifier. Cloning the handle shares the same underlying storage, so to
+/// access the variable from a block, you capture a clone of the handle in
+/// the closure.
+///
+/// The API for accessing the value is similar to [`Cell`], since the value
+/// may be modified by any block that holds a reference to it.
+///
+/// [`Cell`]: core::cell::Cell
+///
+///
+/// # Example
+///
+/// ```
+/// use block2::{BlockByRef, ConcreteBlock};
+///
+/// let counter = BlockByRef::new(0);
+/// let block = ConcreteBlock::new({
+///     let counter = counter.clone();
+///     move || counter.set(counter.get() + 1)
+/// });
+/// let block = block.copy();
+///
+/// unsafe { block.call(()) };
+/// unsafe { block.call(()) };
+/// assert_eq!(counter.get(), 2);
+/// ```
+pub struct BlockByRef<T> {
+    /// Pointer to the byref structure on the heap.
+    ptr: NonNull<ffi::Block_byref_header>,
+    /// We (partially) own a `T`.
+    p: PhantomData<T>,
+}
+
+impl<T> BlockByRef<T> {
+    /// Create a new `__block` variable containing the given value.
+    pub fn new(value: T) -> Self {
+        // SAFETY: The helpers move and drop a `T`.
+        unsafe { Self::with_helpers(value, byref_keep::<T>, byref_destroy::<T>) }
+    }
+
+    /// Create a new `__block` variable with custom copy and dispose helpers.
+    ///
+    ///
+    /// # Safety
+    ///
+    /// The helpers must be valid for a [`ByRefLayout<T>`], and `keep` must
+    /// move the value to the destination.
+    pub(crate) unsafe fn with_helpers(
+        value: T,
+        keep: unsafe extern "C" fn(*mut c_void, *mut c_void),
+        destroy: unsafe extern "C" fn(*mut c_void),
+    ) -> Self {
+        let mut stack = ManuallyDrop::new(ByRefLayout {
+            byref: ffi::Block_byref {
+                header: ffi::Block_byref_header {
+                    isa: ptr::null(),
+                    // Populated below
+                    forwarding: ptr::null_mut(),
+                    flags: ffi::BLOCK_BYREF_HAS_COPY_DISPOSE,
+                    size: mem::size_of::<ByRefLayout<T>>() as _,
+                },
+                keep: Some(keep),
+                destroy: Some(destroy),
+            },
+            value,
+        });
+        let stack: *mut ByRefLayout<T> = &mut *stack;
+        // SAFETY: The structure is not moved until we're done with it.
+        unsafe { (*stack).byref.header.forwarding = stack.cast() };
+
+        // Let the runtime copy the structure to the heap, like it would do
+        // for a `__block` variable when the first block referencing it is
+        // copied.
+        //
+        // SAFETY: The structure is valid, and contains a valid `T`. The
+        // helpers are upheld by the caller.
+        let this = unsafe { Self::copy(stack.cast()) };
+
+        // Release the reference held by the stack structure.
+        //
+        // The value has been moved to the heap by the copy helper, so it's
+        // important that we don't drop it here (which is ensured by using
+        // `ManuallyDrop`).
+        //
+        // SAFETY: The structure was copied above.
+        unsafe { ffi::_Block_object_dispose(stack.cast(), ffi::BLOCK_FIELD_IS_BYREF) };
+
+        this
+    }
+
+    /// Retain the given `__block` variable, copying it to the heap if it
+    /// isn't already.
+    ///
+    /// This can be used to access `__block` variables that were created in
+    /// C or Objective-C.
+    ///
+    ///
+    /// # Safety
+    ///
+    /// The pointer must point to a valid byref structure whose variable has
+    /// type `T`.
+    pub unsafe fn copy(ptr: *mut ffi::Block_byref_header) -> Self {
+        let mut heap: *mut c_void = ptr::null_mut();
+        let dst: *mut *mut c_void = &mut heap;
+        // SAFETY: Upheld by caller.
+        unsafe { ffi::_Block_object_assign(dst.cast(), ptr.cast(), ffi::BLOCK_FIELD_IS_BYREF) };
+        Self {
+            ptr: NonNull::new(heap.cast()).expect("failed copying __block variable"),
+            p: PhantomData,
+        }
+    }
+
+    /// A pointer to the underlying byref structure.
+    ///
+    /// This can be used to pass the variable to C or Objective-C.
+    pub fn as_byref_ptr(&self) -> *mut ffi::Block_byref_header {
+        self.ptr.as_ptr()
+    }
+
+    /// A pointer to the variable.
+    pub fn as_ptr(&self) -> *mut T {
+        let byref = self.ptr.as_ptr();
+        // SAFETY: The pointer is valid.
+        let flags = unsafe { (*byref).flags };
+
+        // Compute the offset of the variable in the same way Clang lays out
+        // the structure.
+        let mut offset = mem::size_of::<ffi::Block_byref_header>();
+        if flags & ffi::BLOCK_BYREF_HAS_COPY_DISPOSE != 0 {
+            offset = mem::size_of::<ffi::Block_byref>();
+        }
+        #[cfg(feature = "apple")]
+        if flags & ffi::BLOCK_BYREF_LAYOUT_MASK == ffi::BLOCK_BYREF_LAYOUT_EXTENDED {
+            offset += mem::size_of::<*const c_void>();
+        }
+        let align = mem::align_of::<T>();
+        let offset = (offset + align - 1) / align * align;
+
+        // SAFETY: The variable is stored at the computed offset.
+        unsafe { byref.cast::<u8>().add(offset).cast::<T>() }
+    }
+
+    /// Set the contained value.
+    pub fn set(&self, value: T) {
+        drop(self.replace(value));
+    }
+
+    /// Replace the contained value with `value`, and return the old value.
+    pub fn replace(&self, value: T) -> T {
+        // SAFETY: The value is valid, and since we're not `Send` or `Sync`,
+        // there can be no concurrent accesses to it from Rust.
+        unsafe { ptr::replace(self.as_ptr(), value) }
+    }
+}
+
+impl<T: Copy> BlockByRef<T> {
+    /// Return a copy of the contained value.
+    pub fn get(&self) -> T {
+        // SAFETY: Same as in `replace`.
+        unsafe { *self.as_ptr() }
+    }
+}
+
+impl<T> Clone for BlockByRef<T> {
+    fn clone(&self) -> Self {
+        // SAFETY: The structure is valid, and contains a `T`.
+        unsafe { Self::copy(self.ptr.as_ptr()) }
+    }
+}
+
+impl<T> Drop for BlockByRef<T> {
+    fn drop(&mut self) {
+        // SAFETY: We hold a reference to the structure, which we release.
+        unsafe { ffi::_Block_object_dispose(self.ptr.as_ptr().cast(), ffi::BLOCK_FIELD_IS_BYREF) };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ConcreteBlock;
+    use alloc::rc::Rc;
+
+    #[test]
+    fn test_layout() {
+        let byref = BlockByRef::new(5u8);
+        let expected: *const ByRefLayout<u8> = byref.as_byref_ptr().cast();
+        let expected = unsafe { ptr::addr_of!((*expected).value) };
+        assert_eq!(byref.as_ptr() as *const u8, expected);
+    }
+
+    #[test]
+    fn test_shared() {
+        let byref = BlockByRef::new(1);
+        let clone = byref.clone();
+        assert_eq!(byref.as_ptr(), clone.as_ptr());
+        clone.set(2);
+        assert_eq!(byref.get(), 2);
+        assert_eq!(byref.replace(3), 2);
+        assert_eq!(clone.get(), 3);
+    }
+
+    #[test]
+    fn test_drop() {
+        let value = Rc::new(());
+        let byref = BlockByRef::new(value.clone());
+        assert_eq!(Rc::strong_count(&value), 2);
+        let clone = byref.clone();
+        drop(byref);
+        assert_eq!(Rc::strong_count(&value), 2);
+        clone.set(Rc::new(()));
+        assert_eq!(Rc::strong_count(&value), 1);
+        let byref = BlockByRef::new(value.clone());
+        assert_eq!(Rc::strong_count(&value), 2);
+        drop(byref);
+        assert_eq!(Rc::strong_count(&value), 1);
+    }
+
+    #[test]
+    fn test_capture() {
+        let byref = BlockByRef::new(0);
+        let block = ConcreteBlock::new({
+            let byref = byref.clone();
+            move |x: i32| byref.set(byref.get() + x)
+        });
+        unsafe { block.call((2,)) };
+        let block = block.copy();
+        unsafe { block.call((3,)) };
+        assert_eq!(byref.get(), 5);
+        drop(block);
+        assert_eq!(byref.get(), 5);
+    }
+}
@@ -1,17 +1,23 @@
+use core::cell::{Cell, RefCell};
 use core::ffi::c_void;
 use core::marker::PhantomData;
 use core::mem::{self, ManuallyDrop};
 use core::ops::Deref;
 use core::ptr;
+use std::io::Write;
 use std::os::raw::c_ulong;
 
 use objc2::encode::__unstable::EncodeReturn;
 use objc2::encode::{Encode, Encoding, RefEncode};
 
-use crate::{ffi, Block, BlockArguments, RcBlock};
+use crate::{ffi, Block, BlockArguments, RcBlock, SendBlock};
 
 mod private {
     pub trait Sealed<A> {}
+
+    pub trait SealedMut<A> {}
+
+    pub trait SealedOnce<A> {}
 }
 
 /// Types that may be converted into a [`ConcreteBlock`].
@@ -32,6 +38,48 @@ pub unsafe trait IntoConcreteBlock<A: BlockArguments>: private::Sealed<A> + Size
     fn __into_concrete_block(self) -> ConcreteBlock<A, Self::Output, Self>;
 }
 
+/// Types that may be converted into a [`ConcreteBlock`] with
+/// [`ConcreteBlock::new_mut`].
+///
+/// This is implemented for [`FnMut`] closures of up to 12 arguments, where
+/// each argument and the return type implements [`Encode`].
+///
+///
+/// # Safety
+///
+/// This is a sealed trait, and should not need to be implemented. Open an
+/// issue if you know a use-case where this restrition should be lifted!
+pub unsafe trait IntoConcreteBlockMut<A: BlockArguments>:
+    private::SealedMut<A> + Sized
+{
+    /// The return type of the resulting `ConcreteBlock`.
+    type Output: EncodeReturn;
+
+    #[doc(hidden)]
+    fn __into_concrete_block_mut(self) -> ConcreteBlock<A, Self::Output, RefCell<Self>>;
+}
+
+/// Types that may be converted into a [`ConcreteBlock`] with
+/// [`ConcreteBlock::new_once`].
+///
+/// This is implemented for [`FnOnce`] closures of up to 12 arguments, where
+/// each argument and the return type implements [`Encode`].
+///
+///
+/// # Safety
+///
+/// This is a sealed trait, and should not need to be implemented. Open an
+/// issue if you know a use-case where this restrition should be lifted!
+pub unsafe trait IntoConcreteBlockOnce<A: BlockArguments>:
+    private::SealedOnce<A> + Sized
+{
+    /// The return type of the resulting `ConcreteBlock`.
+    type Output: EncodeReturn;
+
+    #[doc(hidden)]
+    fn __into_concrete_block_once(self) -> ConcreteBlock<A, Self::Output, Cell<Option<Self>>>;
+}
+
 macro_rules! concrete_block_impl {
     ($f:ident) => (
         concrete_block_impl!($f,);
@@ -64,9 +112,83 @@ macro_rules! concrete_block_impl {
                 unsafe { ConcreteBlock::with_invoke(f, self) }
             }
         }
+
+        impl<$($t: Encode,)* R: EncodeReturn, X> private::SealedMut<($($t,)*)> for X
+        where
+            X: FnMut($($t,)*) -> R,
+        {}
+
+        unsafe impl<$($t: Encode,)* R: EncodeReturn, X> IntoConcreteBlockMut<($($t,)*)> for X
+        where
+            X: FnMut($($t,)*) -> R,
+        {
+            type Output = R;
+
+            fn __into_concrete_block_mut(self) -> ConcreteBlock<($($t,)*), R, RefCell<X>> {
+                extern "C" fn $f<$($t,)* R, X>(
+                    block: &ConcreteBlock<($($t,)*), R, RefCell<X>>,
+                    $($a: $t,)*
+                ) -> R
+                where
+                    X: FnMut($($t,)*) -> R,
+                {
+                    let mut closure = match block.closure.try_borrow_mut() {
+                        Ok(closure) => closure,
+                        Err(_) => abort("re-entrant call of block created with `ConcreteBlock::new_mut`"),
+                    };
+                    (&mut *closure)($($a),*)
+                }
+
+                let f: extern "C" fn(&ConcreteBlock<($($t,)*), R, RefCell<X>>, $($a: $t,)*) -> R = $f;
+                let f: unsafe extern "C" fn() = unsafe { mem::transmute(f) };
+                unsafe { ConcreteBlock::with_invoke(f, RefCell::new(self)) }
+            }
+        }
+
+        impl<$($t: Encode,)* R: EncodeReturn, X> private::SealedOnce<($($t,)*)> for X
+        where
+            X: FnOnce($($t,)*) -> R,
+        {}
+
+        unsafe impl<$($t: Encode,)* R: EncodeReturn, X> IntoConcreteBlockOnce<($($t,)*)> for X
+        where
+            X: FnOnce($($t,)*) -> R,
+        {
+            type Output = R;
+
+            fn __into_concrete_block_once(self) -> ConcreteBlock<($($t,)*), R, Cell<Option<X>>> {
+                extern "C" fn $f<$($t,)* R, X>(
+                    block: &ConcreteBlock<($($t,)*), R, Cell<Option<X>>>,
+                    $($a: $t,)*
+                ) -> R
+                where
+                    X: FnOnce($($t,)*) -> R,
+                {
+                    let closure = match block.closure.take() {
+                        Some(closure) => closure,
+                        None => abort("block created with `ConcreteBlock::new_once` was called more than once"),
+                    };
+                    closure($($a),*)
+                }
+
+                let f: extern "C" fn(&ConcreteBlock<($($t,)*), R, Cell<Option<X>>>, $($a: $t,)*) -> R = $f;
+                let f: unsafe extern "C" fn() = unsafe { mem::transmute(f) };
+                unsafe { ConcreteBlock::with_invoke(f, Cell::new(Some(self))) }
+            }
+        }
     );
 }
 
+/// Print the message and abort the process.
+///
+/// Used instead of panicking in the invoke functions, since those can't
+/// unwind.
+#[cold]
+fn abort(msg: &str) -> ! {
+    let _ = writeln!(std::io::stderr(), "{msg}");
+    std::process::abort()
+}
+
 concrete_block_impl!(concrete_block_invoke_args0);
 concrete_block_impl!(concrete_block_invoke_args1, a: A);
 concrete_block_impl!(concrete_block_invoke_args2, a: A, b: B);
@@ -167,6 +289,16 @@ pub struct ConcreteBlock<A, R, F> {
     pub(crate) closure: F,
 }
 
+// SAFETY: The block layout only contains pointers to static data (the
+// class and the descriptor), so the thread-safety of the block is entirely
+// determined by the closure.
+//
+// Calling the block only requires a shared reference, so the argument and
+// return types don't matter.
+unsafe impl<A, R, F: Send> Send for ConcreteBlock<A, R, F> {}
+// SAFETY: Same as above.
+unsafe impl<A, R, F: Sync> Sync for ConcreteBlock<A, R, F> {}
+
 unsafe impl<A: BlockArguments, R: EncodeReturn, F> RefEncode for ConcreteBlock<A, R, F> {
     const ENCODING_REF: Encoding = Encoding::Block;
 }
@@ -185,41 +317,116 @@ where
     }
 }
 
-impl<A, R, F> ConcreteBlock<A, R, F> {
-    // TODO: Use new ABI with BLOCK_HAS_SIGNATURE
+impl<A, R, F> ConcreteBlock<A, R, RefCell<F>>
+where
+    A: BlockArguments,
+    R: EncodeReturn,
+    F: IntoConcreteBlockMut<A, Output = R>,
+{
+    /// Constructs a `ConcreteBlock` with the given [`FnMut`] closure.
+    ///
+    /// This allows the closure to mutate its captured state without having
+    /// to wrap it in a [`Cell`] or [`RefCell`] yourself.
+    ///
+    ///
+    /// # Aborts
+    ///
+    /// The block aborts the process if it is called re-entrantly, i.e. if it
+    /// is called while it is already being called (since that would give out
+    /// two mutable references to the closure). Unwinding is not possible
+    /// here, since the block is called through an `extern "C"` function.
+    ///
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use block2::ConcreteBlock;
+    /// let mut counter = 0;
+    /// let block = ConcreteBlock::new_mut(move || {
+    ///     counter += 1;
+    ///     counter
+    /// });
+    /// assert_eq!(unsafe { block.call(()) }, 1);
+    /// assert_eq!(unsafe { block.call(()) }, 2);
+    /// ```
+    pub fn new_mut(closure: F) -> Self {
+        closure.__into_concrete_block_mut()
+    }
+}
+
+impl<A, R, F> ConcreteBlock<A, R, Cell<Option<F>>>
+where
+    A: BlockArguments,
+    R: EncodeReturn,
+    F: IntoConcreteBlockOnce<A, Output = R>,
+{
+    /// Constructs a `ConcreteBlock` with the given [`FnOnce`] closure.
+    ///
+    /// This is useful for completion handlers, which are called exactly
+    /// once, since it allows moving captured values out of the closure.
+    ///
+    ///
+    /// # Aborts
+    ///
+    /// The block aborts the process if it is called more than once.
+    ///
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use block2::ConcreteBlock;
+    /// let v = vec![1, 2, 3];
+    /// let block = ConcreteBlock::new_once(move || v.into_iter().sum::<i32>());
+    /// assert_eq!(unsafe { block.call(()) }, 6);
+    /// ```
+    pub fn new_once(closure: F) -> Self {
+        closure.__into_concrete_block_once()
+    }
+}
+
+impl<A: BlockArguments, R: EncodeReturn, F> ConcreteBlock<A, R, F> {
     const FLAGS: ffi::block_flags = if mem::needs_drop::<Self>() {
-        ffi::BLOCK_HAS_COPY_DISPOSE
+        ffi::BLOCK_HAS_COPY_DISPOSE | Block::<A, R>::SIGNATURE_FLAGS
     } else {
-        0
+        Block::<A, R>::SIGNATURE_FLAGS
     };
 
-    const DESCRIPTOR: ffi::Block_descriptor = ffi::Block_descriptor {
-        header: ffi::Block_descriptor_header {
-            reserved: 0,
-            size: mem::size_of::<Self>() as c_ulong,
-        },
-        copy: if mem::needs_drop::<Self>() {
-            Some(block_context_copy::<Self>)
-        } else {
-            None
-        },
-        dispose: if mem::needs_drop::<Self>() {
-            Some(block_context_dispose::<Self>)
-        } else {
-            None
-        },
+    const HEADER: ffi::Block_descriptor_header = ffi::Block_descriptor_header {
+        reserved: 0,
+        size: mem::size_of::<Self>() as c_ulong,
+    };
+
+    const DESCRIPTOR_BASIC: ffi::Block_descriptor_basic = ffi::Block_descriptor_basic {
+        header: Self::HEADER,
+        encoding: Block::<A, R>::SIGNATURE_PTR,
+    };
+
+    const DESCRIPTOR_WITH_COPY_DISPOSE: ffi::Block_descriptor_with_signature =
+        ffi::Block_descriptor_with_signature {
+            header: Self::HEADER,
+            copy: Some(block_context_copy::<Self>),
+            dispose: Some(block_context_dispose::<Self>),
+            encoding: Block::<A, R>::SIGNATURE_PTR,
+        };
+
+    const DESCRIPTOR: *const c_void = if mem::needs_drop::<Self>() {
+        let ptr: *const ffi::Block_descriptor_with_signature = &Self::DESCRIPTOR_WITH_COPY_DISPOSE;
+        ptr.cast()
+    } else {
+        let ptr: *const ffi::Block_descriptor_basic = &Self::DESCRIPTOR_BASIC;
+        ptr.cast()
     };
 
     /// Constructs a `ConcreteBlock` with the given invoke function and closure.
     /// Unsafe because the caller must ensure the invoke function takes the
     /// correct arguments.
-    unsafe fn with_invoke(invoke: unsafe extern "C" fn(), closure: F) -> Self {
+    pub(crate) unsafe fn with_invoke(invoke: unsafe extern "C" fn(), closure: F) -> Self {
         let layout = ffi::Block_layout {
             isa: unsafe { &ffi::_NSConcreteStackBlock },
             flags: Self::FLAGS,
             reserved: 0,
             invoke: Some(invoke),
-            descriptor: &Self::DESCRIPTOR as *const ffi::Block_descriptor as *mut c_void,
+            descriptor: Self::DESCRIPTOR,
         };
         Self {
             p: PhantomData,
@@ -241,7 +448,22 @@ impl<A, R, F: 'static> ConcreteBlock<A, R, F> {
     }
 }
 
-impl<A, R, F: Clone> Clone for ConcreteBlock<A, R, F> {
+impl<A, R, F: Send + Sync + 'static> ConcreteBlock<A, R, F> {
+    /// Copy self onto the heap as a [`SendBlock`], which can be sent to and
+    /// called from other threads.
+    ///
+    /// Since the block may be called concurrently from several threads, the
+    /// closure must be both [`Send`] and [`Sync`]. This means that blocks
+    /// created with [`ConcreteBlock::new_mut`] or [`ConcreteBlock::new_once`]
+    /// cannot be made thread-safe this way.
+    pub fn copy_send(self) -> SendBlock<A, R> {
+        // SAFETY: The closure is `Send + Sync`, and so is the rest of the
+        // block.
+        unsafe { SendBlock::new(self.copy()) }
+    }
+}
+
+impl<A: BlockArguments, R: EncodeReturn, F: Clone> Clone for ConcreteBlock<A, R, F> {
     fn clone(&self) -> Self {
         unsafe { Self::with_invoke(self.layout.invoke.unwrap(), self.closure.clone()) }
     }
@@ -4,7 +4,7 @@ use core::fmt::{Debug, DebugStruct, Error, Formatter};
 use core::ptr;
 use std::ffi::CStr;
 
-use crate::{ffi, Block, ConcreteBlock, GlobalBlock, RcBlock};
+use crate::{ffi, Block, ConcreteBlock, GlobalBlock, RcBlock, SendBlock, StackBlock};
 
 #[derive(Clone, Copy, PartialEq, Eq)]
 struct Isa(*const ffi::Class);
@@ -71,6 +71,16 @@ impl<A, R> Debug for RcBlock<A, R> {
     }
 }
 
+impl<A, R> Debug for SendBlock<A, R> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        let mut f = f.debug_struct("SendBlock");
+        let ptr: *const Block<A, R> = &**self;
+        let layout = unsafe { ptr.cast::<ffi::Block_layout>().as_ref().unwrap() };
+        debug_block_layout(layout, &mut f);
+        f.finish_non_exhaustive()
+    }
+}
+
 impl<A, R, F: Debug> Debug for ConcreteBlock<A, R, F> {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
         let mut f = f.debug_struct("ConcreteBlock");
@@ -80,6 +90,16 @@ impl<A, R, F: Debug> Debug for ConcreteBlock<A, R, F> {
     }
 }
 
+impl<'a, A, R> Debug for StackBlock<'a, A, R> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        let mut f = f.debug_struct("StackBlock");
+        let ptr: *const Block<A, R> = &**self;
+        let layout = unsafe { ptr.cast::<ffi::Block_layout>().as_ref().unwrap() };
+        debug_block_layout(layout, &mut f);
+        f.finish_non_exhaustive()
+    }
+}
+
 impl<A, R> Debug for GlobalBlock<A, R> {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
         let mut f = f.debug_struct("GlobalBlock");
@@ -10,12 +10,6 @@ use objc2::encode::__unstable::EncodeReturn;
 use super::{ffi, Block};
 use crate::BlockArguments;
 
-// TODO: Should this be a static to help the compiler deduplicating them?
-const GLOBAL_DESCRIPTOR: ffi::Block_descriptor_header = ffi::Block_descriptor_header {
-    reserved: 0,
-    size: mem::size_of::<ffi::Block_layout>() as c_ulong,
-};
-
 /// An Objective-C block that does not capture its environment.
 ///
 /// This is effectively a glorified function pointer, and can created and
@@ -45,14 +39,21 @@ where
 {
 }
 
-// Note: We can't put correct bounds on A and R because we have a const fn!
-//
-// Fortunately, we don't need them, since they're present on `Sync`, so
-// constructing the static in `global_block!` with an invalid `GlobalBlock`
-// triggers an error.
-impl<A, R> GlobalBlock<A, R> {
-    // TODO: Use new ABI with BLOCK_HAS_SIGNATURE
-    const FLAGS: ffi::block_flags = ffi::BLOCK_IS_GLOBAL | ffi::BLOCK_USE_STRET;
+impl<A, R> GlobalBlock<A, R>
+where
+    A: BlockArguments,
+    R: EncodeReturn,
+{
+    const FLAGS: ffi::block_flags = ffi::BLOCK_IS_GLOBAL | Block::<A, R>::SIGNATURE_FLAGS;
+
+    // TODO: Should this be a static to help the compiler deduplicating them?
+    const DESCRIPTOR: ffi::Block_descriptor_basic = ffi::Block_descriptor_basic {
+        header: ffi::Block_descriptor_header {
+            reserved: 0,
+            size: mem::size_of::<ffi::Block_layout>() as c_ulong,
+        },
+        encoding: Block::<A, R>::SIGNATURE_PTR,
+    };
 
     #[doc(hidden)]
     pub const __DEFAULT_LAYOUT: ffi::Block_layout = ffi::Block_layout {
@@ -62,9 +63,16 @@ impl<A, R> GlobalBlock<A, R> {
         reserved: 0,
         // Populated in `global_block!`
         invoke: None,
-        descriptor: &GLOBAL_DESCRIPTOR as *const ffi::Block_descriptor_header as *mut c_void,
+        descriptor: &Self::DESCRIPTOR as *const ffi::Block_descriptor_basic as *mut c_void,
     };
+}
 
+// Note: We can't put correct bounds on A and R because we have a const fn!
+//
+// Fortunately, we don't need them, since they're present on `Sync`, so
+// constructing the static in `global_block!` with an invalid `GlobalBlock`
+// triggers an error.
+impl<A, R> GlobalBlock<A, R> {
     /// Use the [`global_block`] macro instead.
     #[doc(hidden)]
     pub const unsafe fn from_layout(layout: ffi::Block_layout) -> Self {
@@ -190,6 +198,7 @@ macro_rules! global_block {
 mod tests {
     use super::*;
     use alloc::format;
+    use objc2::encode::{Encode, Encoding};
 
     global_block! {
         /// Test comments and visibility
@@ -204,6 +213,25 @@ mod tests {
         };
     }
 
+    #[repr(C)]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    struct Large {
+        a: u64,
+        b: u64,
+        c: u64,
+    }
+
+    unsafe impl Encode for Large {
+        const ENCODING: Encoding =
+            Encoding::Struct("Large", &[u64::ENCODING, u64::ENCODING, u64::ENCODING]);
+    }
+
+    global_block! {
+        static LARGE_BLOCK = || -> Large {
+            Large { a: 1, b: 2, c: 3 }
+        };
+    }
+
     #[test]
     fn test_noop_block() {
         unsafe { NOOP_BLOCK.call(()) };
@@ -219,7 +247,7 @@ mod tests {
 
     #[cfg(feature = "apple")]
     const DEBUG_BLOCKFLAGS: &str = r#"BlockFlags {
-        value: "00110000000000000000000000000000",
+        value: "01010000000000000000000000000000",
         deallocating: false,
         inline_layout_string: false,
         small_descriptor: false,
@@ -229,8 +257,8 @@ mod tests {
         has_ctor: false,
         is_gc: false,
         is_global: true,
-        use_stret: true,
-        has_signature: false,
+        use_stret: false,
+        has_signature: true,
         has_extended_layout: false,
         over_referenced: false,
         reference_count: 0,
@@ -239,17 +267,45 @@ mod tests {
 
     #[cfg(not(feature = "apple"))]
     const DEBUG_BLOCKFLAGS: &str = r#"BlockFlags {
-        value: "00110000000000000000000000000000",
+        value: "01010000000000000000000000000000",
         has_copy_dispose: false,
         has_ctor: false,
         is_global: true,
-        use_stret: true,
-        has_signature: false,
+        use_stret: false,
+        has_signature: true,
         over_referenced: false,
         reference_count: 0,
         ..
     }"#;
 
+    #[test]
+    fn test_signature() {
+        assert_eq!(NOOP_BLOCK.signature(), Some("v@?"));
+        NOOP_BLOCK.verify().unwrap();
+        assert_eq!(BLOCK.signature(), Some("i@?iiii"));
+        BLOCK.verify().unwrap();
+    }
+
+    #[test]
+    fn test_use_stret() {
+        assert_eq!(NOOP_BLOCK.layout.flags & ffi::BLOCK_USE_STRET, 0);
+        assert_eq!(BLOCK.layout.flags & ffi::BLOCK_USE_STRET, 0);
+
+        let expected = if cfg!(any(
+            target_arch = "x86_64",
+            target_arch = "x86",
+            target_arch = "arm"
+        )) {
+            ffi::BLOCK_USE_STRET
+        } else {
+            0
+        };
+        assert_eq!(LARGE_BLOCK.layout.flags & ffi::BLOCK_USE_STRET, expected);
+        assert_eq!(LARGE_BLOCK.signature(), Some("{Large=QQQ}@?"));
+        LARGE_BLOCK.verify().unwrap();
+        assert_eq!(unsafe { LARGE_BLOCK.call(()) }, Large { a: 1, b: 2, c: 3 });
+    }
+
     #[test]
     fn test_debug() {
         let invoke = NOOP_BLOCK.layout.invoke.unwrap();
@@ -265,6 +321,9 @@ mod tests {
     descriptor: BlockDescriptor {{
         reserved: 0,
         size: {size},
+        encoding: Some(
+            \"v@?\",
+        ),
     }},
     ..
 }}"
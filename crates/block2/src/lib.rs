@@ -58,6 +58,27 @@
 //!
 //! [`copy`]: ConcreteBlock::copy
 //!
+//! Closures that mutate their environment can be turned into blocks with
+//! [`ConcreteBlock::new_mut`], and closures that may only be called once
+//! (such as completion handlers that consume their captured values) can use
+//! [`ConcreteBlock::new_once`].
+//!
+//! An [`RcBlock`] doesn't know anything about the closure it was created
+//! from, and can therefore not be sent to other threads. If the closure is
+//! [`Send`] and [`Sync`], you can use [`ConcreteBlock::copy_send`] to get a
+//! [`SendBlock`] instead, which can be passed to APIs that call the block on
+//! a background queue.
+//!
+//! If the block is only used for the duration of a call (e.g. because the
+//! method's block parameter is marked `NS_NOESCAPE`), you can instead use
+//! [`StackBlock`], which borrows the closure and therefore doesn't require
+//! it to be `'static`.
+//!
+//! Storage that is shared between a block and its creator (like variables
+//! declared with `__block` in C) can be created with [`BlockByRef`]. Objects
+//! can be captured weakly (like `__block __weak` variables) with
+//! [`BlockWeak`], which is useful for avoiding reference cycles.
+//!
 //! As an optimization if your block doesn't capture any variables, you can
 //! use the [`global_block!`] macro to create a static block:
 //!
@@ -71,6 +92,11 @@
 //! assert_eq!(unsafe { MY_BLOCK.call(()) }, 10.0);
 //! ```
 //!
+//! ## Closures as methods
+//!
+//! Closures can also be used as the implementation of Objective-C methods,
+//! see [`ClassBuilderExt`] and [`AnyClassExt`].
+//!
 //! [lang]: https://clang.llvm.org/docs/BlockLanguageSpec.html
 //! [ABI]: http://clang.llvm.org/docs/Block-ABI-Apple.html
 
@@ -98,12 +124,24 @@ extern "C" {}
 pub use block_sys as ffi;
 
 mod block;
+mod byref;
 mod concrete_block;
 mod debug;
 mod global;
+mod method;
 mod rc_block;
+mod signature;
+mod stack_block;
+mod weak;
 
 pub use block::{Block, BlockArguments};
-pub use concrete_block::{ConcreteBlock, IntoConcreteBlock};
+pub use byref::BlockByRef;
+pub use concrete_block::{
+    ConcreteBlock, IntoConcreteBlock, IntoConcreteBlockMut, IntoConcreteBlockOnce,
+};
 pub use global::GlobalBlock;
-pub use rc_block::RcBlock;
+pub use method::{AnyClassExt, ClassBuilderExt, MethodClosure};
+pub use rc_block::{RcBlock, SendBlock};
+pub use signature::VerificationError;
+pub use stack_block::StackBlock;
+pub use weak::BlockWeak;
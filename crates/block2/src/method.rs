@@ -0,0 +1,474 @@
+//! Using closures as Objective-C method implementations.
+//!
+//! This is built upon `imp_implementationWithBlock`, which creates a method
+//! implementation that calls the block with the receiver as the first
+//! argument, followed by the method's arguments (the selector is not passed
+//! to the block).
+//!
+//! The runtime needs to know whether the block returns its value through a
+//! hidden pointer, since the method implementation must then pass that
+//! pointer before the receiver. Apple's runtime and GNUstep's libobjc2 both
+//! determine this from the block's `BLOCK_USE_STRET` flag, which is only
+//! valid if the block also has a type signature; this relies on
+//! [`ConcreteBlock`] always setting both correctly.
+use core::mem;
+
+use objc2::declare::ClassBuilder;
+use objc2::encode::__unstable::EncodeReturn;
+use objc2::encode::{Encode, Encoding, RefEncode};
+use objc2::runtime::{AnyClass, Imp, Sel};
+use objc2::{ffi, Message};
+
+use crate::ConcreteBlock;
+
+mod private {
+    pub trait Sealed<T: ?Sized, A> {}
+
+    pub trait SealedExt {}
+}
+
+/// Closures that may be used as the implementation of an Objective-C method
+/// on objects of type `T`.
+///
+/// This is implemented for [`Fn`] closures of up to 12 arguments (excluding
+/// the receiver), where the first argument is `&T`, and each subsequent
+/// argument and the return type implements [`Encode`].
+///
+/// Since the method may be called from any thread, the closure must be
+/// [`Send`] and [`Sync`]. Once the method has been added, the closure is
+/// never dropped, since the class keeps the method alive for the remainder
+/// of the program.
+///
+///
+/// # Safety
+///
+/// This is a sealed trait, and should not need to be implemented. Open an
+/// issue if you know a use-case where this restrition should be lifted!
+pub unsafe trait MethodClosure<T: ?Sized, A>: private::Sealed<T, A> + Sized {
+    #[doc(hidden)]
+    const __ARGS: &'static [Encoding];
+
+    #[doc(hidden)]
+    const __RET: Encoding;
+
+    #[doc(hidden)]
+    fn __into_imp(self) -> Imp;
+}
+
+macro_rules! method_closure_impl {
+    ($f:ident) => (
+        method_closure_impl!($f,);
+    );
+    ($f:ident, $($a:ident : $t:ident),*) => (
+        impl<T, $($t: Encode,)* R: EncodeReturn, X> private::Sealed<T, ($($t,)*)> for X
+        where
+            T: RefEncode + ?Sized,
+            X: Fn(&T, $($t,)*) -> R + Send + Sync + 'static,
+        {}
+
+        unsafe impl<T, $($t: Encode,)* R: EncodeReturn, X> MethodClosure<T, ($($t,)*)> for X
+        where
+            T: RefEncode + ?Sized,
+            X: Fn(&T, $($t,)*) -> R + Send + Sync + 'static,
+        {
+            const __ARGS: &'static [Encoding] = &[$($t::ENCODING),*];
+
+            const __RET: Encoding = R::ENCODING_RETURN;
+
+            fn __into_imp(self) -> Imp {
+                extern "C" fn $f<T, $($t,)* R, X>(
+                    block: &ConcreteBlock<(*const T, $($t,)*), R, X>,
+                    this: &T,
+                    $($a: $t,)*
+                ) -> R
+                where
+                    T: ?Sized,
+                    X: Fn(&T, $($t,)*) -> R,
+                {
+                    (block.closure)(this, $($a),*)
+                }
+
+                let f: extern "C" fn(&ConcreteBlock<(*const T, $($t,)*), R, X>, &T, $($a: $t,)*) -> R = $f;
+                let f: unsafe extern "C" fn() = unsafe { mem::transmute(f) };
+                let block: ConcreteBlock<(*const T, $($t,)*), R, X> =
+                    unsafe { ConcreteBlock::with_invoke(f, self) };
+                let block = block.copy();
+
+                // SAFETY: The block is valid, and takes the receiver as the
+                // first argument.
+                //
+                // The runtime copies the block, so we can release our
+                // reference to it afterwards.
+                let imp = unsafe { ffi::imp_implementationWithBlock(block.ptr.cast()) };
+                imp.expect("failed creating method implementation from block")
+            }
+        }
+    );
+}
+
+method_closure_impl!(method_closure_invoke_args0);
+method_closure_impl!(method_closure_invoke_args1, a: A);
+method_closure_impl!(method_closure_invoke_args2, a: A, b: B);
+method_closure_impl!(method_closure_invoke_args3, a: A, b: B, c: C);
+method_closure_impl!(method_closure_invoke_args4, a: A, b: B, c: C, d: D);
+method_closure_impl!(method_closure_invoke_args5, a: A, b: B, c: C, d: D, e: E);
+method_closure_impl!(
+    method_closure_invoke_args6,
+    a: A,
+    b: B,
+    c: C,
+    d: D,
+    e: E,
+    f: F
+);
+method_closure_impl!(
+    method_closure_invoke_args7,
+    a: A,
+    b: B,
+    c: C,
+    d: D,
+    e: E,
+    f: F,
+    g: G
+);
+method_closure_impl!(
+    method_closure_invoke_args8,
+    a: A,
+    b: B,
+    c: C,
+    d: D,
+    e: E,
+    f: F,
+    g: G,
+    h: H
+);
+method_closure_impl!(
+    method_closure_invoke_args9,
+    a: A,
+    b: B,
+    c: C,
+    d: D,
+    e: E,
+    f: F,
+    g: G,
+    h: H,
+    i: I
+);
+method_closure_impl!(
+    method_closure_invoke_args10,
+    a: A,
+    b: B,
+    c: C,
+    d: D,
+    e: E,
+    f: F,
+    g: G,
+    h: H,
+    i: I,
+    j: J
+);
+method_closure_impl!(
+    method_closure_invoke_args11,
+    a: A,
+    b: B,
+    c: C,
+    d: D,
+    e: E,
+    f: F,
+    g: G,
+    h: H,
+    i: I,
+    j: J,
+    k: K
+);
+method_closure_impl!(
+    method_closure_invoke_args12,
+    a: A,
+    b: B,
+    c: C,
+    d: D,
+    e: E,
+    f: F,
+    g: G,
+    h: H,
+    i: I,
+    j: J,
+    k: K,
+    l: L
+);
+
+/// Create the implementation for a closure that is about to be added as a
+/// method.
+///
+/// The number of arguments is checked first, such that we don't create the
+/// block only to leak it when the check fails.
+fn create_imp<T: ?Sized, A, F: MethodClosure<T, A>>(sel: Sel, closure: F) -> ImpGuard {
+    let sel_args = sel.name().bytes().filter(|&b| b == b':').count();
+    assert_eq!(
+        sel_args,
+        F::__ARGS.len(),
+        "selector {sel} accepts {sel_args} arguments, but function accepts {}",
+        F::__ARGS.len(),
+    );
+    ImpGuard(closure.__into_imp())
+}
+
+/// Releases the block backing an implementation, unless it ended up being
+/// used (e.g. if adding the method failed or panicked).
+struct ImpGuard(Imp);
+
+impl ImpGuard {
+    fn used(self) {
+        mem::forget(self);
+    }
+}
+
+impl Drop for ImpGuard {
+    fn drop(&mut self) {
+        // SAFETY: The implementation was created with
+        // `imp_implementationWithBlock`, and is not referenced anywhere else.
+        let _ = unsafe { ffi::imp_removeBlock(Some(self.0)) };
+    }
+}
+
+/// Extension methods on [`ClassBuilder`] for declaring methods whose
+/// implementation is a Rust closure.
+///
+/// Unlike `extern "C" fn`s, closures can capture state, which is useful for
+/// e.g. creating mock classes at runtime.
+///
+///
+/// # Example
+///
+/// ```
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// use std::sync::Arc;
+///
+/// use block2::ClassBuilderExt;
+/// use objc2::declare::ClassBuilder;
+/// use objc2::rc::Id;
+/// use objc2::runtime::{AnyObject, NSObject};
+/// use objc2::{msg_send, msg_send_id, sel, ClassType};
+///
+/// let counter = Arc::new(AtomicUsize::new(0));
+///
+/// let mut builder = ClassBuilder::new("ClosureMethodExample", NSObject::class()).unwrap();
+/// let captured = counter.clone();
+/// unsafe {
+///     builder.add_method_with_closure(sel!(incrementBy:), move |_this: &AnyObject, n: usize| {
+///         captured.fetch_add(n, Ordering::Relaxed)
+///     });
+/// }
+/// let cls = builder.register();
+///
+/// let obj: Id<AnyObject> = unsafe { msg_send_id![cls, new] };
+/// let previous: usize = unsafe { msg_send![&obj, incrementBy: 5usize] };
+/// assert_eq!(previous, 0);
+/// assert_eq!(counter.load(Ordering::Relaxed), 5);
+/// ```
+pub trait ClassBuilderExt: private::SealedExt {
+    /// Adds a method with the given name, implemented by the given closure.
+    ///
+    /// The closure takes the receiver as its first argument, followed by the
+    /// method's arguments.
+    ///
+    /// See [`ClassBuilder::add_method`] for details.
+    ///
+    ///
+    /// # Panics
+    ///
+    /// Panics in the same cases as [`ClassBuilder::add_method`], including
+    /// if the number of arguments of the selector and the closure does not
+    /// match.
+    ///
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that the types match those that are expected
+    /// when the method is invoked from Objective-C.
+    #[doc(alias = "imp_implementationWithBlock")]
+    unsafe fn add_method_with_closure<T, A, F>(&mut self, sel: Sel, closure: F)
+    where
+        T: Message + ?Sized,
+        F: MethodClosure<T, A>;
+
+    /// Adds a class method with the given name, implemented by the given
+    /// closure.
+    ///
+    /// See [`add_method_with_closure`][Self::add_method_with_closure] for
+    /// details.
+    ///
+    ///
+    /// # Panics
+    ///
+    /// Panics in the same cases as [`ClassBuilder::add_class_method`].
+    ///
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that the types match those that are expected
+    /// when the method is invoked from Objective-C.
+    #[doc(alias = "imp_implementationWithBlock")]
+    unsafe fn add_class_method_with_closure<A, F>(&mut self, sel: Sel, closure: F)
+    where
+        F: MethodClosure<AnyClass, A>;
+}
+
+impl private::SealedExt for ClassBuilder {}
+
+impl ClassBuilderExt for ClassBuilder {
+    unsafe fn add_method_with_closure<T, A, F>(&mut self, sel: Sel, closure: F)
+    where
+        T: Message + ?Sized,
+        F: MethodClosure<T, A>,
+    {
+        let imp = create_imp(sel, closure);
+        unsafe { self.__add_method_inner(sel, F::__ARGS, &F::__RET, imp.0) };
+        imp.used();
+    }
+
+    unsafe fn add_class_method_with_closure<A, F>(&mut self, sel: Sel, closure: F)
+    where
+        F: MethodClosure<AnyClass, A>,
+    {
+        let imp = create_imp(sel, closure);
+        unsafe { self.__add_class_method_inner(sel, F::__ARGS, &F::__RET, imp.0) };
+        imp.used();
+    }
+}
+
+/// Extension methods on [`AnyClass`] for adding or replacing methods on
+/// already registered classes with a Rust closure.
+///
+/// See [`ClassBuilderExt`] for declaring such methods on new classes.
+pub trait AnyClassExt: private::SealedExt {
+    /// Adds a method with the given name, implemented by the given closure,
+    /// to an already registered class.
+    ///
+    /// Returns `false` if the class itself already contains a method with
+    /// that name, in which case the closure is dropped.
+    ///
+    /// See [`AnyClass::add_method`] for details.
+    ///
+    ///
+    /// # Panics
+    ///
+    /// Panics in the same cases as [`AnyClass::add_method`].
+    ///
+    ///
+    /// # Safety
+    ///
+    /// Same as [`AnyClass::add_method`].
+    #[doc(alias = "imp_implementationWithBlock")]
+    unsafe fn add_method_with_closure<T, A, F>(&self, sel: Sel, closure: F) -> bool
+    where
+        T: Message + ?Sized,
+        F: MethodClosure<T, A>;
+
+    /// Adds a class method with the given name, implemented by the given
+    /// closure, to an already registered class.
+    ///
+    /// See [`add_method_with_closure`][Self::add_method_with_closure] for
+    /// details.
+    ///
+    ///
+    /// # Safety
+    ///
+    /// Same as [`AnyClass::add_class_method`].
+    #[doc(alias = "imp_implementationWithBlock")]
+    unsafe fn add_class_method_with_closure<A, F>(&self, sel: Sel, closure: F) -> bool
+    where
+        F: MethodClosure<AnyClass, A>;
+
+    /// Replaces the implementation of the method with the given name with
+    /// the given closure.
+    ///
+    /// Returns the previous implementation, if the class itself contained
+    /// the method. See [`AnyClass::replace_method`] for details.
+    ///
+    ///
+    /// # Panics
+    ///
+    /// Panics in the same cases as [`AnyClass::replace_method`].
+    ///
+    ///
+    /// # Safety
+    ///
+    /// Same as [`AnyClass::replace_method`].
+    #[doc(alias = "imp_implementationWithBlock")]
+    unsafe fn replace_method_with_closure<T, A, F>(&self, sel: Sel, closure: F) -> Option<Imp>
+    where
+        T: Message + ?Sized,
+        F: MethodClosure<T, A>;
+
+    /// Replaces the implementation of the class method with the given name
+    /// with the given closure.
+    ///
+    /// See [`replace_method_with_closure`][Self::replace_method_with_closure]
+    /// for details.
+    ///
+    ///
+    /// # Safety
+    ///
+    /// Same as [`AnyClass::replace_class_method`].
+    #[doc(alias = "imp_implementationWithBlock")]
+    unsafe fn replace_class_method_with_closure<A, F>(&self, sel: Sel, closure: F) -> Option<Imp>
+    where
+        F: MethodClosure<AnyClass, A>;
+}
+
+impl private::SealedExt for AnyClass {}
+
+impl AnyClassExt for AnyClass {
+    unsafe fn add_method_with_closure<T, A, F>(&self, sel: Sel, closure: F) -> bool
+    where
+        T: Message + ?Sized,
+        F: MethodClosure<T, A>,
+    {
+        let imp = create_imp(sel, closure);
+        let success = unsafe { self.__add_method_inner(sel, F::__ARGS, &F::__RET, imp.0) };
+        if success {
+            imp.used();
+        }
+        success
+    }
+
+    unsafe fn add_class_method_with_closure<A, F>(&self, sel: Sel, closure: F) -> bool
+    where
+        F: MethodClosure<AnyClass, A>,
+    {
+        let imp = create_imp(sel, closure);
+        let success = unsafe {
+            self.metaclass()
+                .__add_method_inner(sel, F::__ARGS, &F::__RET, imp.0)
+        };
+        if success {
+            imp.used();
+        }
+        success
+    }
+
+    unsafe fn replace_method_with_closure<T, A, F>(&self, sel: Sel, closure: F) -> Option<Imp>
+    where
+        T: Message + ?Sized,
+        F: MethodClosure<T, A>,
+    {
+        let imp = create_imp(sel, closure);
+        let previous = unsafe { self.__replace_method_inner(sel, F::__ARGS, &F::__RET, imp.0) };
+        imp.used();
+        previous
+    }
+
+    unsafe fn replace_class_method_with_closure<A, F>(&self, sel: Sel, closure: F) -> Option<Imp>
+    where
+        F: MethodClosure<AnyClass, A>,
+    {
+        let imp = create_imp(sel, closure);
+        let previous = unsafe {
+            self.metaclass()
+                .__replace_method_inner(sel, F::__ARGS, &F::__RET, imp.0)
+        };
+        imp.used();
+        previous
+    }
+}
@@ -1,6 +1,10 @@
+use core::mem::ManuallyDrop;
 use core::ops::Deref;
 
-use crate::{ffi, Block};
+use objc2::encode::__unstable::EncodeReturn;
+use objc2::rc::Id;
+
+use crate::{ffi, Block, BlockArguments};
 
 /// A reference-counted Objective-C block.
 pub struct RcBlock<A, R> {
@@ -57,3 +61,107 @@ impl<A, R> Drop for RcBlock<A, R> {
         unsafe { ffi::_Block_release(self.ptr.cast()) };
     }
 }
+
+impl<A: BlockArguments, R: EncodeReturn> From<RcBlock<A, R>> for Id<Block<A, R>> {
+    /// Convert the block into an [`Id`], to use it where an Objective-C
+    /// object is expected.
+    fn from(block: RcBlock<A, R>) -> Self {
+        let block = ManuallyDrop::new(block);
+        // SAFETY: The block is a valid heap block with a +1 reference count,
+        // and releasing it with `objc_release` is equivalent to releasing it
+        // with `_Block_release`.
+        unsafe { Id::new(block.ptr) }.expect("RcBlock pointer was NULL")
+    }
+}
+
+impl<A: BlockArguments, R: EncodeReturn> From<Id<Block<A, R>>> for RcBlock<A, R> {
+    /// Convert a block stored as an Objective-C object back into an
+    /// `RcBlock`.
+    ///
+    /// This copies the block with `_Block_copy`, which only increments the
+    /// reference count of heap blocks, but ensures that the result never
+    /// points to a block on the stack (retaining a stack block with
+    /// `objc_retain` does not move it to the heap).
+    fn from(block: Id<Block<A, R>>) -> Self {
+        let ptr: *const Block<A, R> = Id::as_ptr(&block);
+        // SAFETY: The `Id` points to a valid block. The `Id` itself is
+        // released afterwards, which is balanced by the copy.
+        unsafe { Self::copy(ptr as *mut Block<A, R>) }
+    }
+}
+
+/// A reference-counted Objective-C block that may be sent to, and called
+/// from, other threads.
+///
+/// This is an [`RcBlock`] whose closure is known to be [`Send`] and
+/// [`Sync`], and is useful for passing callbacks to APIs that invoke them on
+/// a background queue.
+///
+/// Create one with [`ConcreteBlock::copy_send`].
+///
+/// [`ConcreteBlock::copy_send`]: crate::ConcreteBlock::copy_send
+///
+///
+/// # Example
+///
+/// ```
+/// use std::thread;
+/// use block2::ConcreteBlock;
+///
+/// let block = ConcreteBlock::new(|a: i32, b: i32| a + b).copy_send();
+/// let res = thread::spawn(move || unsafe { block.call((5, 8)) });
+/// assert_eq!(res.join().unwrap(), 13);
+/// ```
+pub struct SendBlock<A, R> {
+    block: RcBlock<A, R>,
+}
+
+// SAFETY: The closure (and hence all data reachable from the block) is
+// `Send` and `Sync`, and the reference-counting done by the blocks runtime is
+// thread-safe.
+unsafe impl<A, R> Send for SendBlock<A, R> {}
+// SAFETY: Same as above.
+unsafe impl<A, R> Sync for SendBlock<A, R> {}
+
+impl<A, R> SendBlock<A, R> {
+    /// Construct a `SendBlock` from the given reference-counted block.
+    ///
+    /// This can be used for blocks created in C or Objective-C that are
+    /// known to be thread-safe.
+    ///
+    ///
+    /// # Safety
+    ///
+    /// The block must be safe to call from, and release on, any thread, even
+    /// concurrently. Any variables it captures must be thread-safe.
+    pub unsafe fn new(block: RcBlock<A, R>) -> Self {
+        Self { block }
+    }
+
+    /// Get the underlying [`RcBlock`], forgetting that it is thread-safe.
+    pub fn into_rc_block(self) -> RcBlock<A, R> {
+        self.block
+    }
+}
+
+impl<A, R> Clone for SendBlock<A, R> {
+    fn clone(&self) -> Self {
+        Self {
+            block: self.block.clone(),
+        }
+    }
+}
+
+impl<A, R> Deref for SendBlock<A, R> {
+    type Target = Block<A, R>;
+
+    fn deref(&self) -> &Block<A, R> {
+        &self.block
+    }
+}
+
+impl<A, R> From<SendBlock<A, R>> for RcBlock<A, R> {
+    fn from(block: SendBlock<A, R>) -> Self {
+        block.into_rc_block()
+    }
+}
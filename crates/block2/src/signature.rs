@@ -0,0 +1,282 @@
+//! Type signatures of blocks.
+//!
+//! The signature of a block is the encoding of the return type, followed by
+//! the encoding of the block itself (`@?`), followed by the encodings of each
+//! argument. Clang additionally emits the stack offset of each of these,
+//! which we do not.
+use core::fmt;
+use std::error::Error;
+
+use objc2::encode::{Encoding, EncodingBox, ParseError};
+
+/// The maximum length of the signatures we emit, including the trailing NUL
+/// byte.
+///
+/// Creating a block whose signature would be longer than this fails to
+/// compile.
+pub(crate) const MAX_LEN: usize = 256;
+
+/// Append the string representation of the encoding to the buffer.
+const fn push(mut buf: [u8; MAX_LEN], len: usize, encoding: &Encoding) -> ([u8; MAX_LEN], usize) {
+    let encoding_len = encoding.__str_len();
+    // Leave room for the NUL byte
+    if MAX_LEN <= len + encoding_len {
+        panic!("the signature of the block is too long (at most 255 bytes are supported)");
+    }
+    let s = encoding.__str_array::<MAX_LEN>();
+    let mut i = 0;
+    while i < encoding_len {
+        buf[len + i] = s[i];
+        i += 1;
+    }
+    (buf, len + encoding_len)
+}
+
+/// Build the NUL-terminated signature of a block with the given return and
+/// argument types.
+///
+/// # Panics
+///
+/// Panics if the signature is longer than [`MAX_LEN`]. This is evaluated at
+/// compile time, so that results in a compilation error.
+pub(crate) const fn signature(ret: &Encoding, args: &[Encoding]) -> [u8; MAX_LEN] {
+    let (buf, len) = push([0; MAX_LEN], 0, ret);
+    let (mut buf, mut len) = push(buf, len, &Encoding::Block);
+    let mut i = 0;
+    while i < args.len() {
+        (buf, len) = push(buf, len, &args[i]);
+        i += 1;
+    }
+    buf
+}
+
+#[derive(Debug, PartialEq, Eq, Hash)]
+enum Inner {
+    MissingSignature,
+    ParseError(ParseError),
+    MismatchedReturn(EncodingBox, Encoding),
+    InvalidBlockArgument(EncodingBox),
+    MismatchedArgumentsCount(usize, usize),
+    MismatchedArgument(usize, EncodingBox, Encoding),
+}
+
+impl fmt::Display for Inner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingSignature => write!(f, "block has no signature"),
+            Self::ParseError(e) => write!(f, "{e}"),
+            Self::MismatchedReturn(expected, actual) => {
+                write!(
+                    f,
+                    "expected return to have type code '{expected}', but found '{actual}'",
+                )
+            }
+            Self::InvalidBlockArgument(found) => {
+                write!(f, "expected block argument '@?', but found '{found}'")
+            }
+            Self::MismatchedArgumentsCount(expected, actual) => {
+                write!(f, "expected {expected} arguments, but {actual} were given",)
+            }
+            Self::MismatchedArgument(i, expected, actual) => {
+                write!(
+                    f,
+                    "expected argument at index {i} to have type code '{expected}', but found '{actual}'",
+                )
+            }
+        }
+    }
+}
+
+/// Failed verifying the signature of a block.
+///
+/// This is returned in the error case of [`Block::verify`], see that for
+/// details.
+///
+/// This implements [`Error`], and a description of the error can be retrieved
+/// using [`fmt::Display`].
+///
+/// [`Block::verify`]: crate::Block::verify
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct VerificationError(Inner);
+
+impl From<Inner> for VerificationError {
+    fn from(inner: Inner) -> Self {
+        Self(inner)
+    }
+}
+
+impl fmt::Display for VerificationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Delegate to inner
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl Error for VerificationError {}
+
+/// Parse the next encoding in the signature, and skip the stack offset
+/// following it (if any).
+fn next_encoding(s: &mut &str) -> Option<Result<EncodingBox, VerificationError>> {
+    if s.is_empty() {
+        return None;
+    }
+    let res = EncodingBox::from_start_of_str(s).map_err(|e| Inner::ParseError(e).into());
+    skip_extended_info(s);
+    *s = s.trim_start_matches(|c: char| c == '-' || c.is_ascii_digit());
+    Some(res)
+}
+
+/// Skip the extended type information that Clang emits in block signatures,
+/// e.g. the class name in `@"NSString"`, or the signature in `@?<v@?>`.
+fn skip_extended_info(s: &mut &str) {
+    if let Some(rest) = s.strip_prefix('"') {
+        if let Some((_, rest)) = rest.split_once('"') {
+            *s = rest;
+        }
+    } else if s.starts_with('<') {
+        let mut depth = 0;
+        for (i, c) in s.char_indices() {
+            match c {
+                '<' => depth += 1,
+                '>' => depth -= 1,
+                _ => {}
+            }
+            if depth == 0 {
+                *s = &s[i + 1..];
+                return;
+            }
+        }
+    }
+}
+
+pub(crate) fn verify_signature(
+    signature: Option<&str>,
+    args: &[Encoding],
+    ret: &Encoding,
+) -> Result<(), VerificationError> {
+    let mut s = signature.ok_or(Inner::MissingSignature)?;
+
+    let expected = next_encoding(&mut s).ok_or(Inner::MissingSignature)??;
+    if !ret.equivalent_to_box(&expected) {
+        return Err(Inner::MismatchedReturn(expected, ret.clone()).into());
+    }
+
+    match next_encoding(&mut s) {
+        Some(res) => {
+            let found = res?;
+            if !Encoding::Block.equivalent_to_box(&found) {
+                return Err(Inner::InvalidBlockArgument(found).into());
+            }
+        }
+        None => return Err(Inner::MismatchedArgumentsCount(0, args.len()).into()),
+    }
+
+    let actual_count = args.len();
+
+    for (i, actual) in args.iter().enumerate() {
+        if let Some(res) = next_encoding(&mut s) {
+            let expected = res?;
+            if !actual.equivalent_to_box(&expected) {
+                return Err(Inner::MismatchedArgument(i, expected, actual.clone()).into());
+            }
+        } else {
+            return Err(Inner::MismatchedArgumentsCount(i, actual_count).into());
+        }
+    }
+
+    let mut remaining = 0;
+    while let Some(res) = next_encoding(&mut s) {
+        res?;
+        remaining += 1;
+    }
+    if remaining != 0 {
+        return Err(Inner::MismatchedArgumentsCount(actual_count + remaining, actual_count).into());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::{String, ToString};
+    use alloc::vec;
+
+    fn signature_str(ret: &Encoding, args: &[Encoding]) -> String {
+        let buf = signature(ret, args);
+        let len = buf.iter().position(|&b| b == 0).unwrap();
+        core::str::from_utf8(&buf[..len]).unwrap().to_string()
+    }
+
+    #[test]
+    fn test_signature() {
+        assert_eq!(signature_str(&Encoding::Void, &[]), "v@?");
+        assert_eq!(
+            signature_str(&Encoding::Int, &[Encoding::Int, Encoding::Object]),
+            "i@?i@",
+        );
+        assert_eq!(
+            signature_str(
+                &Encoding::Struct("CGPoint", &[Encoding::Double, Encoding::Double]),
+                &[Encoding::Pointer(&Encoding::Struct(
+                    "CGPoint",
+                    &[Encoding::Double, Encoding::Double]
+                ))],
+            ),
+            "{CGPoint=dd}@?^{CGPoint=dd}",
+        );
+        // "v@?" + 252 * "@" + NUL
+        let longest = vec![Encoding::Object; MAX_LEN - 4];
+        assert_eq!(signature_str(&Encoding::Void, &longest).len(), MAX_LEN - 1);
+    }
+
+    #[test]
+    #[should_panic = "the signature of the block is too long"]
+    fn test_signature_too_long() {
+        let long = vec![Encoding::Object; MAX_LEN - 3];
+        let _ = signature(&Encoding::Void, &long);
+    }
+
+    #[test]
+    fn test_verify() {
+        let args = [Encoding::Int];
+        verify_signature(Some("i@?i"), &args, &Encoding::Int).unwrap();
+        verify_signature(Some("i12@?0i8"), &args, &Encoding::Int).unwrap();
+        verify_signature(Some("ri12@?0ri8"), &args, &Encoding::Int).unwrap();
+        let args = [Encoding::Object, Encoding::Block];
+        verify_signature(
+            Some("v24@?0@\"NSString\"8@?<v@?>16"),
+            &args,
+            &Encoding::Void,
+        )
+        .unwrap();
+        let args = [Encoding::Int];
+
+        let err = verify_signature(None, &args, &Encoding::Int).unwrap_err();
+        assert_eq!(err.to_string(), "block has no signature");
+
+        let err = verify_signature(Some("v@?i"), &args, &Encoding::Int).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "expected return to have type code 'v', but found 'i'"
+        );
+
+        let err = verify_signature(Some("i@i"), &args, &Encoding::Int).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "expected block argument '@?', but found '@'"
+        );
+
+        let err = verify_signature(Some("i@?f"), &args, &Encoding::Int).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "expected argument at index 0 to have type code 'f', but found 'i'"
+        );
+
+        let err = verify_signature(Some("i@?ii"), &args, &Encoding::Int).unwrap_err();
+        assert_eq!(err.to_string(), "expected 2 arguments, but 1 were given");
+
+        let err = verify_signature(Some("i@?"), &args, &Encoding::Int).unwrap_err();
+        assert_eq!(err.to_string(), "expected 0 arguments, but 1 were given");
+    }
+}
@@ -0,0 +1,122 @@
+use core::ffi::c_void;
+use core::marker::PhantomData;
+use core::ops::Deref;
+use core::ptr::NonNull;
+
+use objc2::encode::__unstable::EncodeReturn;
+use objc2::encode::{Encoding, RefEncode};
+
+use crate::{ffi, Block, BlockArguments, IntoConcreteBlock};
+
+/// An Objective-C block that borrows its closure, and which can only be used
+/// for the duration of a call.
+///
+/// This is useful for passing closures to methods whose block parameter is
+/// marked `NS_NOESCAPE` (such as `-[NSArray enumerateObjectsUsingBlock:]`),
+/// since the closure does not need to be `'static`, and no heap allocation
+/// is required.
+///
+/// The block is marked with `BLOCK_IS_NOESCAPE` (on Apple platforms), and
+/// cannot be copied to the heap from Rust. Since the closure is only
+/// borrowed, you must make sure that the block is not used by Objective-C
+/// after the call it was given to has returned.
+///
+///
+/// # Example
+///
+/// ```
+/// use block2::StackBlock;
+///
+/// let values = vec![1, 2, 3];
+/// let closure = |i: usize| values[i];
+/// let block = StackBlock::new(&closure);
+/// assert_eq!(unsafe { block.call((1,)) }, 2);
+/// ```
+#[repr(C)]
+pub struct StackBlock<'a, A, R> {
+    p: PhantomData<Block<A, R>>,
+    layout: ffi::Block_layout,
+    /// The closure, as `&'a F` with the type erased.
+    ///
+    /// Together with the layout, this has the same representation as a
+    /// `ConcreteBlock<A, R, &'a F>`, whose invoke function we reuse.
+    closure: NonNull<c_void>,
+    lifetime: PhantomData<&'a ()>,
+}
+
+unsafe impl<'a, A: BlockArguments, R: EncodeReturn> RefEncode for StackBlock<'a, A, R> {
+    const ENCODING_REF: Encoding = Encoding::Block;
+}
+
+impl<'a, A: BlockArguments, R: EncodeReturn> StackBlock<'a, A, R> {
+    /// Construct a `StackBlock` that calls the given closure.
+    pub fn new<F>(closure: &'a F) -> Self
+    where
+        &'a F: IntoConcreteBlock<A, Output = R>,
+    {
+        // A reference does not need to be dropped, so the block has no copy
+        // or dispose helpers.
+        #[allow(unused_mut)]
+        let mut layout = closure.__into_concrete_block().layout;
+        #[cfg(feature = "apple")]
+        {
+            layout.flags |= ffi::BLOCK_IS_NOESCAPE;
+        }
+        Self {
+            p: PhantomData,
+            layout,
+            closure: NonNull::from(closure).cast(),
+            lifetime: PhantomData,
+        }
+    }
+}
+
+impl<'a, A, R> Deref for StackBlock<'a, A, R> {
+    type Target = Block<A, R>;
+
+    fn deref(&self) -> &Self::Target {
+        let ptr: *const Self = self;
+        let ptr: *const Block<A, R> = ptr.cast();
+        // SAFETY: A `StackBlock` is a valid block.
+        unsafe { ptr.as_ref().unwrap_unchecked() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ConcreteBlock;
+    use alloc::string::ToString;
+    use core::cell::Cell;
+    use core::mem;
+
+    #[test]
+    fn test_layout() {
+        fn inner<F: Fn(i32) -> i32>(closure: &F) {
+            assert_eq!(
+                mem::size_of::<StackBlock<'_, (i32,), i32>>(),
+                mem::size_of::<ConcreteBlock<(i32,), i32, &F>>(),
+            );
+            let block = StackBlock::new(closure);
+            assert_eq!(block.layout.flags & ffi::BLOCK_HAS_COPY_DISPOSE, 0);
+            #[cfg(feature = "apple")]
+            assert_ne!(block.layout.flags & ffi::BLOCK_IS_NOESCAPE, 0);
+        }
+        inner(&|x| x + 1);
+    }
+
+    #[test]
+    fn test_borrowing() {
+        let s = "Hello!".to_string();
+        let count = Cell::new(0);
+        let closure = |x: i32| {
+            count.set(count.get() + 1);
+            s.len() as i32 + x
+        };
+        let block = StackBlock::new(&closure);
+        assert_eq!(unsafe { block.call((2,)) }, 8);
+        assert_eq!(unsafe { block.call((3,)) }, 9);
+        assert_eq!(count.get(), 2);
+        assert_eq!(block.signature(), Some("i@?i"));
+    }
+}
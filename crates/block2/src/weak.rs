@@ -0,0 +1,148 @@
+use core::ffi::c_void;
+use core::fmt;
+use core::marker::PhantomData;
+use core::ptr;
+
+use objc2::ffi::{objc_destroyWeak, objc_initWeak, objc_loadWeakRetained, objc_moveWeak};
+use objc2::mutability::{IsIdCloneable, IsRetainable};
+use objc2::rc::{Id, WeakId};
+use objc2::Message;
+
+use crate::byref::ByRefLayout;
+use crate::BlockByRef;
+
+/// The storage of a weak reference, managed with the `objc_XXXWeak`
+/// functions.
+type WeakSlot = *mut objc2::ffi::objc_object;
+
+/// Move the weak reference into the heap copy of the byref structure.
+unsafe extern "C" fn weak_keep(dst: *mut c_void, src: *mut c_void) {
+    let dst = dst.cast::<ByRefLayout<WeakSlot>>();
+    let src = src.cast::<ByRefLayout<WeakSlot>>();
+    // SAFETY: The source contains a weak reference (or NULL), and the
+    // destination is not yet registered with the runtime (though it may
+    // contain a bitwise copy of the source).
+    unsafe {
+        objc_moveWeak(
+            ptr::addr_of_mut!((*dst).value),
+            ptr::addr_of_mut!((*src).value),
+        )
+    };
+}
+
+/// Destroy the weak reference when the last reference to the byref
+/// structure is gone.
+unsafe extern "C" fn weak_destroy(byref: *mut c_void) {
+    let byref = byref.cast::<ByRefLayout<WeakSlot>>();
+    // SAFETY: The runtime only calls this once, on the heap copy.
+    unsafe { objc_destroyWeak(ptr::addr_of_mut!((*byref).value)) };
+}
+
+/// A weak reference to an Objective-C object, for capturing in blocks.
+///
+/// This has the same layout and copy semantics as a variable declared with
+/// `__block __weak` in Objective-C (with ARC), i.e. the weak reference lives
+/// in a reference-counted byref structure, which is moved between memory
+/// locations with `objc_moveWeak` and destroyed with `objc_destroyWeak`.
+/// Cloning a `BlockWeak` shares that structure, like [`BlockByRef`] does.
+///
+/// Capture this in a block instead of an [`Id`] to avoid the block keeping
+/// the object alive, e.g. when the object itself holds on to the block.
+///
+/// See also [`WeakId`], which is similar, but can't be shared with blocks
+/// created in C or Objective-C.
+///
+///
+/// # Example
+///
+/// ```
+/// use block2::{BlockWeak, ConcreteBlock};
+/// use objc2::runtime::NSObject;
+///
+/// let obj = NSObject::new();
+/// let weak = BlockWeak::from_id(&obj);
+/// let block = ConcreteBlock::new(move || match weak.load() {
+///     Some(_obj) => 1,
+///     None => 0,
+/// });
+/// let block = block.copy();
+///
+/// assert_eq!(unsafe { block.call(()) }, 1);
+/// drop(obj);
+/// assert_eq!(unsafe { block.call(()) }, 0);
+/// ```
+pub struct BlockWeak<T> {
+    byref: BlockByRef<WeakSlot>,
+    /// Same variance, dropck and marker traits as `WeakId<T>`.
+    item: PhantomData<WeakId<T>>,
+}
+
+impl<T: Message> BlockWeak<T> {
+    /// Construct a new weak reference to the given object.
+    #[doc(alias = "objc_initWeak")]
+    pub fn new(obj: &T) -> Self
+    where
+        T: IsRetainable,
+    {
+        // SAFETY: `obj` is retainable
+        unsafe { Self::new_inner(obj) }
+    }
+
+    /// Construct a new weak reference to the given [`Id`].
+    ///
+    /// You should prefer [`BlockWeak::new`] whenever the object is
+    /// retainable.
+    #[doc(alias = "objc_initWeak")]
+    pub fn from_id(obj: &Id<T>) -> Self
+    where
+        T: IsIdCloneable,
+    {
+        // SAFETY: `obj` is cloneable, and is known to have come from `Id`.
+        unsafe { Self::new_inner(Id::as_ptr(obj)) }
+    }
+
+    /// # Safety
+    ///
+    /// The object must be valid or null, and loading it again into an `Id`
+    /// must be safe.
+    unsafe fn new_inner(obj: *const T) -> Self {
+        // SAFETY: The helpers move and destroy a weak reference.
+        let byref = unsafe { BlockByRef::with_helpers(ptr::null_mut(), weak_keep, weak_destroy) };
+        // SAFETY: The byref structure is on the heap, and will not move for
+        // as long as the weak reference is registered. The caller verifies
+        // `obj`.
+        let _ = unsafe { objc_initWeak(byref.as_ptr(), (obj as *mut T).cast()) };
+        Self {
+            byref,
+            item: PhantomData,
+        }
+    }
+
+    /// Load the object into an [`Id`] if it still exists.
+    ///
+    /// Returns [`None`] if the object has been deallocated.
+    #[doc(alias = "objc_loadWeakRetained")]
+    pub fn load(&self) -> Option<Id<T>> {
+        // SAFETY: The slot contains a valid weak reference.
+        let obj = unsafe { objc_loadWeakRetained(self.byref.as_ptr()) };
+        // SAFETY: The object has +1 retain count
+        unsafe { Id::new(obj.cast()) }
+    }
+}
+
+impl<T> Clone for BlockWeak<T> {
+    /// Make another reference to the same weak reference.
+    fn clone(&self) -> Self {
+        Self {
+            byref: self.byref.clone(),
+            item: PhantomData,
+        }
+    }
+}
+
+impl<T> fmt::Debug for BlockWeak<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Like `WeakId`, don't try to print the object
+        write!(f, "(BlockWeak)")
+    }
+}
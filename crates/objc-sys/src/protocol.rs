@@ -88,8 +88,14 @@ extern_c! {
         is_instance_property: BOOL,
     ) -> *const objc_property;
 
-    // #[cfg(any(doc, macos >= 10.12))]
-    // protocol_copyPropertyList2
+    /// Available in macOS 10.12.
+    #[cfg(any(doc, not(objfw)))]
+    pub fn protocol_copyPropertyList2(
+        proto: *const objc_protocol,
+        out_len: *mut c_uint,
+        is_required_property: BOOL,
+        is_instance_property: BOOL,
+    ) -> *mut *const objc_property;
 
     // #[cfg(any(doc, gnustep))]
     // _protocol_getMethodTypeEncoding
@@ -1,7 +1,9 @@
 use core::fmt;
 
 use crate::helper::{compare_encodings, Helper, NestingLevel};
+use crate::layout::{size_and_align, PointerWidth};
 use crate::parse::Parser;
+use crate::static_str::{static_encoding_str_array, static_encoding_str_len};
 use crate::EncodingBox;
 
 /// An Objective-C type-encoding.
@@ -224,6 +226,56 @@ impl Encoding {
     pub fn equivalent_to_box(&self, other: &EncodingBox) -> bool {
         compare_encodings(self, NestingLevel::new(), other, NestingLevel::new(), false)
     }
+
+    /// The size of a value with this encoding on a target with the given
+    /// pointer width, following the C layout rules for structs, unions,
+    /// arrays and bitfields.
+    ///
+    /// Returns `None` if the size cannot be determined, e.g. for `void`,
+    /// `long double` (whose layout differs between targets), 8-byte types
+    /// like `double` on 32-bit targets (whose alignment differs between
+    /// `i686` and `armv7`), or structs whose fields are not part of the
+    /// encoding.
+    ///
+    /// This is similar to Foundation's `NSGetSizeAndAlignment`.
+    ///
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use objc2_encode::{Encoding, PointerWidth};
+    /// let enc = Encoding::Struct("CGPoint", &[Encoding::Double, Encoding::Double]);
+    /// assert_eq!(enc.size(PointerWidth::Bits64), Some(16));
+    /// assert_eq!(Encoding::Object.size(PointerWidth::Bits32), Some(4));
+    /// assert_eq!(Encoding::Void.size(PointerWidth::NATIVE), None);
+    /// ```
+    pub fn size(&self, width: PointerWidth) -> Option<usize> {
+        size_and_align(self, width).map(|(size, _)| size)
+    }
+
+    /// The alignment of a value with this encoding on a target with the given
+    /// pointer width.
+    ///
+    /// See [`Encoding::size`] for details.
+    pub fn align(&self, width: PointerWidth) -> Option<usize> {
+        size_and_align(self, width).map(|(_, align)| align)
+    }
+
+    /// The length of the string representation of this encoding.
+    ///
+    /// Used to build encoding strings at compile time.
+    #[doc(hidden)]
+    pub const fn __str_len(&self) -> usize {
+        static_encoding_str_len(self, NestingLevel::new())
+    }
+
+    /// The string representation of this encoding, padded with NUL bytes.
+    ///
+    /// `LEN` must be at least [`__str_len`][Self::__str_len].
+    #[doc(hidden)]
+    pub const fn __str_array<const LEN: usize>(&self) -> [u8; LEN] {
+        static_encoding_str_array(self, NestingLevel::new())
+    }
 }
 
 /// Formats this [`Encoding`] in a similar way that the `@encode` directive
@@ -242,7 +294,6 @@ impl fmt::Display for Encoding {
 mod tests {
     use super::*;
     use crate::helper::NestingLevel;
-    use crate::static_str::{static_encoding_str_array, static_encoding_str_len};
     use alloc::string::ToString;
     use alloc::vec;
     use core::str::FromStr;
@@ -5,6 +5,7 @@ use core::fmt;
 use core::str::FromStr;
 
 use crate::helper::{compare_encodings, Helper, NestingLevel};
+use crate::layout::{size_and_align, PointerWidth};
 use crate::parse::{ParseError, Parser};
 use crate::Encoding;
 
@@ -125,6 +126,16 @@ impl EncodingBox {
             }
         }
     }
+
+    /// Same as [`Encoding::size`].
+    pub fn size(&self, width: PointerWidth) -> Option<usize> {
+        size_and_align(self, width).map(|(size, _)| size)
+    }
+
+    /// Same as [`Encoding::align`].
+    pub fn align(&self, width: PointerWidth) -> Option<usize> {
+        size_and_align(self, width).map(|(_, align)| align)
+    }
 }
 
 /// Same formatting as [`Encoding`]'s `Display` implementation.
@@ -0,0 +1,286 @@
+use crate::helper::{
+    ContainerKind, EncodingType, Helper, IndirectionKind, NestingLevel, Primitive,
+};
+
+/// The pointer width of the target that a type-encoding's layout should be
+/// computed for.
+///
+/// See [`Encoding::size`][crate::Encoding::size].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum PointerWidth {
+    /// A 32-bit target, e.g. `i686-apple-darwin` or `armv7-apple-ios`.
+    Bits32,
+    /// A 64-bit target, e.g. `x86_64-apple-darwin` or `aarch64-apple-ios`.
+    Bits64,
+}
+
+impl PointerWidth {
+    /// The pointer width of the current target.
+    pub const NATIVE: Self = if cfg!(target_pointer_width = "64") {
+        Self::Bits64
+    } else {
+        Self::Bits32
+    };
+
+    const fn bytes(self) -> usize {
+        match self {
+            Self::Bits32 => 4,
+            Self::Bits64 => 8,
+        }
+    }
+}
+
+const fn round_up(value: usize, align: usize) -> Option<usize> {
+    match value.checked_add(align - 1) {
+        Some(value) => Some(value / align * align),
+        None => None,
+    }
+}
+
+/// The size and alignment of primitives, or `None` if they do not have a
+/// well-defined layout.
+fn primitive_layout(primitive: Primitive, width: PointerWidth) -> Option<(usize, usize)> {
+    use Primitive::*;
+    let ptr = width.bytes();
+    Some(match primitive {
+        Char | UChar | Bool => (1, 1),
+        Short | UShort => (2, 2),
+        // The runtime always treats `l` as a 32-bit quantity, see
+        // `Encoding::C_LONG`.
+        Int | UInt | Long | ULong | Float => (4, 4),
+        // The alignment of 8-byte types differs between 32-bit targets (it
+        // is 4 on `i686`, and 8 on `armv7`), so we cannot know it there.
+        LongLong | ULongLong | Double | DoubleComplex if width == PointerWidth::Bits32 => {
+            return None
+        }
+        LongLong | ULongLong | Double => (8, 8),
+        FloatComplex => (8, 4),
+        DoubleComplex => (16, 8),
+        String | Object | Block | Class | Sel => (ptr, ptr),
+        // The layout of `long double` differs between targets with the same
+        // pointer width (e.g. it is 8 bytes on `aarch64`, and 16 bytes on
+        // `x86_64`), so we cannot know it.
+        LongDouble | LongDoubleComplex => return None,
+        // `void` and functions are incomplete types.
+        Void | Unknown => return None,
+    })
+}
+
+/// Compute the size and alignment of the given encoding, following the
+/// C layout rules.
+pub(crate) fn size_and_align<E: EncodingType>(
+    enc: &E,
+    width: PointerWidth,
+) -> Option<(usize, usize)> {
+    // Layout never depends on what's behind a pointer, so we always want
+    // to see container fields.
+    match enc.helper(NestingLevel::new()) {
+        Helper::Primitive(primitive) => primitive_layout(primitive, width),
+        // A bitfield is only meaningful as a member of a container.
+        Helper::BitField(_, _, _) => None,
+        Helper::Indirection(IndirectionKind::Pointer, _, _) => Some((width.bytes(), width.bytes())),
+        Helper::Indirection(IndirectionKind::Atomic, t, _) => size_and_align(t, width),
+        Helper::Array(len, item, _) => {
+            let (size, align) = size_and_align(item, width)?;
+            let len = usize::try_from(len).ok()?;
+            Some((size.checked_mul(len)?, align))
+        }
+        Helper::Container(_, _, None, _) => None,
+        Helper::Container(ContainerKind::Struct, _, Some(fields), _) => {
+            struct_layout(fields, width)
+        }
+        Helper::Container(ContainerKind::Union, _, Some(members), _) => {
+            let mut size = 0;
+            let mut align = 1;
+            for member in members {
+                let (member_size, member_align) = match member.helper(NestingLevel::new()) {
+                    Helper::BitField(bits, ty, _) => {
+                        let (_, unit_align) = bitfield_unit(ty, width)?;
+                        ((bits as usize + 7) / 8, unit_align)
+                    }
+                    _ => size_and_align(member, width)?,
+                };
+                size = size.max(member_size);
+                align = align.max(member_align);
+            }
+            Some((round_up(size, align)?, align))
+        }
+    }
+}
+
+/// The size and alignment of the storage unit of a bitfield.
+///
+/// On GNUStep, bitfields carry their underlying type, and they are not
+/// allowed to straddle the boundary of that type. Otherwise, we don't know
+/// the type, and the bits are packed tightly.
+fn bitfield_unit<E: EncodingType>(
+    ty: Option<&(u64, E)>,
+    width: PointerWidth,
+) -> Option<(Option<usize>, usize)> {
+    match ty {
+        Some((_, ty)) => {
+            let (size, align) = size_and_align(ty, width)?;
+            Some((Some(size), align))
+        }
+        None => Some((None, 1)),
+    }
+}
+
+fn struct_layout<E: EncodingType>(fields: &[E], width: PointerWidth) -> Option<(usize, usize)> {
+    // Track the offset in bits, to allow packing bitfields.
+    let mut bits: usize = 0;
+    let mut align = 1;
+    for field in fields {
+        match field.helper(NestingLevel::new()) {
+            Helper::BitField(n, ty, _) => {
+                let n = n as usize;
+                let (unit_size, unit_align) = bitfield_unit(ty, width)?;
+                if let Some(unit_size) = unit_size {
+                    let unit_bits = unit_size.checked_mul(8)?;
+                    if n > unit_bits {
+                        return None;
+                    }
+                    if n == 0 {
+                        // A zero-width bitfield pads to the next unit
+                        bits = round_up(bits, unit_bits)?;
+                    } else if bits / unit_bits != (bits + n - 1) / unit_bits {
+                        // The bitfield would straddle a unit boundary
+                        bits = round_up(bits, unit_bits)?;
+                    }
+                }
+                bits = bits.checked_add(n)?;
+                align = align.max(unit_align);
+            }
+            _ => {
+                let (field_size, field_align) = size_and_align(field, width)?;
+                let offset = round_up(round_up(bits, 8)? / 8, field_align)?;
+                bits = offset.checked_add(field_size)?.checked_mul(8)?;
+                align = align.max(field_align);
+            }
+        }
+    }
+    let size = round_up(round_up(bits, 8)? / 8, align)?;
+    Some((size, align))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Encoding, EncodingBox};
+    use alloc::boxed::Box;
+    use alloc::vec;
+    use core::str::FromStr;
+
+    fn layout(s: &str, width: PointerWidth) -> Option<(usize, usize)> {
+        let enc = EncodingBox::from_str(s).unwrap();
+        let res = enc.size(width).zip(enc.align(width));
+        assert_eq!(size_and_align(&enc, width), res);
+        res
+    }
+
+    #[test]
+    fn primitives() {
+        use PointerWidth::*;
+        assert_eq!(Encoding::Char.size(Bits64), Some(1));
+        assert_eq!(Encoding::Int.size(Bits64), Some(4));
+        assert_eq!(Encoding::Long.size(Bits64), Some(4));
+        assert_eq!(Encoding::LongLong.size(Bits64), Some(8));
+        assert_eq!(Encoding::LongLong.align(Bits64), Some(8));
+        assert_eq!(Encoding::LongLong.align(Bits32), None);
+        assert_eq!(Encoding::Double.size(Bits32), None);
+        assert_eq!(Encoding::DoubleComplex.size(Bits32), None);
+        assert_eq!(Encoding::FloatComplex.size(Bits32), Some(8));
+        assert_eq!(Encoding::DoubleComplex.size(Bits64), Some(16));
+        assert_eq!(Encoding::DoubleComplex.align(Bits64), Some(8));
+        assert_eq!(Encoding::Object.size(Bits32), Some(4));
+        assert_eq!(Encoding::Object.size(Bits64), Some(8));
+        assert_eq!(Encoding::Pointer(&Encoding::Void).size(Bits64), Some(8));
+        assert_eq!(Encoding::Atomic(&Encoding::Short).size(Bits64), Some(2));
+
+        assert_eq!(Encoding::Void.size(Bits64), None);
+        assert_eq!(Encoding::Unknown.size(Bits64), None);
+        assert_eq!(Encoding::LongDouble.size(Bits64), None);
+        assert_eq!(Encoding::BitField(4, None).size(Bits64), None);
+    }
+
+    #[test]
+    fn native() {
+        use core::ffi::c_void;
+        use core::mem::{align_of, size_of};
+        let width = PointerWidth::NATIVE;
+        assert_eq!(Encoding::Sel.size(width), Some(size_of::<*const c_void>()));
+        assert_eq!(
+            Encoding::Sel.align(width),
+            Some(align_of::<*const c_void>())
+        );
+    }
+
+    #[test]
+    fn structs() {
+        use PointerWidth::*;
+        assert_eq!(layout("{CGPoint=dd}", Bits64), Some((16, 8)));
+        assert_eq!(layout("{CGPoint=ff}", Bits32), Some((8, 4)));
+        assert_eq!(layout("{CGPoint=dd}", Bits32), None);
+        assert_eq!(layout("{a=ci}", Bits64), Some((8, 4)));
+        assert_eq!(layout("{a=ic}", Bits64), Some((8, 4)));
+        assert_eq!(layout("{a=c@}", Bits64), Some((16, 8)));
+        assert_eq!(layout("{a=c@}", Bits32), Some((8, 4)));
+        assert_eq!(layout("{a=c{b=sc}c}", Bits64), Some((8, 2)));
+        assert_eq!(layout("{a=}", Bits64), Some((0, 1)));
+        // Fields behind pointers are not needed
+        assert_eq!(layout("^{a}", Bits64), Some((8, 8)));
+        assert_eq!(layout("{a}", Bits64), None);
+        assert_eq!(layout("{a=iv}", Bits64), None);
+    }
+
+    #[test]
+    fn unions_and_arrays() {
+        use PointerWidth::*;
+        assert_eq!(layout("(a=ci)", Bits64), Some((4, 4)));
+        assert_eq!(layout("(a=[5c]s)", Bits64), Some((6, 2)));
+        assert_eq!(layout("[10i]", Bits64), Some((40, 4)));
+        assert_eq!(layout("[3{a=ic}]", Bits64), Some((24, 4)));
+        assert_eq!(layout("[0i]", Bits64), Some((0, 4)));
+        assert_eq!(layout("{a=[2s]c}", Bits64), Some((6, 2)));
+    }
+
+    #[test]
+    fn bitfields() {
+        use PointerWidth::*;
+        assert_eq!(layout("{a=b1b7}", Bits64), Some((1, 1)));
+        assert_eq!(layout("{a=b1b8}", Bits64), Some((2, 1)));
+        assert_eq!(layout("{a=cb4b4c}", Bits64), Some((3, 1)));
+
+        // GNUStep-style bitfields don't straddle their underlying type.
+        const INT_BITS: Encoding = Encoding::Struct(
+            "a",
+            &[
+                Encoding::BitField(30, Some(&(0, Encoding::Int))),
+                Encoding::BitField(4, Some(&(32, Encoding::Int))),
+            ],
+        );
+        assert_eq!(INT_BITS.size(Bits64), Some(8));
+        assert_eq!(INT_BITS.align(Bits64), Some(4));
+
+        let char_bits = EncodingBox::Struct(
+            "a".into(),
+            Some(vec![
+                EncodingBox::Char,
+                EncodingBox::BitField(3, Some(Box::new((8, EncodingBox::Int)))),
+                EncodingBox::BitField(0, Some(Box::new((32, EncodingBox::Int)))),
+                EncodingBox::BitField(3, Some(Box::new((32, EncodingBox::Int)))),
+            ]),
+        );
+        assert_eq!(char_bits.size(Bits64), Some(8));
+        assert_eq!(char_bits.align(Bits64), Some(4));
+    }
+
+    #[test]
+    fn overflow() {
+        assert_eq!(
+            layout("[18446744073709551615q]", PointerWidth::Bits64),
+            None
+        );
+    }
+}
@@ -63,12 +63,12 @@ extern crate alloc;
 mod encoding;
 mod encoding_box;
 mod helper;
+mod layout;
 mod parse;
 
-// Will be used at some point when generic constants are available
-#[allow(dead_code)]
 mod static_str;
 
 pub use self::encoding::Encoding;
 pub use self::encoding_box::EncodingBox;
+pub use self::layout::PointerWidth;
 pub use self::parse::ParseError;
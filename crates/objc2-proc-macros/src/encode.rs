@@ -0,0 +1,467 @@
+//! Implementation of `#[derive(Encode)]` and `#[derive(RefEncode)]`.
+//!
+//! We don't depend on `syn`, so parsing is done by hand. Only the parts of
+//! the item that are needed for generating the implementation are parsed;
+//! the compiler will validate the rest.
+use proc_macro::{Delimiter, Group, Ident, Literal, Punct, Spacing, Span, TokenStream, TokenTree};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Derive {
+    Encode,
+    RefEncode,
+}
+
+impl Derive {
+    fn trait_name(self) -> &'static str {
+        match self {
+            Self::Encode => "Encode",
+            Self::RefEncode => "RefEncode",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Struct,
+    Union,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Repr {
+    C,
+    Transparent,
+}
+
+struct Error {
+    span: Span,
+    message: String,
+}
+
+impl Error {
+    fn new(span: Span, message: impl Into<String>) -> Self {
+        Self {
+            span,
+            message: message.into(),
+        }
+    }
+
+    /// Emit `compile_error!("...")` with the correct span.
+    fn into_compile_error(self) -> TokenStream {
+        let mut message = Literal::string(&self.message);
+        message.set_span(self.span);
+        let mut group = Group::new(Delimiter::Parenthesis, TokenTree::from(message).into());
+        group.set_span(self.span);
+        let mut bang = Punct::new('!', Spacing::Alone);
+        bang.set_span(self.span);
+        [
+            TokenTree::from(Ident::new("compile_error", self.span)),
+            TokenTree::from(bang),
+            TokenTree::from(group),
+            TokenTree::from(Punct::new(';', Spacing::Alone)),
+        ]
+        .into_iter()
+        .collect()
+    }
+}
+
+/// The parsed item.
+struct Item {
+    kind: Kind,
+    repr: Repr,
+    /// The name given to Objective-C, as a string literal.
+    encoding_name: String,
+    ident: Ident,
+    /// The generic parameters, without the angle brackets.
+    generics: Vec<TokenStream>,
+    /// The where-clause predicates, without the `where` keyword.
+    where_clause: TokenStream,
+    /// The type of each field.
+    fields: Vec<TokenStream>,
+}
+
+pub(crate) fn derive(input: TokenStream, derive: Derive) -> TokenStream {
+    match parse_item(input, derive).and_then(|item| generate(&item, derive)) {
+        Ok(output) => output.parse().unwrap(),
+        Err(err) => err.into_compile_error(),
+    }
+}
+
+fn is_punct(token: Option<&TokenTree>, c: char) -> bool {
+    matches!(token, Some(TokenTree::Punct(punct)) if punct.as_char() == c)
+}
+
+fn is_ident(token: Option<&TokenTree>, s: &str) -> bool {
+    matches!(token, Some(TokenTree::Ident(ident)) if ident.to_string() == s)
+}
+
+/// Tracks the nesting depth of angle brackets in a token stream.
+#[derive(Default)]
+struct AngleBrackets {
+    depth: usize,
+    prev_is_dash: bool,
+}
+
+impl AngleBrackets {
+    /// Update the depth with the next token.
+    fn next(&mut self, token: &TokenTree) {
+        let mut is_dash = false;
+        if let TokenTree::Punct(punct) = token {
+            match punct.as_char() {
+                '<' => self.depth += 1,
+                // Don't treat the `>` in `->` as a closing bracket
+                '>' if !self.prev_is_dash => self.depth = self.depth.saturating_sub(1),
+                '-' => is_dash = punct.spacing() == Spacing::Joint,
+                _ => {}
+            }
+        }
+        self.prev_is_dash = is_dash;
+    }
+
+    fn is_top_level(&self) -> bool {
+        self.depth == 0
+    }
+}
+
+/// Split a token stream by top-level commas, taking angle brackets into
+/// account.
+fn split_commas(stream: TokenStream) -> Vec<Vec<TokenTree>> {
+    let mut items = vec![];
+    let mut current = vec![];
+    let mut brackets = AngleBrackets::default();
+    for token in stream {
+        if is_punct(Some(&token), ',') && brackets.is_top_level() {
+            items.push(core::mem::take(&mut current));
+            continue;
+        }
+        brackets.next(&token);
+        current.push(token);
+    }
+    if !current.is_empty() {
+        items.push(current);
+    }
+    items
+}
+
+/// Skip outer attributes and visibility at the start of a field.
+fn skip_attributes_and_visibility(tokens: &[TokenTree]) -> &[TokenTree] {
+    let mut tokens = tokens;
+    while is_punct(tokens.first(), '#') {
+        tokens = &tokens[2..];
+    }
+    if is_ident(tokens.first(), "pub") {
+        tokens = &tokens[1..];
+        if let Some(TokenTree::Group(group)) = tokens.first() {
+            if group.delimiter() == Delimiter::Parenthesis {
+                tokens = &tokens[1..];
+            }
+        }
+    }
+    tokens
+}
+
+fn parse_fields(group: &Group, named: bool) -> Vec<TokenStream> {
+    split_commas(group.stream())
+        .into_iter()
+        .map(|field| {
+            let field = skip_attributes_and_visibility(&field);
+            // Skip `name:`
+            let ty = if named { &field[2..] } else { field };
+            ty.iter().cloned().collect()
+        })
+        .collect()
+}
+
+/// Parse the contents of a `#[repr(...)]` attribute.
+fn parse_repr(group: &Group, repr: &mut Option<Repr>, derive: Derive) -> Result<(), Error> {
+    for token in group.stream() {
+        if let TokenTree::Ident(ident) = token {
+            match &*ident.to_string() {
+                "C" => *repr = Some(Repr::C),
+                "transparent" => *repr = Some(Repr::Transparent),
+                // The encoding can't describe the missing padding
+                "packed" => {
+                    return Err(Error::new(
+                        ident.span(),
+                        format!(
+                            "`#[derive({})]` does not support `#[repr(packed)]`",
+                            derive.trait_name(),
+                        ),
+                    ))
+                }
+                _ => {}
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Parse the contents of an `#[encoding(name = "...")]` attribute.
+fn parse_encoding_attribute(group: &Group) -> Result<String, Error> {
+    let tokens: Vec<_> = group.stream().into_iter().collect();
+    match &*tokens {
+        [TokenTree::Ident(ident), TokenTree::Punct(eq), TokenTree::Literal(lit)]
+            if ident.to_string() == "name" && eq.as_char() == '=' =>
+        {
+            let name = lit.to_string();
+            if name.starts_with('"') {
+                Ok(name)
+            } else {
+                Err(Error::new(lit.span(), "expected a string literal"))
+            }
+        }
+        _ => Err(Error::new(
+            group.span(),
+            "expected `#[encoding(name = \"...\")]`",
+        )),
+    }
+}
+
+fn parse_item(input: TokenStream, derive: Derive) -> Result<Item, Error> {
+    let tokens: Vec<_> = input.into_iter().collect();
+    let mut tokens = &*tokens;
+
+    let mut repr = None;
+    let mut encoding_name = None;
+
+    // Attributes
+    while is_punct(tokens.first(), '#') {
+        if let Some(TokenTree::Group(attr)) = tokens.get(1) {
+            let mut inner = attr.stream().into_iter();
+            let name = inner.next();
+            if let Some(TokenTree::Group(args)) = inner.next() {
+                if is_ident(name.as_ref(), "repr") {
+                    parse_repr(&args, &mut repr, derive)?;
+                } else if is_ident(name.as_ref(), "encoding") {
+                    encoding_name = Some(parse_encoding_attribute(&args)?);
+                }
+            }
+        }
+        tokens = &tokens[2..];
+    }
+
+    tokens = skip_attributes_and_visibility(tokens);
+
+    let (kind, keyword_span) = match tokens.first() {
+        Some(TokenTree::Ident(ident)) if ident.to_string() == "struct" => {
+            (Kind::Struct, ident.span())
+        }
+        Some(TokenTree::Ident(ident)) if ident.to_string() == "union" => {
+            (Kind::Union, ident.span())
+        }
+        Some(token) => {
+            return Err(Error::new(
+                token.span(),
+                format!(
+                    "`#[derive({})]` is only supported on structs and unions",
+                    derive.trait_name(),
+                ),
+            ))
+        }
+        None => return Err(Error::new(Span::call_site(), "expected item")),
+    };
+
+    let repr = repr.ok_or_else(|| {
+        Error::new(
+            keyword_span,
+            format!(
+                "`#[derive({})]` requires `#[repr(C)]` or `#[repr(transparent)]`",
+                derive.trait_name(),
+            ),
+        )
+    })?;
+
+    let ident = match tokens.get(1) {
+        Some(TokenTree::Ident(ident)) => ident.clone(),
+        _ => return Err(Error::new(keyword_span, "expected identifier")),
+    };
+    tokens = &tokens[2..];
+
+    // Generics
+    let mut generics = vec![];
+    if is_punct(tokens.first(), '<') {
+        let mut brackets = AngleBrackets::default();
+        let mut end = 0;
+        for (i, token) in tokens.iter().enumerate() {
+            brackets.next(token);
+            if brackets.is_top_level() {
+                end = i;
+                break;
+            }
+        }
+        generics = split_commas(tokens[1..end].iter().cloned().collect())
+            .into_iter()
+            .map(|param| param.into_iter().collect())
+            .collect();
+        tokens = &tokens[end + 1..];
+    }
+
+    // Fields and where clause
+    let mut where_clause = TokenStream::new();
+    let mut fields = vec![];
+    let mut in_where_clause = false;
+    for token in tokens {
+        match token {
+            TokenTree::Ident(ident) if ident.to_string() == "where" => in_where_clause = true,
+            TokenTree::Group(group) if group.delimiter() == Delimiter::Brace => {
+                fields = parse_fields(group, true);
+                break;
+            }
+            TokenTree::Group(group)
+                if group.delimiter() == Delimiter::Parenthesis && !in_where_clause =>
+            {
+                fields = parse_fields(group, false);
+            }
+            TokenTree::Punct(punct) if punct.as_char() == ';' => break,
+            token if in_where_clause => where_clause.extend([token.clone()]),
+            _ => {}
+        }
+    }
+
+    Ok(Item {
+        kind,
+        repr,
+        encoding_name: encoding_name
+            .unwrap_or_else(|| Literal::string(&ident.to_string()).to_string()),
+        ident,
+        generics,
+        where_clause,
+        fields,
+    })
+}
+
+/// Whether the type is `PhantomData<...>`, and hence zero-sized.
+fn is_phantom_data(ty: &TokenStream) -> bool {
+    let mut last_ident = None;
+    for token in ty.clone() {
+        match token {
+            TokenTree::Ident(ident) => last_ident = Some(ident.to_string()),
+            TokenTree::Punct(punct) if punct.as_char() == '<' => break,
+            _ => {}
+        }
+    }
+    last_ident.as_deref() == Some("PhantomData")
+}
+
+/// Convert generic parameters to the arguments used when naming the type,
+/// e.g. `'a, T: Encode, const N: usize` to `'a, T, N`.
+fn generic_arguments(generics: &[TokenStream]) -> Vec<String> {
+    generics
+        .iter()
+        .map(|param| {
+            let tokens: Vec<_> = param.clone().into_iter().collect();
+            match &*tokens {
+                [TokenTree::Punct(punct), TokenTree::Ident(ident), ..]
+                    if punct.as_char() == '\'' =>
+                {
+                    format!("'{ident}")
+                }
+                [TokenTree::Ident(kw), TokenTree::Ident(ident), ..]
+                    if kw.to_string() == "const" =>
+                {
+                    ident.to_string()
+                }
+                [TokenTree::Ident(ident), ..] => ident.to_string(),
+                _ => param.to_string(),
+            }
+        })
+        .collect()
+}
+
+/// Remove default values from generic parameters, since they are not
+/// allowed on `impl` blocks.
+fn strip_default(param: &TokenStream) -> String {
+    let tokens: Vec<_> = param.clone().into_iter().collect();
+    let tokens = split_default(&tokens);
+    tokens.iter().cloned().collect::<TokenStream>().to_string()
+}
+
+fn split_default(tokens: &[TokenTree]) -> &[TokenTree] {
+    let mut brackets = AngleBrackets::default();
+    for (i, token) in tokens.iter().enumerate() {
+        if is_punct(Some(token), '=') && brackets.is_top_level() {
+            return &tokens[..i];
+        }
+        brackets.next(token);
+    }
+    tokens
+}
+
+fn generate(item: &Item, derive: Derive) -> Result<String, Error> {
+    let krate = "::objc2::encode";
+    let ident = &item.ident;
+
+    let impl_generics: Vec<_> = item.generics.iter().map(strip_default).collect();
+    let type_generics = generic_arguments(&item.generics);
+    let has_type_generics = item
+        .generics
+        .iter()
+        .any(|param| !matches!(param.clone().into_iter().next(), Some(TokenTree::Punct(_))));
+
+    let mut predicates = item.where_clause.to_string();
+    if !predicates.is_empty() && !predicates.trim_end().ends_with(',') {
+        predicates.push(',');
+    }
+
+    let body = match (item.repr, derive) {
+        (Repr::Transparent, _) => {
+            let mut candidates = item.fields.iter().filter(|ty| !is_phantom_data(ty));
+            let ty = match (candidates.next(), candidates.next()) {
+                (Some(ty), None) => ty.to_string(),
+                _ => {
+                    return Err(Error::new(
+                        ident.span(),
+                        "could not determine the non-zero-sized field of the `#[repr(transparent)]` type",
+                    ))
+                }
+            };
+            if has_type_generics {
+                predicates.push_str(&format!("{ty}: {krate}::{},", derive.trait_name()));
+            }
+            match derive {
+                Derive::Encode => {
+                    format!("const ENCODING: {krate}::Encoding = <{ty} as {krate}::Encode>::ENCODING;")
+                }
+                Derive::RefEncode => format!(
+                    "const ENCODING_REF: {krate}::Encoding = <{ty} as {krate}::RefEncode>::ENCODING_REF;"
+                ),
+            }
+        }
+        (Repr::C, Derive::Encode) => {
+            let variant = match item.kind {
+                Kind::Struct => "Struct",
+                Kind::Union => "Union",
+            };
+            let mut encodings = String::new();
+            for ty in &item.fields {
+                if has_type_generics {
+                    predicates.push_str(&format!("{ty}: {krate}::Encode,"));
+                }
+                encodings.push_str(&format!("<{ty} as {krate}::Encode>::ENCODING,"));
+            }
+            format!(
+                "const ENCODING: {krate}::Encoding = {krate}::Encoding::{variant}({}, &[{encodings}]);",
+                item.encoding_name,
+            )
+        }
+        (Repr::C, Derive::RefEncode) => {
+            if has_type_generics {
+                predicates.push_str(&format!("Self: {krate}::Encode,"));
+            }
+            format!(
+                "const ENCODING_REF: {krate}::Encoding = {krate}::Encoding::Pointer(&<Self as {krate}::Encode>::ENCODING);"
+            )
+        }
+    };
+
+    Ok(format!(
+        "
+        #[automatically_derived]
+        unsafe impl<{}> {krate}::{} for {ident}<{}> where {predicates} {{
+            {body}
+        }}
+        ",
+        impl_generics.join(", "),
+        derive.trait_name(),
+        type_generics.join(", "),
+    ))
+}
@@ -17,6 +17,8 @@
 #[doc = include_str!("../README.md")]
 extern "C" {}
 
+mod encode;
+
 use core::hash::{Hash, Hasher};
 
 use proc_macro::Ident;
@@ -72,3 +74,31 @@ pub fn __hash_idents(input: TokenStream) -> TokenStream {
     let s = format!("{:016x}", hasher.finish());
     TokenTree::Literal(Literal::string(&s)).into()
 }
+
+/// Derive the `Encode` trait for a `#[repr(C)]` struct or union, or a
+/// `#[repr(transparent)]` newtype.
+///
+/// The name that Objective-C sees defaults to the name of the type, and can
+/// be changed with `#[encoding(name = "...")]`.
+///
+/// See [`objc2::encode`] for details.
+///
+/// [`objc2::encode`]: https://docs.rs/objc2/latest/objc2/encode/index.html
+#[proc_macro_derive(Encode, attributes(encoding))]
+pub fn derive_encode(input: TokenStream) -> TokenStream {
+    encode::derive(input, encode::Derive::Encode)
+}
+
+/// Derive the `RefEncode` trait for a `#[repr(C)]` struct or union, or a
+/// `#[repr(transparent)]` newtype.
+///
+/// Structs and unions are encoded as a pointer to their `Encode`
+/// implementation, while newtypes delegate to the inner type.
+///
+/// See [`objc2::encode`] for details.
+///
+/// [`objc2::encode`]: https://docs.rs/objc2/latest/objc2/encode/index.html
+#[proc_macro_derive(RefEncode, attributes(encoding))]
+pub fn derive_ref_encode(input: TokenStream) -> TokenStream {
+    encode::derive(input, encode::Derive::RefEncode)
+}
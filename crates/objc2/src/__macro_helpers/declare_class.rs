@@ -1,4 +1,9 @@
+#[cfg(feature = "panic-to-exception")]
+use core::panic::AssertUnwindSafe;
+
 use crate::declare::__IdReturnValue;
+#[cfg(feature = "panic-to-exception")]
+use crate::exception::{throw, Exception};
 use crate::rc::{Allocated, Id};
 use crate::{ClassType, Message, MessageReceiver};
 
@@ -173,3 +178,30 @@ where
 {
     // Noop
 }
+
+/// Run the body of a method in `declare_class!`, and convert any panic that
+/// occurs into an Objective-C exception.
+///
+/// The exception is thrown from the method, which `declare_class!` declares
+/// as `extern "C-unwind"` when this feature is enabled, so that unwinding out
+/// of it into the Objective-C caller is allowed.
+#[cfg(feature = "panic-to-exception")]
+#[inline]
+pub fn convert_panics<R>(f: impl FnOnce() -> R) -> R {
+    match std::panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(value) => value,
+        Err(payload) => {
+            let exception = Exception::from_panic(&*payload);
+            drop(payload);
+            match exception {
+                // SAFETY: The method is `extern "C-unwind"`, so the
+                // exception may unwind out of it into the Objective-C
+                // caller, which is expected to handle it.
+                Some(exception) => unsafe { throw(exception) },
+                // We can't unwind out of the method, and without Foundation
+                // there is nothing sensible to throw.
+                None => std::process::abort(),
+            }
+        }
+    }
+}
@@ -30,13 +30,18 @@ pub use std::sync::Once;
 mod cache;
 mod common_selectors;
 mod declare_class;
+mod property;
 
 pub use self::cache::{CachedClass, CachedSel};
 pub use self::common_selectors::{alloc_sel, dealloc_sel, init_sel, new_sel};
+#[cfg(feature = "panic-to-exception")]
+pub use self::declare_class::convert_panics;
 pub use self::declare_class::{
     assert_mutability_matches_superclass_mutability, MaybeOptionId, MessageRecieveId,
     ValidSubclassMutability,
 };
+pub use self::property::{declare_property, ownership, PropertyIvar, ValidOwnership};
+pub use crate::message::__Variadic;
 
 /// Helper for specifying the retain semantics for a given selector family.
 ///
@@ -466,17 +471,17 @@ impl ClassBuilder {
                 builder: self,
                 protocol,
                 required_instance_methods: protocol
-                    .map(|p| p.method_descriptions(true))
+                    .map(|p| p.method_descriptions(true, true))
                     .unwrap_or_default(),
                 optional_instance_methods: protocol
-                    .map(|p| p.method_descriptions(false))
+                    .map(|p| p.method_descriptions(false, true))
                     .unwrap_or_default(),
                 registered_instance_methods: HashSet::new(),
                 required_class_methods: protocol
-                    .map(|p| p.class_method_descriptions(true))
+                    .map(|p| p.method_descriptions(true, false))
                     .unwrap_or_default(),
                 optional_class_methods: protocol
-                    .map(|p| p.class_method_descriptions(false))
+                    .map(|p| p.method_descriptions(false, false))
                     .unwrap_or_default(),
                 registered_class_methods: HashSet::new(),
             }
@@ -0,0 +1,502 @@
+//! Helpers for `#[property(...)]` in `declare_class!`.
+use alloc::format;
+use alloc::string::String;
+use core::mem::MaybeUninit;
+use core::ptr::{self, NonNull};
+use core::sync::atomic::{AtomicBool, Ordering};
+use std::io::Write;
+
+use crate::declare::{
+    ClassBuilder, CopyPropertyObject, InnerIvarType, IvarBool, IvarDrop, IvarEncode, IvarType,
+    PropertyObject,
+};
+use crate::encode::Encode;
+use crate::mutability::{IsIdCloneable, IsRetainable};
+use crate::rc::{Id, WeakId};
+use crate::runtime::{AnyObject, Bool, PropertyAttributes, PropertyOwnership, Sel};
+use crate::{msg_send_id, Message};
+
+/// Type-level versions of the ownership attributes of `#[property(...)]`,
+/// which allows rejecting invalid ownership at compile time.
+pub mod ownership {
+    use crate::runtime::PropertyOwnership;
+
+    /// An ownership attribute of a property.
+    pub trait Ownership {
+        /// The ownership, or `None` if the default should be used.
+        const OWNERSHIP: Option<PropertyOwnership>;
+    }
+
+    /// No ownership attribute was given.
+    pub struct Unspecified;
+
+    /// `#[property(name, retain)]`.
+    pub struct Retain;
+
+    /// `#[property(name, copy)]`.
+    pub struct Copy;
+
+    /// `#[property(name, weak)]`.
+    pub struct Weak;
+
+    impl Ownership for Unspecified {
+        const OWNERSHIP: Option<PropertyOwnership> = None;
+    }
+
+    impl Ownership for Retain {
+        const OWNERSHIP: Option<PropertyOwnership> = Some(PropertyOwnership::Retain);
+    }
+
+    impl Ownership for Copy {
+        const OWNERSHIP: Option<PropertyOwnership> = Some(PropertyOwnership::Copy);
+    }
+
+    impl Ownership for Weak {
+        const OWNERSHIP: Option<PropertyOwnership> = Some(PropertyOwnership::Weak);
+    }
+}
+
+use self::ownership::{Ownership, Retain, Unspecified, Weak};
+
+/// Instance variable types that can back a declared property.
+///
+/// The accessors work on the instance variable's storage directly, which
+/// is zero-initialized by the runtime when the object is allocated.
+///
+///
+/// # Safety
+///
+/// `Storage` must have the same layout as `Self`, and `Value` must be the
+/// type that Objective-C sees the property as. The accessors must follow the
+/// memory management rules of the ownership semantics that
+/// [`ValidOwnership`] is implemented for.
+pub unsafe trait PropertyIvar: InnerIvarType {
+    /// The type that the instance variable is stored as.
+    type Storage;
+
+    /// The value read from the instance variable, before it is converted
+    /// to `Value`.
+    type Loaded;
+
+    /// The type that the getter returns and the setter takes.
+    type Value: Encode;
+
+    /// The ownership used when none was specified.
+    const DEFAULT_OWNERSHIP: PropertyOwnership;
+
+    /// Read the instance variable.
+    ///
+    /// This is done while holding the lock for atomic properties, so it must
+    /// not call into user code.
+    ///
+    /// # Safety
+    ///
+    /// The storage must be valid for reads, and either zeroed or have been
+    /// written by the setter.
+    unsafe fn load(ivar: &Self::Storage) -> Self::Loaded;
+
+    /// Convert the loaded value to return it from the getter.
+    fn into_value(loaded: Self::Loaded) -> Self::Value;
+
+    /// Convert the value given to the setter to the value to store.
+    ///
+    /// # Safety
+    ///
+    /// The value must be valid for the property's type.
+    unsafe fn into_storage(value: Self::Value, copy: bool) -> Self::Storage;
+}
+
+/// Marks the ownership attributes that a property backed by `Self` supports.
+///
+/// # Safety
+///
+/// The [`PropertyIvar`] implementation must correctly implement the
+/// ownership semantics.
+pub unsafe trait ValidOwnership<O: Ownership>: PropertyIvar {}
+
+/// Types that are valid when zero-initialized, which is the state the
+/// instance variable starts out in.
+///
+///
+/// # Safety
+///
+/// The all-zeroes bit pattern must be a valid value of the type.
+pub unsafe trait ValidWhenZeroed {}
+
+macro_rules! valid_when_zeroed_impls {
+    ($($t:ty),*) => ($(
+        unsafe impl ValidWhenZeroed for $t {}
+    )*);
+}
+
+valid_when_zeroed_impls!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize, f32, f64, Bool);
+
+unsafe impl<T: ?Sized> ValidWhenZeroed for *const T {}
+unsafe impl<T: ?Sized> ValidWhenZeroed for *mut T {}
+// `None` is guaranteed to be represented as NULL in both of these.
+unsafe impl<T: ?Sized> ValidWhenZeroed for Option<NonNull<T>> {}
+unsafe impl<T: ?Sized> ValidWhenZeroed for Option<&T> {}
+
+unsafe impl<T: Encode + Copy + ValidWhenZeroed> PropertyIvar for IvarEncode<T> {
+    type Storage = MaybeUninit<T>;
+    type Loaded = T;
+    type Value = T;
+
+    const DEFAULT_OWNERSHIP: PropertyOwnership = PropertyOwnership::Assign;
+
+    unsafe fn load(ivar: &MaybeUninit<T>) -> T {
+        // SAFETY: The instance variable is either zeroed, which is valid
+        // because of `ValidWhenZeroed`, or has been written to.
+        unsafe { ivar.assume_init() }
+    }
+
+    fn into_value(loaded: T) -> T {
+        loaded
+    }
+
+    unsafe fn into_storage(value: T, _copy: bool) -> MaybeUninit<T> {
+        MaybeUninit::new(value)
+    }
+}
+
+unsafe impl<T: Encode + Copy + ValidWhenZeroed> ValidOwnership<Unspecified> for IvarEncode<T> {}
+
+unsafe impl PropertyIvar for IvarBool {
+    type Storage = bool;
+    type Loaded = bool;
+    type Value = Bool;
+
+    const DEFAULT_OWNERSHIP: PropertyOwnership = PropertyOwnership::Assign;
+
+    unsafe fn load(ivar: &bool) -> bool {
+        *ivar
+    }
+
+    fn into_value(loaded: bool) -> Bool {
+        Bool::new(loaded)
+    }
+
+    unsafe fn into_storage(value: Bool, _copy: bool) -> bool {
+        value.as_bool()
+    }
+}
+
+unsafe impl ValidOwnership<Unspecified> for IvarBool {}
+
+/// Retain the object stored in an instance variable, to return it from a
+/// getter.
+fn retain_ivar<T: PropertyObject>(ivar: &Id<T>) -> Id<T> {
+    // SAFETY: The object is valid, and `PropertyObject` ensures that it can
+    // be retained again.
+    let obj = unsafe { Id::retain(Id::as_ptr(ivar) as *mut T) };
+    // SAFETY: `objc_retain` always returns the same object pointer.
+    unsafe { obj.unwrap_unchecked() }
+}
+
+/// Retain or copy the object given to a setter.
+///
+/// # Safety
+///
+/// The pointer must be a valid object or NULL. If `copy` is set, the object's
+/// `copy` method must return an object of the same type.
+unsafe fn retain_or_copy<T: Message>(value: *mut T, copy: bool) -> Option<Id<T>> {
+    // SAFETY: Checked by caller
+    let value = unsafe { value.as_ref() }?;
+    if copy {
+        // SAFETY: Checked by caller
+        let copied: Option<Id<T>> = unsafe { msg_send_id![value, copy] };
+        match copied {
+            Some(copied) => Some(copied),
+            // We can't unwind out of the setter.
+            None => abort("`copy` returned nil in property setter"),
+        }
+    } else {
+        // SAFETY: The object is valid, and the caller ensures that it is
+        // not uniquely owned elsewhere.
+        unsafe { Id::retain(value as *const T as *mut T) }
+    }
+}
+
+// An `IvarDrop<Id<T>>` is stored as an `Option<Id<T>>`, which is `None`
+// until it has been initialized. The getter returns `nil` in that case.
+unsafe impl<T: PropertyObject> PropertyIvar for IvarDrop<Id<T>> {
+    type Storage = Option<Id<T>>;
+    type Loaded = Option<Id<T>>;
+    type Value = *mut T;
+
+    const DEFAULT_OWNERSHIP: PropertyOwnership = if T::REQUIRES_COPY {
+        PropertyOwnership::Copy
+    } else {
+        PropertyOwnership::Retain
+    };
+
+    unsafe fn load(ivar: &Option<Id<T>>) -> Option<Id<T>> {
+        ivar.as_ref().map(retain_ivar)
+    }
+
+    fn into_value(loaded: Option<Id<T>>) -> *mut T {
+        Id::autorelease_return_option(loaded)
+    }
+
+    unsafe fn into_storage(value: *mut T, copy: bool) -> Option<Id<T>> {
+        match unsafe { retain_or_copy(value, copy) } {
+            Some(value) => Some(value),
+            // We can't unwind out of the setter.
+            None => abort("tried to set non-optional property to nil"),
+        }
+    }
+}
+
+unsafe impl<T: PropertyObject> ValidOwnership<Unspecified> for IvarDrop<Id<T>> {}
+unsafe impl<T: CopyPropertyObject> ValidOwnership<ownership::Copy> for IvarDrop<Id<T>> {}
+unsafe impl<T: PropertyObject + IsIdCloneable> ValidOwnership<Retain> for IvarDrop<Id<T>> {}
+
+unsafe impl<T: PropertyObject> PropertyIvar for IvarDrop<Option<Id<T>>> {
+    type Storage = Option<Id<T>>;
+    type Loaded = Option<Id<T>>;
+    type Value = *mut T;
+
+    const DEFAULT_OWNERSHIP: PropertyOwnership = if T::REQUIRES_COPY {
+        PropertyOwnership::Copy
+    } else {
+        PropertyOwnership::Retain
+    };
+
+    unsafe fn load(ivar: &Option<Id<T>>) -> Option<Id<T>> {
+        ivar.as_ref().map(retain_ivar)
+    }
+
+    fn into_value(loaded: Option<Id<T>>) -> *mut T {
+        Id::autorelease_return_option(loaded)
+    }
+
+    unsafe fn into_storage(value: *mut T, copy: bool) -> Option<Id<T>> {
+        unsafe { retain_or_copy(value, copy) }
+    }
+}
+
+unsafe impl<T: PropertyObject> ValidOwnership<Unspecified> for IvarDrop<Option<Id<T>>> {}
+unsafe impl<T: CopyPropertyObject> ValidOwnership<ownership::Copy> for IvarDrop<Option<Id<T>>> {}
+unsafe impl<T: PropertyObject + IsIdCloneable> ValidOwnership<Retain> for IvarDrop<Option<Id<T>>> {}
+
+unsafe impl<T: Message + IsRetainable> PropertyIvar for IvarDrop<Option<WeakId<T>>> {
+    type Storage = Option<WeakId<T>>;
+    type Loaded = Option<Id<T>>;
+    type Value = *mut T;
+
+    const DEFAULT_OWNERSHIP: PropertyOwnership = PropertyOwnership::Weak;
+
+    unsafe fn load(ivar: &Option<WeakId<T>>) -> Option<Id<T>> {
+        ivar.as_ref().and_then(WeakId::load)
+    }
+
+    fn into_value(loaded: Option<Id<T>>) -> *mut T {
+        Id::autorelease_return_option(loaded)
+    }
+
+    unsafe fn into_storage(value: *mut T, _copy: bool) -> Option<WeakId<T>> {
+        // SAFETY: The pointer is a valid object or NULL.
+        unsafe { value.as_ref() }.map(WeakId::new)
+    }
+}
+
+unsafe impl<T: Message + IsRetainable> ValidOwnership<Unspecified> for IvarDrop<Option<WeakId<T>>> {}
+unsafe impl<T: Message + IsRetainable> ValidOwnership<Weak> for IvarDrop<Option<WeakId<T>>> {}
+
+/// Print the message and abort, since panicking in the accessors would
+/// unwind into Objective-C.
+#[cold]
+fn abort(msg: &str) -> ! {
+    let _ = writeln!(std::io::stderr(), "{msg}");
+    std::process::abort()
+}
+
+/// A spin lock used for atomic properties.
+///
+/// Similar to the Objective-C runtime, the locks are striped on the address
+/// of the instance variable. They are only held while loading or storing the
+/// instance variable, and never while running code that may call back into
+/// user code (such as releasing the previous value), so they can't deadlock.
+struct PropertyLock(AtomicBool);
+
+struct PropertyLockGuard(&'static PropertyLock);
+
+impl PropertyLock {
+    const STRIPE_COUNT: usize = 64;
+
+    fn lock<S>(ivar: NonNull<S>) -> PropertyLockGuard {
+        // Only used to initialize the array below.
+        #[allow(clippy::declare_interior_mutable_const)]
+        const UNLOCKED: PropertyLock = PropertyLock(AtomicBool::new(false));
+        static LOCKS: [PropertyLock; PropertyLock::STRIPE_COUNT] =
+            [UNLOCKED; PropertyLock::STRIPE_COUNT];
+
+        let addr = ivar.as_ptr() as usize;
+        let lock = &LOCKS[((addr >> 4) ^ (addr >> 9)) % Self::STRIPE_COUNT];
+        while lock
+            .0
+            .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            core::hint::spin_loop();
+        }
+        PropertyLockGuard(lock)
+    }
+}
+
+impl Drop for PropertyLockGuard {
+    fn drop(&mut self) {
+        self.0 .0.store(false, Ordering::Release);
+    }
+}
+
+/// # Safety
+///
+/// The object must be an instance of a class that has the instance variable
+/// described by `I`.
+unsafe fn ivar_ptr<I>(obj: &AnyObject) -> NonNull<<I::Type as PropertyIvar>::Storage>
+where
+    I: IvarType,
+    I::Type: PropertyIvar,
+{
+    let ptr = NonNull::from(obj);
+    // SAFETY: Upheld by caller
+    let offset = unsafe { I::__offset(ptr) };
+    // SAFETY: The offset is valid, and `PropertyIvar` guarantees that the
+    // storage has the same layout as the instance variable.
+    unsafe { AnyObject::ivar_at_offset::<I::Type>(ptr, offset) }.cast()
+}
+
+// The accessors are emitted with the same ABI as methods in
+// `declare_class!`, and convert panics in the same way.
+crate::__extern_method_fn! {
+    ()
+    (unsafe) fn getter[I, const ATOMIC: bool](
+        this: &AnyObject,
+        _cmd: Sel,
+    ) -> <I::Type as PropertyIvar>::Value
+    where [
+        I: IvarType,
+        I::Type: PropertyIvar,
+    ] {
+        crate::__convert_panics! {{
+            // SAFETY: The method is only registered on the class that
+            // declared the instance variable.
+            let ivar = unsafe { ivar_ptr::<I>(this) };
+            let guard = if ATOMIC {
+                Some(PropertyLock::lock(ivar))
+            } else {
+                None
+            };
+            // SAFETY: The instance variable is valid for reads, and
+            // `PropertyIvar` ensures that it is valid when zeroed. Writes
+            // from other setters are synchronized by the lock if the property
+            // is atomic.
+            let loaded = unsafe { <I::Type as PropertyIvar>::load(ivar.as_ref()) };
+            drop(guard);
+            <I::Type as PropertyIvar>::into_value(loaded)
+        }}
+    }
+}
+
+crate::__extern_method_fn! {
+    ()
+    (unsafe) fn setter[I, const ATOMIC: bool, const COPY: bool](
+        this: &AnyObject,
+        _cmd: Sel,
+        value: <I::Type as PropertyIvar>::Value,
+    )
+    where [
+        I: IvarType,
+        I::Type: PropertyIvar,
+    ] {
+        crate::__convert_panics! {{
+            // SAFETY: Objective-C upholds that the value is valid.
+            let new = unsafe { <I::Type as PropertyIvar>::into_storage(value, COPY) };
+            // SAFETY: Same as in `getter`.
+            let ivar = unsafe { ivar_ptr::<I>(this) };
+            let guard = if ATOMIC {
+                Some(PropertyLock::lock(ivar))
+            } else {
+                None
+            };
+            // SAFETY: The instance variable is valid for writes. We write
+            // through the raw pointer, since we only have shared access to
+            // the object.
+            let old = unsafe { ptr::replace(ivar.as_ptr(), new) };
+            drop(guard);
+            // Release the previous value after unlocking, since that may run
+            // arbitrary code.
+            drop(old);
+        }}
+    }
+}
+
+/// The default setter name, `setFoo:` for a property named `foo`.
+fn setter_name(name: &str) -> String {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => format!("set{}{}:", first.to_ascii_uppercase(), chars.as_str()),
+        None => String::from("set:"),
+    }
+}
+
+/// Register a property backed by the instance variable `I` with ownership
+/// `O`, along with its getter and (unless `readonly`) setter.
+pub fn declare_property<I, O>(
+    builder: &mut ClassBuilder,
+    name: &str,
+    mut attributes: PropertyAttributes<'_>,
+) where
+    I: IvarType,
+    I::Type: ValidOwnership<O>,
+    O: Ownership,
+{
+    attributes.ownership = O::OWNERSHIP.unwrap_or(<I::Type as PropertyIvar>::DEFAULT_OWNERSHIP);
+    attributes.ivar = Some(I::NAME);
+
+    let atomic = !attributes.nonatomic;
+    let copy = attributes.ownership == PropertyOwnership::Copy;
+    let readonly = attributes.readonly;
+
+    builder.add_property::<<I::Type as PropertyIvar>::Value>(name, attributes);
+
+    let getter_sel = Sel::register(name);
+    let setter_sel = Sel::register(&setter_name(name));
+
+    // SAFETY: The getter and setter have the correct signature, and access
+    // the instance variable which was added to the class.
+    unsafe {
+        if atomic {
+            builder.add_method(
+                getter_sel,
+                getter::<I, true> as crate::__extern_method_fn_ptr!((unsafe) (_, _)),
+            );
+        } else {
+            builder.add_method(
+                getter_sel,
+                getter::<I, false> as crate::__extern_method_fn_ptr!((unsafe) (_, _)),
+            );
+        }
+
+        match (readonly, atomic, copy) {
+            (true, _, _) => {}
+            (false, true, true) => builder.add_method(
+                setter_sel,
+                setter::<I, true, true> as crate::__extern_method_fn_ptr!((unsafe) (_, _, _)),
+            ),
+            (false, true, false) => builder.add_method(
+                setter_sel,
+                setter::<I, true, false> as crate::__extern_method_fn_ptr!((unsafe) (_, _, _)),
+            ),
+            (false, false, true) => builder.add_method(
+                setter_sel,
+                setter::<I, false, true> as crate::__extern_method_fn_ptr!((unsafe) (_, _, _)),
+            ),
+            (false, false, false) => builder.add_method(
+                setter_sel,
+                setter::<I, false, false> as crate::__extern_method_fn_ptr!((unsafe) (_, _, _)),
+            ),
+        }
+    }
+}
@@ -231,3 +231,29 @@ pub unsafe trait ClassType: Message {
     // TODO: `fn alloc_on_main(mtm: MainThreadMarker)`
     // TODO: `fn mtm(&self) -> MainThreadMarker where T::Mutability: MainThreadOnly`
 }
+
+/// Marks classes that objects can be downcast to by checking their class at
+/// runtime.
+///
+/// This is required by [`AnyObject::downcast_ref`] and [`Id::downcast`].
+///
+/// It is implemented automatically for your type by the
+/// [`declare_class!`][crate::declare_class] and
+/// [`extern_class!`][crate::extern_class] macros, but not for generic classes
+/// like `NSArray<T>`, since it is not possible to check the generic
+/// parameters at runtime.
+///
+///
+/// # Safety
+///
+/// Any object that is an instance of [`ClassType::class`] (or one of its
+/// subclasses) must be valid to use as `Self`.
+///
+/// In particular, this must not be implemented for types with generic
+/// parameters (such as `NSArray<NSString>`), unless the type's methods are
+/// valid regardless of the parameters (such as would be the case for
+/// `NSArray<AnyObject>`).
+///
+/// [`AnyObject::downcast_ref`]: crate::runtime::AnyObject::downcast_ref
+/// [`Id::downcast`]: crate::rc::Id::downcast
+pub unsafe trait DowncastTarget: ClassType {}
@@ -1,11 +1,12 @@
 #![deny(deprecated, unreachable_code)]
 use core::ptr;
 
-use crate::declare::IvarEncode;
+use crate::declare::{IvarBool, IvarDrop, IvarEncode};
 use crate::mutability::{Immutable, Mutable};
-use crate::rc::Id;
-use crate::runtime::NSObject;
-use crate::{declare_class, extern_methods, sel, ClassType};
+use crate::rc::{__RcTestObject, __ThreadTestData, autoreleasepool, Id, WeakId};
+use crate::runtime::__NSProxy as NSProxy;
+use crate::runtime::{AnyClass, AnyObject, NSObject, PropertyOwnership, Sel};
+use crate::{declare_class, extern_methods, msg_send, msg_send_id, sel, ClassType};
 
 // Test that adding the `deprecated` attribute does not mean that warnings
 // when using the method internally are output.
@@ -527,3 +528,295 @@ mod out_param {
         OutParam::unsupported4(None);
     }
 }
+
+declare_class!(
+    struct DeclareClassProperties {
+        #[property(number, nonatomic)]
+        number: IvarEncode<i32, "_number">,
+        /// Other attributes are kept.
+        #[property(flag, readonly)]
+        #[allow(dead_code)]
+        flag: IvarBool<"_flag">,
+        #[property(object, retain)]
+        object: IvarDrop<Option<Id<__RcTestObject>>, "_object">,
+        #[property(copied, copy, nonatomic)]
+        copied: IvarDrop<Option<Id<__RcTestObject>>, "_copied">,
+        #[property(delegate, weak, nonatomic)]
+        delegate: IvarDrop<Option<WeakId<__RcTestObject>>, "_delegate">,
+        #[property(required)]
+        required: IvarDrop<Id<__RcTestObject>, "_required">,
+    }
+
+    mod ivars_properties;
+
+    unsafe impl ClassType for DeclareClassProperties {
+        type Super = NSObject;
+        type Mutability = Immutable;
+        const NAME: &'static str = "DeclareClassProperties";
+    }
+);
+
+#[test]
+fn test_properties() {
+    let cls = DeclareClassProperties::class();
+
+    let attributes = cls.property("number").unwrap().attributes();
+    assert!(attributes.nonatomic);
+    assert!(!attributes.readonly);
+    assert_eq!(attributes.ownership, PropertyOwnership::Assign);
+    assert_eq!(attributes.ivar, Some("_number"));
+
+    let attributes = cls.property("flag").unwrap().attributes();
+    assert!(attributes.readonly);
+    assert!(!attributes.nonatomic);
+    assert!(cls.instance_method(sel!(flag)).is_some());
+    assert!(cls.instance_method(sel!(setFlag:)).is_none());
+
+    let attributes = cls.property("object").unwrap().attributes();
+    assert_eq!(attributes.ownership, PropertyOwnership::Retain);
+    let attributes = cls.property("copied").unwrap().attributes();
+    assert_eq!(attributes.ownership, PropertyOwnership::Copy);
+    let attributes = cls.property("delegate").unwrap().attributes();
+    assert_eq!(attributes.ownership, PropertyOwnership::Weak);
+
+    let obj: Id<DeclareClassProperties> = unsafe { msg_send_id![cls, new] };
+
+    let _: () = unsafe { msg_send![&obj, setNumber: 42i32] };
+    assert_eq!(*obj.number, 42);
+    let number: i32 = unsafe { msg_send![&obj, number] };
+    assert_eq!(number, 42);
+
+    let flag: bool = unsafe { msg_send![&obj, flag] };
+    assert!(!flag);
+
+    let value = __RcTestObject::new();
+    let mut expected = __ThreadTestData::current();
+
+    let _: () = unsafe { msg_send![&obj, setObject: &*value] };
+    expected.retain += 1;
+    expected.assert_current();
+    let object: Option<Id<__RcTestObject>> = unsafe { msg_send_id![&obj, object] };
+    assert!(ptr::eq(&*object.unwrap(), &*value));
+
+    let _: () = unsafe { msg_send![&obj, setCopied: &*value] };
+    let copied: Option<Id<__RcTestObject>> = unsafe { msg_send_id![&obj, copied] };
+    assert!(!ptr::eq(&*copied.unwrap(), &*value));
+    assert_eq!(__ThreadTestData::current().copy, expected.copy + 1);
+
+    let _: () = unsafe { msg_send![&obj, setObject: ptr::null::<__RcTestObject>()] };
+    assert!(obj.object.is_none());
+
+    let delegate = __RcTestObject::new();
+    let _: () = unsafe { msg_send![&obj, setDelegate: &*delegate] };
+    autoreleasepool(|_| {
+        let res: Option<Id<__RcTestObject>> = unsafe { msg_send_id![&obj, delegate] };
+        assert!(ptr::eq(&*res.unwrap(), &*delegate));
+    });
+    drop(delegate);
+    let res: Option<Id<__RcTestObject>> = unsafe { msg_send_id![&obj, delegate] };
+    assert!(res.is_none());
+
+    // Not yet initialized
+    let res: Option<Id<__RcTestObject>> = unsafe { msg_send_id![&obj, required] };
+    assert!(res.is_none());
+    let _: () = unsafe { msg_send![&obj, setRequired: &*value] };
+    let res: Option<Id<__RcTestObject>> = unsafe { msg_send_id![&obj, required] };
+    assert!(ptr::eq(&*res.unwrap(), &*value));
+    assert!(ptr::eq(&**obj.required, &*value));
+}
+
+declare_class!(
+    struct DeclareClassForwardingTarget;
+
+    unsafe impl ClassType for DeclareClassForwardingTarget {
+        type Super = NSObject;
+        type Mutability = Immutable;
+        const NAME: &'static str = "DeclareClassForwardingTarget";
+    }
+
+    unsafe impl DeclareClassForwardingTarget {
+        #[method(forwardedNumber)]
+        fn forwarded_number(&self) -> u32 {
+            42
+        }
+
+        #[method(invokedNumber:)]
+        fn invoked_number(&self, number: u32) -> u32 {
+            number + 1
+        }
+    }
+);
+
+declare_class!(
+    struct DeclareClassForwarding {
+        target: IvarDrop<Option<Id<NSObject>>, "_target">,
+    }
+
+    mod ivars_forwarding;
+
+    unsafe impl ClassType for DeclareClassForwarding {
+        type Super = NSObject;
+        type Mutability = Mutable;
+        const NAME: &'static str = "DeclareClassForwarding";
+    }
+
+    unsafe impl DeclareClassForwarding {
+        #[forwarding_target]
+        fn forwarding_target(&self, sel: Sel) -> Option<Id<AnyObject>> {
+            if sel == sel!(forwardedNumber) {
+                self.target.clone().map(Id::into_super)
+            } else {
+                None
+            }
+        }
+
+        #[resolve_instance_method]
+        fn resolve_instance_method(sel: Sel) -> bool {
+            extern "C" fn resolved(_this: &AnyObject, _cmd: Sel) -> u32 {
+                7
+            }
+
+            if sel == sel!(resolvedNumber) {
+                unsafe { Self::class().add_method(sel, resolved as extern "C" fn(_, _) -> _) }
+            } else {
+                false
+            }
+        }
+
+        #[resolve_class_method]
+        fn resolve_class_method(sel: Sel) -> bool {
+            extern "C" fn resolved(_cls: &AnyClass, _cmd: Sel) -> u32 {
+                8
+            }
+
+            if sel == sel!(resolvedClassNumber) {
+                unsafe { Self::class().add_class_method(sel, resolved as extern "C" fn(_, _) -> _) }
+            } else {
+                false
+            }
+        }
+    }
+);
+
+#[test]
+fn test_forwarding() {
+    let cls = DeclareClassForwarding::class();
+    assert!(cls
+        .instance_method(sel!(forwardingTargetForSelector:))
+        .is_some());
+    assert!(cls.class_method(sel!(resolveInstanceMethod:)).is_some());
+    assert!(cls.class_method(sel!(resolveClassMethod:)).is_some());
+    assert!(cls.instance_method(sel!(resolvedNumber)).is_none());
+
+    let mut obj: Id<DeclareClassForwarding> = unsafe { msg_send_id![cls, new] };
+    let target: Id<DeclareClassForwardingTarget> =
+        unsafe { msg_send_id![DeclareClassForwardingTarget::class(), new] };
+    *obj.target = Some(Id::into_super(target));
+
+    let number: u32 = unsafe { msg_send![&obj, forwardedNumber] };
+    assert_eq!(number, 42);
+
+    let number: u32 = unsafe { msg_send![&obj, resolvedNumber] };
+    assert_eq!(number, 7);
+    assert!(cls.instance_method(sel!(resolvedNumber)).is_some());
+    let number: u32 = unsafe { msg_send![cls, resolvedClassNumber] };
+    assert_eq!(number, 8);
+}
+
+declare_class!(
+    struct DeclareClassProxy {
+        target: IvarDrop<Option<Id<NSObject>>, "_target">,
+    }
+
+    mod ivars_proxy;
+
+    unsafe impl ClassType for DeclareClassProxy {
+        type Super = NSProxy;
+        type Mutability = Mutable;
+        const NAME: &'static str = "DeclareClassProxy";
+    }
+
+    unsafe impl DeclareClassProxy {
+        #[method(init)]
+        fn init(this: &mut Self) -> Option<&mut Self> {
+            // `NSProxy` does not implement `init`
+            Some(this)
+        }
+
+        #[forwarding_target]
+        fn forwarding_target(&self, sel: Sel) -> Option<Id<AnyObject>> {
+            if sel == sel!(forwardedNumber) {
+                self.target.clone().map(Id::into_super)
+            } else {
+                None
+            }
+        }
+
+        #[method(methodSignatureForSelector:)]
+        fn method_signature_for_selector(&self, sel: Sel) -> *mut AnyObject {
+            match self.target.as_deref() {
+                Some(target) => unsafe { msg_send![target, methodSignatureForSelector: sel] },
+                None => ptr::null_mut(),
+            }
+        }
+
+        #[method(forwardInvocation:)]
+        fn forward_invocation(&self, invocation: &AnyObject) {
+            let target = self.target.as_deref();
+            let _: () = unsafe { msg_send![invocation, invokeWithTarget: target] };
+        }
+    }
+);
+
+#[test]
+fn test_proxy() {
+    let cls = DeclareClassProxy::class();
+    assert_eq!(cls.superclass(), Some(NSProxy::class()));
+
+    let mut proxy: Id<DeclareClassProxy> =
+        unsafe { msg_send_id![DeclareClassProxy::alloc(), init] };
+    let target: Id<DeclareClassForwardingTarget> =
+        unsafe { msg_send_id![DeclareClassForwardingTarget::class(), new] };
+    *proxy.target = Some(Id::into_super(target));
+
+    // Through `forwardingTargetForSelector:`
+    let number: u32 = unsafe { msg_send![&proxy, forwardedNumber] };
+    assert_eq!(number, 42);
+
+    // Through `forwardInvocation:`
+    let number: u32 = unsafe { msg_send![&proxy, invokedNumber: 5u32] };
+    assert_eq!(number, 6);
+}
+
+#[test]
+fn test_proxy_downcast() {
+    let mut proxy: Id<DeclareClassProxy> =
+        unsafe { msg_send_id![DeclareClassProxy::alloc(), init] };
+    *proxy.target = Some(NSObject::new());
+
+    // The proxy forwards `isKindOfClass:` to the target
+    let is_kind: bool = unsafe { msg_send![&proxy, isKindOfClass: NSObject::class()] };
+    assert!(is_kind);
+
+    // But downcasting looks at the actual class
+    let obj: Id<AnyObject> = Id::into_super(Id::into_super(proxy));
+    assert!(obj.downcast_ref::<NSObject>().is_none());
+    assert!(obj.downcast_ref::<NSProxy>().is_some());
+    assert!(obj.downcast_ref::<DeclareClassProxy>().is_some());
+
+    let obj = Id::downcast::<NSObject>(obj).unwrap_err();
+    let _obj: Id<NSProxy> = Id::downcast(obj).unwrap();
+}
+
+#[test]
+fn test_proxy_dealloc() {
+    let mut proxy: Id<DeclareClassProxy> =
+        unsafe { msg_send_id![DeclareClassProxy::alloc(), init] };
+    *proxy.target = Some(Id::into_super(__RcTestObject::new()));
+
+    let mut expected = __ThreadTestData::current();
+    drop(proxy);
+    expected.release += 1;
+    expected.dealloc += 1;
+    expected.assert_current();
+}
@@ -2,7 +2,7 @@ use alloc::boxed::Box;
 use core::ffi::c_void;
 
 use crate::encode::{Encode, Encoding};
-use crate::rc::Id;
+use crate::rc::{Id, WeakId};
 use crate::Message;
 
 use super::InnerIvarType;
@@ -23,6 +23,7 @@ mod private {
 /// - `Option<Box<T>>`
 /// - `Id<T>`
 /// - `Option<Id<T>>`
+/// - `Option<WeakId<T>>`
 ///
 /// Further may be added when the standard library guarantee their layout.
 #[repr(transparent)]
@@ -159,6 +160,37 @@ unsafe impl<T: Message> InnerIvarType for IvarDrop<Option<Id<T>>> {
     }
 }
 
+// Note that the ivar stores the `Box` inside the `WeakId`, not the weak
+// reference itself, so this can't be accessed as a `__weak` ivar from
+// Objective-C.
+unsafe impl<T: Message> Encode for IvarDrop<Option<WeakId<T>>> {
+    const ENCODING: Encoding = <*const c_void>::ENCODING;
+}
+
+// SAFETY: `Option<WeakId<T>>` is safe to zero-initialize
+unsafe impl<T: Message> private::IvarDropHelper for Option<WeakId<T>> {
+    type Inner = Option<WeakId<T>>;
+}
+
+// SAFETY: `WeakId<T>` is `#[repr(transparent)]` over a `Box`, and hence
+// guarantees the null-pointer optimization.
+//
+// This is valid to initialize as all-zeroes, so the user doesn't have to do
+// anything to initialize it.
+unsafe impl<T: Message> InnerIvarType for IvarDrop<Option<WeakId<T>>> {
+    type Output = Option<WeakId<T>>;
+
+    #[inline]
+    unsafe fn __deref(&self) -> &Self::Output {
+        &self.0
+    }
+
+    #[inline]
+    unsafe fn __deref_mut(&mut self) -> &mut Self::Output {
+        &mut self.0
+    }
+}
+
 // TODO: Allow the following once their layout is guaranteed by `std`:
 // - Arc<T>
 // - Option<Arc<T>>
@@ -169,8 +201,8 @@ unsafe impl<T: Message> InnerIvarType for IvarDrop<Option<Id<T>>> {
 // - Vec<T>
 // - String
 
-// TODO: Allow `WeakId` once we figure out how to allow it being initialized
-// by default.
+// TODO: Allow `WeakId` (without `Option`) once we figure out how to allow it
+// being initialized by default.
 
 #[inline]
 #[track_caller]
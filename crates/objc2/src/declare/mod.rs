@@ -124,7 +124,7 @@ mod ivar_encode;
 mod ivar_forwarding_impls;
 
 use alloc::format;
-use alloc::string::ToString;
+use alloc::string::{String, ToString};
 use core::mem;
 use core::mem::ManuallyDrop;
 use core::ptr;
@@ -134,11 +134,14 @@ use std::ffi::CString;
 use crate::encode::__unstable::{EncodeArguments, EncodeReturn};
 use crate::encode::{Encode, Encoding, RefEncode};
 use crate::ffi;
-use crate::mutability::IsMutable;
+use crate::mutability::{Immutable, IsIdCloneable, IsMutable};
 use crate::rc::Allocated;
-use crate::runtime::{AnyClass, AnyObject, AnyProtocol, Bool, Imp, Sel};
+use crate::runtime::{
+    AnyClass, AnyObject, AnyProtocol, Bool, Imp, Method, PropertyAttributeList, PropertyAttributes,
+    Sel,
+};
 use crate::sel;
-use crate::Message;
+use crate::{ClassType, Message};
 
 pub use ivar::{InnerIvarType, Ivar, IvarType};
 pub use ivar_bool::IvarBool;
@@ -284,6 +287,48 @@ method_decl_impl!(A, B, C, D, E, F, G, H, I, J, K, L, M, N);
 method_decl_impl!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O);
 method_decl_impl!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P);
 
+/// Objects that can be stored in the instance variable backing a property
+/// declared with `#[property(...)]` in [`declare_class!`].
+///
+/// This is implemented for all classes where [`Id::clone`] is safe, and
+/// `block2` implements it for blocks.
+///
+/// [`declare_class!`]: crate::declare_class
+/// [`Id::clone`]: crate::rc::Id
+///
+///
+/// # Safety
+///
+/// It must be safe to retain the object stored in the instance variable
+/// again, and to hand that out from the getter.
+///
+/// If `REQUIRES_COPY` is `false`, it must be safe to retain any object given
+/// to the setter.
+pub unsafe trait PropertyObject: Message {
+    /// Whether objects must be copied when given to the setter, e.g. because
+    /// they may live on the stack (like blocks).
+    const REQUIRES_COPY: bool = false;
+}
+
+unsafe impl<T: ?Sized + IsIdCloneable> PropertyObject for T {}
+
+/// Objects that can be stored in a property declared with
+/// `#[property(name, copy)]` in [`declare_class!`].
+///
+/// This is implemented for all classes whose [`ClassType::Mutability`] is
+/// [`Immutable`], and `block2` implements it for blocks.
+///
+/// [`declare_class!`]: crate::declare_class
+///
+///
+/// # Safety
+///
+/// Sending `copy` to any object of this type must return an object of the
+/// same type.
+pub unsafe trait CopyPropertyObject: PropertyObject {}
+
+unsafe impl<T: ?Sized + ClassType<Mutability = Immutable>> CopyPropertyObject for T {}
+
 /// Helper type for implementing `MethodImplementation` with a receiver of
 /// `Allocated<T>`, without exposing that implementation to users.
 #[doc(hidden)]
@@ -295,7 +340,40 @@ unsafe impl Encode for __IdReturnValue {
     const ENCODING: Encoding = <*mut AnyObject>::ENCODING;
 }
 
-fn method_type_encoding(ret: &Encoding, args: &[Encoding]) -> CString {
+/// Check the arguments of a method that is about to be added to or replaced
+/// on a class, and compute its type encoding.
+///
+/// If `debug_assertions` are enabled, the encodings are also verified against
+/// the method that will be overridden, if any; `method_name` is used to
+/// describe the method in the panic message.
+pub(crate) fn verified_method_type_encoding<'a>(
+    sel: Sel,
+    enc_args: &[Encoding],
+    enc_ret: &Encoding,
+    overridden: impl FnOnce() -> Option<&'a Method>,
+    method_name: impl FnOnce() -> String,
+) -> CString {
+    let sel_args = sel.number_of_arguments();
+    assert_eq!(
+        sel_args,
+        enc_args.len(),
+        "selector {sel} accepts {sel_args} arguments, but function accepts {}",
+        enc_args.len(),
+    );
+
+    #[cfg(debug_assertions)]
+    if let Some(method) = overridden() {
+        if let Err(err) = crate::verify::verify_method_signature(method, enc_args, enc_ret) {
+            panic!("{}: {err}", method_name())
+        }
+    }
+    #[cfg(not(debug_assertions))]
+    let _ = (overridden, method_name);
+
+    method_type_encoding(enc_ret, enc_args)
+}
+
+pub(crate) fn method_type_encoding(ret: &Encoding, args: &[Encoding]) -> CString {
     // First two arguments are always self and the selector
     let mut types = format!("{ret}{}{}", <*mut AnyObject>::ENCODING, Sel::ENCODING);
     for enc in args {
@@ -428,43 +506,35 @@ impl ClassBuilder {
         F: MethodImplementation<Callee = T>,
     {
         unsafe {
-            self.add_method_inner(
+            self.__add_method_inner(
                 sel,
                 F::Args::ENCODINGS,
-                F::Ret::ENCODING_RETURN,
+                &F::Ret::ENCODING_RETURN,
                 func.__imp(),
             )
         }
     }
 
-    unsafe fn add_method_inner(
+    /// Add an instance method with the given raw implementation.
+    ///
+    /// Used by `block2`.
+    #[doc(hidden)]
+    pub unsafe fn __add_method_inner(
         &mut self,
         sel: Sel,
         enc_args: &[Encoding],
-        enc_ret: Encoding,
+        enc_ret: &Encoding,
         func: Imp,
     ) {
-        let sel_args = sel.number_of_arguments();
-        assert_eq!(
-            sel_args,
-            enc_args.len(),
-            "selector {sel} accepts {sel_args} arguments, but function accepts {}",
-            enc_args.len(),
-        );
-
         // Verify that, if the method is present on the superclass, that the
         // encoding is correct.
-        #[cfg(debug_assertions)]
-        if let Some(superclass) = self.superclass() {
-            if let Some(method) = superclass.instance_method(sel) {
-                if let Err(err) = crate::verify::verify_method_signature(method, enc_args, &enc_ret)
-                {
-                    panic!("declared invalid method -[{} {sel}]: {err}", self.name())
-                }
-            }
-        }
-
-        let types = method_type_encoding(&enc_ret, enc_args);
+        let types = verified_method_type_encoding(
+            sel,
+            enc_args,
+            enc_ret,
+            || self.superclass()?.instance_method(sel),
+            || format!("declared invalid method -[{} {sel}]", self.name()),
+        );
         let success = Bool::from_raw(unsafe {
             ffi::class_addMethod(self.as_mut_ptr(), sel.as_ptr(), Some(func), types.as_ptr())
         });
@@ -492,43 +562,35 @@ impl ClassBuilder {
         F: MethodImplementation<Callee = AnyClass>,
     {
         unsafe {
-            self.add_class_method_inner(
+            self.__add_class_method_inner(
                 sel,
                 F::Args::ENCODINGS,
-                F::Ret::ENCODING_RETURN,
+                &F::Ret::ENCODING_RETURN,
                 func.__imp(),
             )
         }
     }
 
-    unsafe fn add_class_method_inner(
+    /// Add a class method with the given raw implementation.
+    ///
+    /// Used by `block2`.
+    #[doc(hidden)]
+    pub unsafe fn __add_class_method_inner(
         &mut self,
         sel: Sel,
         enc_args: &[Encoding],
-        enc_ret: Encoding,
+        enc_ret: &Encoding,
         func: Imp,
     ) {
-        let sel_args = sel.number_of_arguments();
-        assert_eq!(
-            sel_args,
-            enc_args.len(),
-            "selector {sel} accepts {sel_args} arguments, but function accepts {}",
-            enc_args.len(),
-        );
-
         // Verify that, if the method is present on the superclass, that the
         // encoding is correct.
-        #[cfg(debug_assertions)]
-        if let Some(superclass) = self.superclass() {
-            if let Some(method) = superclass.class_method(sel) {
-                if let Err(err) = crate::verify::verify_method_signature(method, enc_args, &enc_ret)
-                {
-                    panic!("declared invalid method +[{} {sel}]: {err}", self.name())
-                }
-            }
-        }
-
-        let types = method_type_encoding(&enc_ret, enc_args);
+        let types = verified_method_type_encoding(
+            sel,
+            enc_args,
+            enc_ret,
+            || self.superclass()?.class_method(sel),
+            || format!("declared invalid method +[{} {sel}]", self.name()),
+        );
         let success = Bool::from_raw(unsafe {
             ffi::class_addMethod(
                 self.metaclass_mut(),
@@ -613,7 +675,69 @@ impl ClassBuilder {
         assert!(success, "failed to add protocol {proto}");
     }
 
-    // fn add_property(&self, name: &str, attributes: &[ffi::objc_property_attribute_t]);
+    /// Adds a declared property with type `T`, the provided name and the
+    /// given attributes.
+    ///
+    /// This only registers the property's metadata with the runtime (which
+    /// is what e.g. `class_copyPropertyList` and Key-Value Coding use); the
+    /// getter and setter methods, as well as any backing instance variable,
+    /// must be added separately with [`add_method`][Self::add_method] and
+    /// [`add_ivar`][Self::add_ivar].
+    ///
+    ///
+    /// # Panics
+    ///
+    /// If the property wasn't successfully added for some reason - this
+    /// usually happens if there already was a property with that name.
+    ///
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use objc2::declare::ClassBuilder;
+    /// use objc2::runtime::{NSObject, PropertyAttributes};
+    /// use objc2::ClassType;
+    ///
+    /// let mut builder = ClassBuilder::new("ClassBuilderAddPropertyExample", NSObject::class())
+    ///     .unwrap();
+    /// builder.add_ivar::<i32>("_number");
+    /// builder.add_property::<i32>(
+    ///     "number",
+    ///     PropertyAttributes {
+    ///         nonatomic: true,
+    ///         ivar: Some("_number"),
+    ///         ..Default::default()
+    ///     },
+    /// );
+    /// let cls = builder.register();
+    ///
+    /// let property = cls.property("number").unwrap();
+    /// assert_eq!(property.type_encoding(), "i");
+    /// assert_eq!(property.attributes().ivar, Some("_number"));
+    /// ```
+    #[doc(alias = "class_addProperty")]
+    pub fn add_property<T: Encode>(&mut self, name: &str, attributes: PropertyAttributes<'_>) {
+        self.add_property_inner(name, &T::ENCODING, &attributes)
+    }
+
+    fn add_property_inner(
+        &mut self,
+        name: &str,
+        encoding: &Encoding,
+        attributes: &PropertyAttributes<'_>,
+    ) {
+        let c_name = CString::new(name).unwrap();
+        let list = PropertyAttributeList::new(encoding, attributes);
+        let success = Bool::from_raw(unsafe {
+            ffi::class_addProperty(
+                self.as_mut_ptr(),
+                c_name.as_ptr(),
+                list.as_ptr(),
+                list.len() as _,
+            )
+        });
+        assert!(success.as_bool(), "failed to add property {name}");
+    }
 
     /// Registers the [`ClassBuilder`], consuming it, and returns a reference
     /// to the newly registered [`AnyClass`].
@@ -728,6 +852,46 @@ impl ProtocolBuilder {
         )
     }
 
+    /// Adds a property declaration with the given name, type and attributes.
+    ///
+    /// `required` specifies whether the property is required, or marked
+    /// `@optional`, and `instance` whether it is an instance or a class
+    /// property.
+    ///
+    /// See [`ClassBuilder::add_property`] for details on the attributes.
+    #[doc(alias = "protocol_addProperty")]
+    pub fn add_property<T: Encode>(
+        &mut self,
+        name: &str,
+        attributes: PropertyAttributes<'_>,
+        required: bool,
+        instance: bool,
+    ) {
+        self.add_property_inner(name, &T::ENCODING, &attributes, required, instance)
+    }
+
+    fn add_property_inner(
+        &mut self,
+        name: &str,
+        encoding: &Encoding,
+        attributes: &PropertyAttributes<'_>,
+        required: bool,
+        instance: bool,
+    ) {
+        let c_name = CString::new(name).unwrap();
+        let list = PropertyAttributeList::new(encoding, attributes);
+        unsafe {
+            ffi::protocol_addProperty(
+                self.as_mut_ptr(),
+                c_name.as_ptr(),
+                list.as_ptr(),
+                list.len() as _,
+                Bool::new(required).as_raw(),
+                Bool::new(instance).as_raw(),
+            );
+        }
+    }
+
     /// Adds a requirement on another protocol.
     pub fn add_protocol(&mut self, proto: &AnyProtocol) {
         unsafe {
@@ -894,6 +1058,17 @@ mod tests {
         builder.add_protocol(protocol);
     }
 
+    #[test]
+    #[should_panic = "failed to add property xyz"]
+    fn duplicate_property() {
+        let cls = test_utils::custom_class();
+        let mut builder = ClassBuilder::new("TestClassBuilderDuplicateProperty", cls).unwrap();
+
+        builder.add_property::<i32>("xyz", Default::default());
+        // Should panic:
+        builder.add_property::<i32>("xyz", Default::default());
+    }
+
     #[test]
     fn test_classbuilder_drop() {
         let cls = test_utils::custom_class();
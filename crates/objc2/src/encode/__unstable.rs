@@ -216,6 +216,13 @@ encode_args_impl!(A, B, C, D, E, F, G, H, I, J, K, L, M, N);
 encode_args_impl!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O);
 encode_args_impl!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P);
 
+impl<A: EncodeArguments, V> args_private::Sealed for crate::message::__Variadic<A, V> {}
+
+// Only the fixed arguments are part of the method's type encoding.
+impl<A: EncodeArguments, V> EncodeArguments for crate::message::__Variadic<A, V> {
+    const ENCODINGS: &'static [Encoding] = A::ENCODINGS;
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -43,6 +43,37 @@
 //! assert!(MyStruct::ENCODING_REF.equivalent_to_str("^{MyStruct=fs}"));
 //! ```
 //!
+//! With the `"derive"` feature enabled, the implementations above can
+//! instead be generated with `#[derive(Encode, RefEncode)]`. The field
+//! encodings are taken from each field's [`Encode`] implementation, and the
+//! name that Objective-C sees can be set with `#[encoding(name = "...")]`
+//! (it defaults to the name of the type).
+//!
+//! This works on `#[repr(C)]` structs and unions, and on
+//! `#[repr(transparent)]` newtypes (where the encoding of the inner type is
+//! used). `#[repr(packed)]` types are not supported, since the encoding
+//! can't describe their layout.
+//!
+//! ```
+//! # #[cfg(feature = "derive")]
+//! # fn main() {
+//! use objc2::encode::{Encode, RefEncode};
+//!
+//! #[repr(C)]
+//! #[derive(Encode, RefEncode)]
+//! #[encoding(name = "_MyStruct")]
+//! struct MyStruct {
+//!     a: f32,
+//!     b: i16,
+//! }
+//!
+//! assert!(MyStruct::ENCODING.equivalent_to_str("{_MyStruct=fs}"));
+//! assert!(MyStruct::ENCODING_REF.equivalent_to_str("^{_MyStruct=fs}"));
+//! # }
+//! # #[cfg(not(feature = "derive"))]
+//! # fn main() {}
+//! ```
+//!
 //! Implementing [`Encode`] for a few core-graphics types.
 //!
 //! Note that these are available in `icrate`, so the implementation here is
@@ -85,7 +116,11 @@ use core::sync::atomic;
 pub mod __unstable;
 
 #[doc(inline)]
-pub use objc2_encode::{Encoding, EncodingBox, ParseError};
+pub use objc2_encode::{Encoding, EncodingBox, ParseError, PointerWidth};
+
+/// Derive macros for [`Encode`] and [`RefEncode`].
+#[cfg(feature = "derive")]
+pub use objc2_proc_macros::{Encode, RefEncode};
 
 /// Types that have an Objective-C type-encoding.
 ///
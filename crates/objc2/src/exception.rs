@@ -6,6 +6,11 @@
 //! [`msg_send!`] in a `@catch` and panics if an exception is caught,
 //! preventing Objective-C from unwinding into Rust.
 //!
+//! Conversely, the `"panic-to-exception"` feature converts panics in methods
+//! declared with [`declare_class!`] into exceptions, preventing Rust from
+//! unwinding into Objective-C. This requires nightly, since it enables the
+//! `"unstable-c-unwind"` feature.
+//!
 //! Most of the functionality in this module is only available when the
 //! `"exception"` feature is enabled.
 //!
@@ -15,10 +20,13 @@
 //! - [Exception Handling in LLVM](https://llvm.org/docs/ExceptionHandling.html)
 //!
 //! [`msg_send!`]: crate::msg_send
+//! [`declare_class!`]: crate::declare_class
 
 // TODO: Test this with panic=abort, and ensure that the code-size is
 // reasonable in that case.
 
+#[cfg(feature = "panic-to-exception")]
+use core::any::Any;
 #[cfg(feature = "exception")]
 use core::ffi::c_void;
 use core::fmt;
@@ -29,15 +37,25 @@ use core::panic::RefUnwindSafe;
 use core::panic::UnwindSafe;
 #[cfg(feature = "exception")]
 use core::ptr;
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
 use std::error::Error;
 
 use crate::encode::{Encoding, RefEncode};
 #[cfg(feature = "exception")]
 use crate::ffi;
-use crate::rc::{autoreleasepool_leaking, Id};
+use crate::ffi::NSUInteger;
+#[cfg(feature = "panic-to-exception")]
+use crate::rc::Allocated;
+use crate::rc::{autoreleasepool, autoreleasepool_leaking, Id};
 use crate::runtime::__nsstring::nsstring_to_str;
-use crate::runtime::{AnyClass, AnyObject, NSObject, NSObjectProtocol};
-use crate::{extern_methods, sel, Message};
+#[cfg(feature = "panic-to-exception")]
+use crate::runtime::__nsstring::UTF8_ENCODING;
+use crate::runtime::{AnyClass, AnyObject, NSObject, NSObjectProtocol, Sel};
+#[cfg(feature = "exception")]
+use crate::ClassType;
+use crate::{extern_methods, msg_send, msg_send_id, sel, Message};
 
 /// An Objective-C exception.
 ///
@@ -72,17 +90,182 @@ impl AsRef<AnyObject> for Exception {
 }
 
 impl Exception {
-    fn is_nsexception(&self) -> Option<bool> {
+    /// Whether the exception is an instance of the given class, or one of
+    /// its subclasses.
+    fn is_kind_of(&self, cls: &AnyClass) -> bool {
         if self.class().responds_to(sel!(isKindOfClass:)) {
             // SAFETY: We only use `isKindOfClass:` on NSObject
             let obj: *const Exception = self;
             let obj = unsafe { obj.cast::<NSObject>().as_ref().unwrap() };
-            // Get class dynamically instead of with `class!` macro
-            Some(obj.__isKindOfClass(AnyClass::get("NSException")?))
+            obj.__isKindOfClass(cls)
+        } else {
+            false
+        }
+    }
+
+    fn is_nsexception(&self) -> Option<bool> {
+        // Get class dynamically instead of with `class!` macro
+        Some(self.is_kind_of(AnyClass::get("NSException")?))
+    }
+
+    /// Convert an `NSString` to a Rust string.
+    ///
+    /// # Safety
+    ///
+    /// The object must be an `NSString`.
+    unsafe fn nsstring_to_string(obj: &NSObject) -> String {
+        autoreleasepool(|pool| unsafe { nsstring_to_str(obj, pool) }.to_string())
+    }
+
+    /// The name of the exception, if it is an `NSException`.
+    ///
+    /// This is e.g. `"NSInvalidArgumentException"`.
+    pub fn name(&self) -> Option<String> {
+        if let Some(true) = self.is_nsexception() {
+            // SAFETY: Just checked that object is an NSException
+            let name = unsafe { self.__name() }?;
+            // SAFETY: `name` is guaranteed to be an NSString.
+            Some(unsafe { Self::nsstring_to_string(&name) })
+        } else {
+            None
+        }
+    }
+
+    /// A human-readable reason for the exception, if it is an
+    /// `NSException` that has one.
+    pub fn reason(&self) -> Option<String> {
+        if let Some(true) = self.is_nsexception() {
+            // SAFETY: Just checked that object is an NSException
+            let reason = unsafe { self.__reason() }?;
+            // SAFETY: `reason` is guaranteed to be an NSString.
+            Some(unsafe { Self::nsstring_to_string(&reason) })
+        } else {
+            None
+        }
+    }
+
+    /// The user info dictionary of the exception, if it is an `NSException`
+    /// that has one.
+    ///
+    /// The returned object is an `NSDictionary`.
+    pub fn user_info(&self) -> Option<Id<AnyObject>> {
+        if let Some(true) = self.is_nsexception() {
+            // SAFETY: Just checked that object is an NSException
+            unsafe { self.__user_info() }
         } else {
-            Some(false)
+            None
         }
     }
+
+    /// Whether the exception is an `NSException` that responds to the given
+    /// selector.
+    fn nsexception_responds_to(&self, sel: Sel) -> bool {
+        self.is_nsexception() == Some(true) && self.class().responds_to(sel)
+    }
+
+    /// The return addresses of the call stack at the point where the
+    /// exception was raised.
+    ///
+    /// Returns `None` if the exception is not an `NSException`, or if the
+    /// runtime doesn't record the call stack.
+    pub fn call_stack_return_addresses(&self) -> Option<Vec<usize>> {
+        if !self.nsexception_responds_to(sel!(callStackReturnAddresses)) {
+            return None;
+        }
+        // SAFETY: Just checked that object is an NSException
+        let array = unsafe { self.__call_stack_return_addresses() }?;
+        // SAFETY: The array contains NSNumbers.
+        let addresses = unsafe { nsarray_to_vec(&array) }
+            .iter()
+            .map(|number| unsafe { msg_send![number, unsignedIntegerValue] })
+            .collect();
+        Some(addresses)
+    }
+
+    /// A human-readable description of each frame of the call stack at the
+    /// point where the exception was raised.
+    ///
+    /// The format of each frame is determined by the runtime, but usually
+    /// includes the image, address and symbol name.
+    ///
+    /// Returns `None` if the exception is not an `NSException`, or if the
+    /// runtime doesn't record the call stack.
+    pub fn call_stack_symbols(&self) -> Option<Vec<String>> {
+        if !self.nsexception_responds_to(sel!(callStackSymbols)) {
+            return None;
+        }
+        // SAFETY: Just checked that object is an NSException
+        let array = unsafe { self.__call_stack_symbols() }?;
+        // SAFETY: The array contains NSStrings.
+        let symbols = unsafe { nsarray_to_vec(&array) }
+            .iter()
+            .map(|symbol| unsafe { Self::nsstring_to_string(symbol) })
+            .collect();
+        Some(symbols)
+    }
+
+    /// Create an `NSException` named `RustPanic`, with the panic message as
+    /// the reason.
+    ///
+    /// Returns `None` if Foundation is not available.
+    #[cfg(feature = "panic-to-exception")]
+    pub(crate) fn from_panic(payload: &(dyn Any + Send)) -> Option<Id<Self>> {
+        let message = if let Some(s) = payload.downcast_ref::<&'static str>() {
+            s
+        } else if let Some(s) = payload.downcast_ref::<String>() {
+            s.as_str()
+        } else {
+            "Box<dyn Any>"
+        };
+
+        // Get classes dynamically instead of with `class!` macro, since we
+        // don't link to Foundation.
+        let string_cls = AnyClass::get("NSString")?;
+        let exception_cls = AnyClass::get("NSException")?;
+
+        let name = nsstring_from_str(string_cls, "RustPanic")?;
+        let reason = nsstring_from_str(string_cls, message)?;
+
+        let obj: Option<Allocated<NSObject>> = unsafe { msg_send_id![exception_cls, alloc] };
+        let obj: Option<Id<NSObject>> = unsafe {
+            msg_send_id![
+                obj,
+                initWithName: &*name,
+                reason: &*reason,
+                userInfo: None::<&NSObject>,
+            ]
+        };
+        // SAFETY: The object is an `NSException`.
+        obj.map(|obj| unsafe { Id::cast(obj) })
+    }
+}
+
+/// Get the objects in an `NSArray`.
+///
+/// # Safety
+///
+/// The object must be an `NSArray`.
+unsafe fn nsarray_to_vec(array: &NSObject) -> Vec<Id<NSObject>> {
+    let count: NSUInteger = unsafe { msg_send![array, count] };
+    (0..count)
+        .map(|i| unsafe { msg_send_id![array, objectAtIndex: i] })
+        .collect()
+}
+
+/// Create an `NSString` by copying the given string slice.
+#[cfg(feature = "panic-to-exception")]
+fn nsstring_from_str(cls: &AnyClass, string: &str) -> Option<Id<NSObject>> {
+    let obj: Option<Allocated<NSObject>> = unsafe { msg_send_id![cls, alloc] };
+    let bytes: *const c_void = string.as_ptr().cast();
+    // SAFETY: The class is `NSString`, and the bytes are valid UTF-8.
+    unsafe {
+        msg_send_id![
+            obj,
+            initWithBytes: bytes,
+            length: string.len(),
+            encoding: UTF8_ENCODING,
+        ]
+    }
 }
 
 extern_methods!(
@@ -90,12 +273,27 @@ extern_methods!(
         // Only safe on NSException
         // Returns NSString
         #[method_id(name)]
-        unsafe fn name(&self) -> Option<Id<NSObject>>;
+        unsafe fn __name(&self) -> Option<Id<NSObject>>;
 
         // Only safe on NSException
         // Returns NSString
         #[method_id(reason)]
-        unsafe fn reason(&self) -> Option<Id<NSObject>>;
+        unsafe fn __reason(&self) -> Option<Id<NSObject>>;
+
+        // Only safe on NSException
+        // Returns NSDictionary
+        #[method_id(userInfo)]
+        unsafe fn __user_info(&self) -> Option<Id<AnyObject>>;
+
+        // Only safe on NSException
+        // Returns NSArray of NSNumber
+        #[method_id(callStackReturnAddresses)]
+        unsafe fn __call_stack_return_addresses(&self) -> Option<Id<NSObject>>;
+
+        // Only safe on NSException
+        // Returns NSArray of NSString
+        #[method_id(callStackSymbols)]
+        unsafe fn __call_stack_symbols(&self) -> Option<Id<NSObject>>;
     }
 );
 
@@ -111,7 +309,7 @@ impl fmt::Debug for Exception {
         if let Some(true) = self.is_nsexception() {
             autoreleasepool_leaking(|pool| {
                 // SAFETY: Just checked that object is an NSException
-                let (name, reason) = unsafe { (self.name(), self.reason()) };
+                let (name, reason) = unsafe { (self.__name(), self.__reason()) };
 
                 // SAFETY: `name` and `reason` are guaranteed to be NSString.
                 let name = name
@@ -124,16 +322,24 @@ impl fmt::Debug for Exception {
                 let obj: &AnyObject = self.as_ref();
                 write!(f, "{obj:?} '{}'", name.unwrap_or_default())?;
                 if let Some(reason) = reason {
-                    write!(f, " reason:{reason}")?;
+                    write!(f, " reason:{reason}")
                 } else {
-                    write!(f, " reason:(NULL)")?;
+                    write!(f, " reason:(NULL)")
                 }
-                Ok(())
-            })
+            })?;
         } else {
             // Fall back to `AnyObject` Debug
-            write!(f, "{:?}", self.0)
+            write!(f, "{:?}", self.0)?;
+        }
+
+        if f.alternate() {
+            if let Some(symbols) = self.call_stack_symbols() {
+                for symbol in symbols {
+                    write!(f, "\n    {symbol}")?;
+                }
+            }
         }
+        Ok(())
     }
 }
 
@@ -142,7 +348,7 @@ impl fmt::Display for Exception {
         autoreleasepool_leaking(|pool| {
             if let Some(true) = self.is_nsexception() {
                 // SAFETY: Just checked that object is an NSException
-                let reason = unsafe { self.reason() };
+                let reason = unsafe { self.__reason() };
 
                 if let Some(reason) = &reason {
                     // SAFETY: `reason` is guaranteed to be NSString.
@@ -290,6 +496,62 @@ pub unsafe fn catch<R>(
     result.map(|()| value.unwrap())
 }
 
+/// Tries to execute the given closure, and catches Objective-C exceptions
+/// that are instances of `T` (or one of its subclasses).
+///
+/// This is similar to [`catch`], except that other exceptions (including
+/// `nil` exceptions) are rethrown, which is the equivalent of the following
+/// Objective-C code:
+///
+/// ```objc
+/// @try {
+///     closure();
+/// } @catch (T *exception) {
+///     return exception;
+/// }
+/// ```
+///
+///
+/// # Safety
+///
+/// Same as [`catch`]. Additionally, since other exceptions are rethrown, this
+/// must itself be called inside an Objective-C exception handler if they may
+/// occur.
+///
+///
+/// # Example
+///
+/// Catch only exceptions that are instances of `NSObject`.
+///
+/// ```
+/// use objc2::exception::{catch_as, throw, Exception};
+/// use objc2::rc::Id;
+/// use objc2::runtime::NSObject;
+///
+/// let obj: Id<Exception> = unsafe { Id::cast(NSObject::new()) };
+/// let res = unsafe { catch_as::<NSObject, (), _>(|| throw(obj)) };
+/// let obj: Id<NSObject> = res.unwrap_err();
+/// ```
+#[cfg(feature = "exception")]
+pub unsafe fn catch_as<T: ClassType, R, F: FnOnce() -> R + UnwindSafe>(
+    closure: F,
+) -> Result<R, Id<T>> {
+    match unsafe { catch(closure) } {
+        Ok(value) => Ok(value),
+        Err(Some(exception)) if exception.is_kind_of(T::class()) => {
+            // SAFETY: Just checked that the exception is an instance of `T`.
+            Err(unsafe { Id::cast(exception) })
+        }
+        Err(exception) => {
+            let ptr = Id::consume_as_ptr_option(exception);
+            // SAFETY: The caller ensures that the exception will be caught
+            // again. The exception is passed on with the retain count that we
+            // got from catching it, just like `throw`.
+            unsafe { ffi::objc_exception_throw(ptr.cast()) }
+        }
+    }
+}
+
 #[cfg(test)]
 #[cfg(feature = "exception")]
 mod tests {
@@ -297,6 +559,7 @@ mod tests {
     use alloc::string::ToString;
 
     use super::*;
+    use crate::rc::__RcTestObject;
     use crate::runtime::NSObject;
 
     #[test]
@@ -343,5 +606,53 @@ mod tests {
         assert_eq!(format!("{obj:?}"), format!("exception <NSObject: {ptr:p}>"));
 
         assert!(ptr::eq(&*obj, ptr));
+
+        assert_eq!(obj.name(), None);
+        assert_eq!(obj.reason(), None);
+        assert!(obj.user_info().is_none());
+    }
+
+    #[test]
+    fn test_catch_as() {
+        let obj = NSObject::new();
+        let _obj2 = obj.clone();
+        let ptr: *const NSObject = &*obj;
+        let exc: Id<Exception> = unsafe { Id::cast(obj) };
+
+        let result = unsafe { catch_as::<NSObject, (), _>(|| throw(exc)) };
+        let obj = result.unwrap_err();
+        assert!(ptr::eq(&*obj, ptr));
+
+        let result = unsafe { catch_as::<NSObject, _, _>(|| 42) };
+        assert_eq!(result.unwrap(), 42);
+    }
+
+    #[test]
+    fn test_catch_as_rethrow() {
+        let obj = NSObject::new();
+        let _obj2 = obj.clone();
+        let ptr: *const NSObject = &*obj;
+        let exc: Id<Exception> = unsafe { Id::cast(obj) };
+
+        // Not an instance of the subclass, so the exception is passed on to
+        // the outer handler.
+        let result: Result<(), _> = unsafe {
+            catch(|| {
+                let _ = catch_as::<__RcTestObject, (), _>(|| throw(exc));
+                unreachable!()
+            })
+        };
+        let exc = result.unwrap_err().unwrap();
+        assert!(ptr::eq(&*exc, ptr.cast()));
+    }
+
+    #[test]
+    fn test_catch_as_subclass() {
+        let obj = __RcTestObject::new();
+        let ptr: *const __RcTestObject = &*obj;
+        let exc: Id<Exception> = unsafe { Id::cast(obj) };
+
+        let result = unsafe { catch_as::<NSObject, (), _>(|| throw(exc)) };
+        assert!(ptr::eq(&*result.unwrap_err(), ptr.cast()));
     }
 }
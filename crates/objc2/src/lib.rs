@@ -184,10 +184,10 @@ extern "C" {}
 #[doc(no_inline)]
 pub use objc_sys as ffi;
 
-pub use self::class_type::ClassType;
+pub use self::class_type::{ClassType, DowncastTarget};
 #[doc(no_inline)]
 pub use self::encode::{Encode, Encoding, RefEncode};
-pub use self::message::{Message, MessageArguments, MessageReceiver};
+pub use self::message::{Message, MessageArguments, MessageReceiver, VaArg, VariadicArguments};
 pub use self::protocol_type::ProtocolType;
 
 #[cfg(feature = "objc2-proc-macros")]
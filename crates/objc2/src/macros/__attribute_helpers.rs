@@ -50,6 +50,10 @@ macro_rules! __extract_and_apply_cfg_attributes {
 /// Extract `#[method(...)]` or `#[method_id(...)]` and the `#[optional]`
 /// attribute, and send it to another macro.
 ///
+/// The message forwarding attributes that `declare_class!` supports are
+/// rewritten to the method they implement, e.g. `#[forwarding_target]` is
+/// passed on as `#[method_id(forwardingTargetForSelector:)]`.
+///
 /// This will ensure that there is one and only one of the method attributes
 /// present.
 ///
@@ -109,6 +113,72 @@ macro_rules! __extract_custom_attributes_inner {
         }
     };
 
+    // Rewrite the message forwarding attributes to the method they
+    // implement.
+    {
+        ()
+        (#[forwarding_target])
+        ($($m_optional:tt)*)
+        ($($m_checked:tt)*)
+        ($name:ident)
+
+        ($out_macro:path)
+        $($macro_args:tt)*
+    } => {
+        $crate::__extract_custom_attributes_inner! {
+            ()
+            (#[method_id(forwardingTargetForSelector:)])
+            ($($m_optional)*)
+            ($($m_checked)*)
+            ($name)
+
+            ($out_macro)
+            $($macro_args)*
+        }
+    };
+    {
+        ()
+        (#[resolve_instance_method])
+        ($($m_optional:tt)*)
+        ($($m_checked:tt)*)
+        ($name:ident)
+
+        ($out_macro:path)
+        $($macro_args:tt)*
+    } => {
+        $crate::__extract_custom_attributes_inner! {
+            ()
+            (#[method(resolveInstanceMethod:)])
+            ($($m_optional)*)
+            ($($m_checked)*)
+            ($name)
+
+            ($out_macro)
+            $($macro_args)*
+        }
+    };
+    {
+        ()
+        (#[resolve_class_method])
+        ($($m_optional:tt)*)
+        ($($m_checked:tt)*)
+        ($name:ident)
+
+        ($out_macro:path)
+        $($macro_args:tt)*
+    } => {
+        $crate::__extract_custom_attributes_inner! {
+            ()
+            (#[method(resolveClassMethod:)])
+            ($($m_optional)*)
+            ($($m_checked)*)
+            ($name)
+
+            ($out_macro)
+            $($macro_args)*
+        }
+    };
+
     // Base case
     {
         // No attributes left to process
@@ -159,6 +229,28 @@ macro_rules! __extract_custom_attributes_inner {
             $($macro_args)*
         }
     };
+    // `method` after a message forwarding attribute
+    {
+        (
+            #[method($($args:tt)*)]
+            $($rest:tt)*
+        )
+        (#[$m_forwarding:ident])
+        ($($m_optional:tt)*)
+        ($($m_checked:tt)*)
+        ($name:ident)
+
+        ($out_macro:path)
+        $($macro_args:tt)*
+    } => {
+        fn $name() {
+            compile_error!(concat!(
+                "cannot combine `#[",
+                stringify!($m_forwarding),
+                "]` with another method attribute",
+            ))
+        }
+    };
     // Duplicate `method` attributes
     {
         (
@@ -205,6 +297,28 @@ macro_rules! __extract_custom_attributes_inner {
             $($macro_args)*
         }
     };
+    // `method_id` after a message forwarding attribute
+    {
+        (
+            #[method_id($($args:tt)*)]
+            $($rest:tt)*
+        )
+        (#[$m_forwarding:ident])
+        ($($m_optional:tt)*)
+        ($($m_checked:tt)*)
+        ($name:ident)
+
+        ($out_macro:path)
+        $($macro_args:tt)*
+    } => {
+        fn $name() {
+            compile_error!(concat!(
+                "cannot combine `#[",
+                stringify!($m_forwarding),
+                "]` with another method attribute",
+            ))
+        }
+    };
     // Duplicate `method` attributes
     {
         (
@@ -224,6 +338,138 @@ macro_rules! __extract_custom_attributes_inner {
         }
     };
 
+    // Message forwarding attributes
+    {
+        (
+            #[forwarding_target]
+            $($rest:tt)*
+        )
+        // If no existing `method` nor `method_id` attributes exist
+        ()
+        ($($m_optional:tt)*)
+        ($($m_checked:tt)*)
+        ($name:ident)
+
+        ($out_macro:path)
+        $($macro_args:tt)*
+    } => {
+        $crate::__extract_custom_attributes_inner! {
+            ($($rest)*)
+            // Rewritten in the base case
+            (#[forwarding_target])
+            ($($m_optional)*)
+            ($($m_checked)*)
+            ($name)
+
+            ($out_macro)
+            $($macro_args)*
+        }
+    };
+    {
+        (
+            #[resolve_instance_method]
+            $($rest:tt)*
+        )
+        // If no existing `method` nor `method_id` attributes exist
+        ()
+        ($($m_optional:tt)*)
+        ($($m_checked:tt)*)
+        ($name:ident)
+
+        ($out_macro:path)
+        $($macro_args:tt)*
+    } => {
+        $crate::__extract_custom_attributes_inner! {
+            ($($rest)*)
+            // Rewritten in the base case
+            (#[resolve_instance_method])
+            ($($m_optional)*)
+            ($($m_checked)*)
+            ($name)
+
+            ($out_macro)
+            $($macro_args)*
+        }
+    };
+    {
+        (
+            #[resolve_class_method]
+            $($rest:tt)*
+        )
+        // If no existing `method` nor `method_id` attributes exist
+        ()
+        ($($m_optional:tt)*)
+        ($($m_checked:tt)*)
+        ($name:ident)
+
+        ($out_macro:path)
+        $($macro_args:tt)*
+    } => {
+        $crate::__extract_custom_attributes_inner! {
+            ($($rest)*)
+            // Rewritten in the base case
+            (#[resolve_class_method])
+            ($($m_optional)*)
+            ($($m_checked)*)
+            ($name)
+
+            ($out_macro)
+            $($macro_args)*
+        }
+    };
+    // Duplicate message forwarding attributes
+    {
+        (
+            #[forwarding_target]
+            $($rest:tt)*
+        )
+        ($($m_method:tt)*)
+        ($($m_optional:tt)*)
+        ($($m_checked:tt)*)
+        ($name:ident)
+
+        ($out_macro:path)
+        $($macro_args:tt)*
+    } => {
+        fn $name() {
+            compile_error!("cannot combine `#[forwarding_target]` with another method attribute")
+        }
+    };
+    {
+        (
+            #[resolve_instance_method]
+            $($rest:tt)*
+        )
+        ($($m_method:tt)*)
+        ($($m_optional:tt)*)
+        ($($m_checked:tt)*)
+        ($name:ident)
+
+        ($out_macro:path)
+        $($macro_args:tt)*
+    } => {
+        fn $name() {
+            compile_error!("cannot combine `#[resolve_instance_method]` with another method attribute")
+        }
+    };
+    {
+        (
+            #[resolve_class_method]
+            $($rest:tt)*
+        )
+        ($($m_method:tt)*)
+        ($($m_optional:tt)*)
+        ($($m_checked:tt)*)
+        ($name:ident)
+
+        ($out_macro:path)
+        $($macro_args:tt)*
+    } => {
+        fn $name() {
+            compile_error!("cannot combine `#[resolve_class_method]` with another method attribute")
+        }
+    };
+
     // `optional` attribute
     {
         (
@@ -66,7 +66,7 @@ macro_rules! __parse_fields {
     (
         () // No more fields left
         () // No module
-        ($($ivar_output:tt)+) ($($ivar_type_name:tt)+)
+        ($($ivar_output:tt)+) ($($ivar:tt)+)
         ($($parsed_fields:tt)*)
 
         ($out_macro:path)
@@ -114,7 +114,7 @@ macro_rules! __parse_fields {
     (
         () // No more fields left
         ($ivar_helper_module_v:vis mod $ivar_helper_module:ident)
-        ($($ivar_output:tt)+) ($($ivar_type_name:ident)+)
+        ($($ivar_output:tt)+) ($($ivar:tt)+)
         ($($parsed_fields:tt)*)
 
         ($out_macro:path)
@@ -126,9 +126,12 @@ macro_rules! __parse_fields {
             $($ivar_output)+
 
             pub(super) fn __objc2_declare_ivars(__objc2_builder: &mut $crate::declare::ClassBuilder) {
-                // Ivars
+                // Ivars and properties
                 $(
-                    __objc2_builder.add_static_ivar::<$ivar_type_name>();
+                    $crate::__declare_ivar! {
+                        (__objc2_builder)
+                        $ivar
+                    }
                 )+
             }
         }
@@ -143,21 +146,31 @@ macro_rules! __parse_fields {
     // PhantomData
     (
         (
+            @property[$(($($property:tt)*))?]
             $(#[$m:meta])*
             $vis:vis $field_name:ident: PhantomData<$ty:ty>
             $(, $($rest_fields:tt)*)?
         )
         ($($ivar_helper_module_v:vis mod $ivar_helper_module:ident)?)
-        ($($ivar_output:tt)*) ($($ivar_type_name:ident)*)
+        ($($ivar_output:tt)*) ($($ivar:tt)*)
         ($($parsed_fields:tt)*)
 
         ($out_macro:path)
         $($macro_args:tt)*
     ) => {
+        $(
+            $crate::__macro_helpers::compile_error!($crate::__macro_helpers::concat!(
+                "`#[property(",
+                $crate::__macro_helpers::stringify!($($property)*),
+                ")]` is not supported on `PhantomData` field ",
+                $crate::__macro_helpers::stringify!($field_name),
+            ));
+        )?
+
         $crate::__parse_fields! {
             ($($($rest_fields)*)?)
             ($($ivar_helper_module_v mod $ivar_helper_module)?)
-            ($($ivar_output)*) ($($ivar_type_name)*)
+            ($($ivar_output)*) ($($ivar)*)
             (
                 $($parsed_fields)*
 
@@ -179,12 +192,13 @@ macro_rules! __parse_fields {
     // IvarDrop
     (
         (
+            @property[$(($($property:tt)*))?]
             $(#[$m:meta])*
             $vis:vis $field_name:ident: IvarDrop<$ty:ty, $ivar_name:literal>
             $(, $($rest_fields:tt)*)?
         )
         ($($ivar_helper_module_v:vis mod $ivar_helper_module:ident)?)
-        ($($ivar_output:tt)*) ($($ivar_type_name:ident)*)
+        ($($ivar_output:tt)*) ($($ivar:tt)*)
         ($($parsed_fields:tt)*)
 
         ($out_macro:path)
@@ -210,7 +224,7 @@ macro_rules! __parse_fields {
                     type Type = IvarDrop<$ty>;
                     const NAME: &'static $crate::__macro_helpers::str = $ivar_name;
                 }
-            ) ($($ivar_type_name)* $field_name)
+            ) ($($ivar)* ($field_name $(($($property)*))?))
             (
                 $($parsed_fields)*
 
@@ -226,12 +240,13 @@ macro_rules! __parse_fields {
     // IvarEncode
     (
         (
+            @property[$(($($property:tt)*))?]
             $(#[$m:meta])*
             $vis:vis $field_name:ident: IvarEncode<$ty:ty, $ivar_name:literal>
             $(, $($rest_fields:tt)*)?
         )
         ($($ivar_helper_module_v:vis mod $ivar_helper_module:ident)?)
-        ($($ivar_output:tt)*) ($($ivar_type_name:ident)*)
+        ($($ivar_output:tt)*) ($($ivar:tt)*)
         ($($parsed_fields:tt)*)
 
         ($out_macro:path)
@@ -254,7 +269,7 @@ macro_rules! __parse_fields {
                     type Type = IvarEncode<$ty>;
                     const NAME: &'static $crate::__macro_helpers::str = $ivar_name;
                 }
-            ) ($($ivar_type_name)* $field_name)
+            ) ($($ivar)* ($field_name $(($($property)*))?))
             (
                 $($parsed_fields)*
 
@@ -270,12 +285,13 @@ macro_rules! __parse_fields {
     // IvarBool
     (
         (
+            @property[$(($($property:tt)*))?]
             $(#[$m:meta])*
             $vis:vis $field_name:ident: IvarBool<$ivar_name:literal>
             $(, $($rest_fields:tt)*)?
         )
         ($($ivar_helper_module_v:vis mod $ivar_helper_module:ident)?)
-        ($($ivar_output:tt)*) ($($ivar_type_name:ident)*)
+        ($($ivar_output:tt)*) ($($ivar:tt)*)
         ($($parsed_fields:tt)*)
 
         ($out_macro:path)
@@ -298,7 +314,7 @@ macro_rules! __parse_fields {
                     type Type = IvarBool;
                     const NAME: &'static $crate::__macro_helpers::str = $ivar_name;
                 }
-            ) ($($ivar_type_name)* $field_name)
+            ) ($($ivar)* ($field_name $(($($property)*))?))
             (
                 $($parsed_fields)*
 
@@ -314,12 +330,13 @@ macro_rules! __parse_fields {
     // Invalid type
     (
         (
+            @property[$(($($property:tt)*))?]
             $(#[$m:meta])*
             $vis:vis $field_name:ident: $ty:ty
             $(, $($rest_fields:tt)*)?
         )
         ($($ivar_helper_module_v:vis mod $ivar_helper_module:ident)?)
-        ($($ivar_output:tt)*) ($($ivar_type_name:ident)*)
+        ($($ivar_output:tt)*) ($($ivar:tt)*)
         ($($parsed_fields:tt)*)
 
         ($out_macro:path)
@@ -336,7 +353,7 @@ macro_rules! __parse_fields {
         $crate::__parse_fields! {
             ($($($rest_fields)*)?)
             ($($ivar_helper_module_v mod $ivar_helper_module)?)
-            ($($ivar_output)*) ($($ivar_type_name)*)
+            ($($ivar_output)*) ($($ivar)*)
             (
                 $($parsed_fields)*
 
@@ -347,5 +364,177 @@ macro_rules! __parse_fields {
             ($out_macro)
             $($macro_args)*
         }
-    }
+    };
+
+    // Extract `#[property(...)]` from the field's attributes
+    (
+        (
+            $(#[$($m:tt)*])*
+            $vis:vis $field_name:ident: $($rest:tt)*
+        )
+        $($args:tt)*
+    ) => {
+        $crate::__extract_property_attribute! {
+            ($(#[$($m)*])*)
+            () // No kept attributes
+            () // No property
+            ($vis $field_name: $($rest)*)
+            ($($args)*)
+        }
+    };
+}
+
+/// Split out the `#[property(...)]` attribute from a field's attributes,
+/// and pass the field back to `__parse_fields!` with an `@property` prefix.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __extract_property_attribute {
+    // Property
+    (
+        (#[property($($property:tt)*)] $($rest_attrs:tt)*)
+        ($($kept_attrs:tt)*)
+        ()
+        $($rest:tt)*
+    ) => {
+        $crate::__extract_property_attribute! {
+            ($($rest_attrs)*)
+            ($($kept_attrs)*)
+            (($($property)*))
+            $($rest)*
+        }
+    };
+
+    // Duplicate property
+    (
+        (#[property($($property:tt)*)] $($rest_attrs:tt)*)
+        ($($kept_attrs:tt)*)
+        ($($existing:tt)+)
+        $($rest:tt)*
+    ) => {
+        $crate::__macro_helpers::compile_error!("cannot specify `#[property(...)]` multiple times");
+
+        $crate::__extract_property_attribute! {
+            ($($rest_attrs)*)
+            ($($kept_attrs)*)
+            ($($existing)+)
+            $($rest)*
+        }
+    };
+
+    // Other attributes
+    (
+        (#[$($m:tt)*] $($rest_attrs:tt)*)
+        ($($kept_attrs:tt)*)
+        ($($property:tt)*)
+        $($rest:tt)*
+    ) => {
+        $crate::__extract_property_attribute! {
+            ($($rest_attrs)*)
+            ($($kept_attrs)* #[$($m)*])
+            ($($property)*)
+            $($rest)*
+        }
+    };
+
+    // Done
+    (
+        ()
+        ($($kept_attrs:tt)*)
+        ($($property:tt)*)
+        ($($field:tt)*)
+        ($($args:tt)*)
+    ) => {
+        $crate::__parse_fields! {
+            (@property[$($property)*] $($kept_attrs)* $($field)*)
+            $($args)*
+        }
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __declare_ivar {
+    (
+        ($builder:ident)
+        ($ivar:ident)
+    ) => {
+        $builder.add_static_ivar::<$ivar>();
+    };
+    (
+        ($builder:ident)
+        ($ivar:ident ($name:ident $(, $attribute:ident)* $(,)?))
+    ) => {
+        $builder.add_static_ivar::<$ivar>();
+
+        #[allow(unused_mut)]
+        let mut __objc2_attributes = $crate::runtime::PropertyAttributes::default();
+        $(
+            $crate::__property_attribute!(__objc2_attributes, $attribute);
+        )*
+        $crate::__macro_helpers::declare_property::<
+            $ivar,
+            $crate::__property_ownership!(() $($attribute)*),
+        >(
+            $builder,
+            $crate::__macro_helpers::stringify!($name),
+            __objc2_attributes,
+        );
+    };
+    (
+        ($builder:ident)
+        ($ivar:ident ($($property:tt)*))
+    ) => {
+        $crate::__macro_helpers::compile_error!($crate::__macro_helpers::concat!(
+            "invalid property attribute `#[property(",
+            $crate::__macro_helpers::stringify!($($property)*),
+            ")]`. Expected `#[property(name, ...)]`",
+        ));
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __property_attribute {
+    // Handled by `__property_ownership!`
+    ($attributes:ident, copy) => {};
+    ($attributes:ident, retain) => {};
+    ($attributes:ident, weak) => {};
+    ($attributes:ident, readonly) => {
+        $attributes.readonly = true;
+    };
+    ($attributes:ident, nonatomic) => {
+        $attributes.nonatomic = true;
+    };
+    ($attributes:ident, $other:ident) => {
+        $crate::__macro_helpers::compile_error!($crate::__macro_helpers::concat!(
+            "unknown property attribute `",
+            $crate::__macro_helpers::stringify!($other),
+            "`. Expected one of `copy`, `retain`, `weak`, `readonly` or `nonatomic`",
+        ))
+    };
+}
+
+/// Find the type-level ownership of a property from its attributes. If
+/// multiple are given, the last one is used.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __property_ownership {
+    (()) => {
+        $crate::__macro_helpers::ownership::Unspecified
+    };
+    (($($ownership:tt)+)) => {
+        $($ownership)+
+    };
+    (($($ownership:tt)*) copy $($rest:ident)*) => {
+        $crate::__property_ownership!(($crate::__macro_helpers::ownership::Copy) $($rest)*)
+    };
+    (($($ownership:tt)*) retain $($rest:ident)*) => {
+        $crate::__property_ownership!(($crate::__macro_helpers::ownership::Retain) $($rest)*)
+    };
+    (($($ownership:tt)*) weak $($rest:ident)*) => {
+        $crate::__property_ownership!(($crate::__macro_helpers::ownership::Weak) $($rest)*)
+    };
+    (($($ownership:tt)*) $other:ident $($rest:ident)*) => {
+        $crate::__property_ownership!(($($ownership)*) $($rest)*)
+    };
 }
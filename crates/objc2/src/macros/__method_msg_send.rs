@@ -122,6 +122,25 @@ macro_rules! __method_msg_send {
     };
 
     // Variadic method
+    //
+    // This is a textual match on `impl`, the trait bound is then checked
+    // when passing the arguments on.
+    (
+        ($receiver:expr)
+        ()
+        ($varargs:ident : impl $($_bound:tt)+)
+
+        ($($sel_parsed:tt)+)
+        ($($arg_parsed:tt)*)
+    ) => {
+        $crate::__msg_send_helper! {
+            @(send_message)
+            @($receiver)
+            @($($sel_parsed)*)
+            @($($arg_parsed)*)
+            @($varargs)
+        }
+    };
     (
         ($receiver:expr)
         ($($sel:ident : _)?)
@@ -131,7 +150,7 @@ macro_rules! __method_msg_send {
         ($($arg_parsed:tt)*)
     ) => ({
         $crate::__macro_helpers::compile_error!(
-            "variadic methods are not yet supported"
+            "variadic arguments must be specified as `args: impl VariadicArguments`"
         )
     });
 
@@ -307,6 +326,24 @@ macro_rules! __method_msg_send_id {
     };
 
     // Variadic method
+    (
+        ($receiver:expr)
+        ()
+        ($varargs:ident : impl $($_bound:tt)+)
+
+        ($($sel_parsed:tt)+)
+        ($($arg_parsed:tt)*)
+        ($($retain_semantics:ident)?)
+    ) => {
+        $crate::__msg_send_id_helper! {
+            @(send_message_id)
+            @($receiver)
+            @($($retain_semantics)?)
+            @($($sel_parsed)*)
+            @($($arg_parsed)*)
+            @($varargs)
+        }
+    };
     (
         ($receiver:expr)
         ($($sel:ident : _)?)
@@ -317,7 +354,7 @@ macro_rules! __method_msg_send_id {
         ($($retain_semantics:ident)?)
     ) => ({
         $crate::__macro_helpers::compile_error!(
-            "variadic methods are not yet supported"
+            "variadic arguments must be specified as `args: impl VariadicArguments`"
         )
     });
 
@@ -37,6 +37,22 @@ macro_rules! __msg_send_parse {
             @($($argument_output)*)
         }
     });
+    // Variadic arguments after the last `selector: argument` pair.
+    {
+        ($out_macro:path)
+        @($_error_fn:ident)
+        @($($selector_output:tt)+)
+        @($($argument_output:tt)*)
+        @(...$varargs:expr $(,)?)
+        $($macro_args:tt)*
+    } => ({
+        $out_macro! {
+            $($macro_args)*
+            @($($selector_output)*)
+            @($($argument_output)*)
+            @($varargs)
+        }
+    });
     {
         ($out_macro:path)
         @($error_fn:ident)
@@ -53,6 +53,48 @@
 /// [`declare::IvarType`]: crate::declare::IvarType
 ///
 ///
+/// ## Properties
+///
+/// An instance variable can be exposed as an Objective-C property by putting
+/// `#[property(name, ...)]` on the field. This registers the property with
+/// the runtime, along with a getter `name` and (unless `readonly`) a setter
+/// `setName:` that read and write the instance variable.
+///
+/// The following attributes are supported after the name:
+/// - `copy`, `retain` or `weak`: The ownership semantics of the setter.
+///   Object properties default to `retain`, and other types to `assign`.
+/// - `readonly`: Don't generate a setter.
+/// - `nonatomic`: Don't synchronize access. Atomic properties are
+///   synchronized with an internal lock, which is only held while reading or
+///   writing the instance variable.
+///
+/// The ivar must be one of the following, using an unsupported ownership is
+/// a compile error:
+/// - `IvarEncode<T, _>` or `IvarBool<_>`, which only support `assign`. Since
+///   the getter may read the instance variable before it has been
+///   initialized, `T` must be valid when zeroed, such as numbers and raw
+///   pointers.
+/// - `IvarDrop<Id<T>, _>` or `IvarDrop<Option<Id<T>>, _>`, which support
+///   `retain` and `copy`. Setting a non-optional property to `nil` aborts
+///   the process.
+/// - `IvarDrop<Option<WeakId<T>>, _>`, which only supports `weak`.
+///
+/// With `copy`, the setter stores the result of sending `copy` to the new
+/// value. Since that must be of the same type as the property, `copy` is
+/// only supported for [`CopyPropertyObject`] types, that is, classes with
+/// [`mutability::Immutable`] and blocks.
+///
+/// Block properties (using `Id<Block<A, R>>` from `block2`) default to, and
+/// only support, `copy`, since the block given to the setter may live on the
+/// stack.
+///
+/// The getter of an `IvarDrop<Id<T>, _>` property returns `nil` until the
+/// instance variable has been initialized.
+///
+/// [`CopyPropertyObject`]: crate::declare::CopyPropertyObject
+/// [`mutability::Immutable`]: crate::mutability::Immutable
+///
+///
 /// ## `ClassType` implementation
 ///
 /// This also resembles that in [`extern_class!`], except that
@@ -112,6 +154,15 @@
 /// Note that `&mut Id<_>` and other such out parameters are not yet
 /// supported, and may generate a panic at runtime.
 ///
+/// Since the methods are called from Objective-C, panicking inside them
+/// unwinds across an `extern "C"` boundary, which is undefined behaviour
+/// (or aborts, in newer Rust versions). If the `"panic-to-exception"`
+/// feature is enabled, panics are instead caught, and converted to an
+/// `NSException` named `RustPanic` with the panic message as the reason,
+/// which is then thrown using [`exception::throw`]. This requires nightly
+/// Rust, since the methods are then declared `extern "C-unwind"` (the
+/// feature enables `"unstable-c-unwind"`).
+///
 /// ["associated functions"]: https://doc.rust-lang.org/reference/items/associated-items.html#methods
 /// ["methods"]: https://doc.rust-lang.org/reference/items/associated-items.html#methods
 /// [`IsMutable`]: crate::mutability::IsMutable
@@ -119,6 +170,151 @@
 /// [open an issue]: https://github.com/madsmtm/objc2/issues/new
 /// [`msg_send!`]: crate::msg_send
 /// [`runtime::Bool`]: crate::runtime::Bool
+/// [`exception::throw`]: crate::exception::throw
+///
+///
+/// ## Message forwarding
+///
+/// The class can take part in Objective-C's message forwarding by using one
+/// of the following attributes in place of `#[method(...)]`:
+///
+/// - `#[forwarding_target]`: Implements `forwardingTargetForSelector:`. The
+///   method receives the selector that the object did not respond to, and
+///   returns an `Option<Id<AnyObject>>` to redirect the message to, or
+///   [`None`] to continue with the rest of the forwarding machinery.
+/// - `#[resolve_instance_method]` and `#[resolve_class_method]`: Implements
+///   `+resolveInstanceMethod:` and `+resolveClassMethod:`. These must be
+///   class methods that take the selector and return `bool`. They are called
+///   before the forwarding target is asked, and can be used to lazily add
+///   the method using [`AnyClass::add_method`] or
+///   [`AnyClass::add_class_method`] on `Self::class()`, in which case they
+///   should return `true`.
+///
+/// Full forwarding using `NSInvocation` can be implemented by overriding
+/// `methodSignatureForSelector:` and `forwardInvocation:` as usual.
+///
+/// ```
+/// use objc2::rc::Id;
+/// use objc2::runtime::{AnyObject, NSObject, Sel};
+/// use objc2::{declare_class, mutability, sel, ClassType};
+///
+/// declare_class!(
+///     struct Decorator;
+///
+///     unsafe impl ClassType for Decorator {
+///         type Super = NSObject;
+///         type Mutability = mutability::InteriorMutable;
+///         const NAME: &'static str = "Decorator";
+///     }
+///
+///     unsafe impl Decorator {
+///         #[forwarding_target]
+///         fn forwarding_target(&self, _sel: Sel) -> Option<Id<AnyObject>> {
+///             // Forward everything to a new object
+///             Some(Id::into_super(NSObject::new()))
+///         }
+///
+///         #[resolve_instance_method]
+///         fn resolve_instance_method(sel: Sel) -> bool {
+///             extern "C" fn answer(_this: &AnyObject, _cmd: Sel) -> u32 {
+///                 42
+///             }
+///
+///             if sel == sel!(answer) {
+///                 // SAFETY: The implementation has the correct signature.
+///                 unsafe { Self::class().add_method(sel, answer as extern "C" fn(_, _) -> _) }
+///             } else {
+///                 false
+///             }
+///         }
+///     }
+/// );
+/// #
+/// # let obj: Id<Decorator> = unsafe { objc2::msg_send_id![Decorator::class(), new] };
+/// # let answer: u32 = unsafe { objc2::msg_send![&obj, answer] };
+/// # assert_eq!(answer, 42);
+/// ```
+///
+/// [`AnyClass::add_method`]: crate::runtime::AnyClass::add_method
+/// [`AnyClass::add_class_method`]: crate::runtime::AnyClass::add_class_method
+///
+///
+/// ## Subclassing `NSProxy`
+///
+/// Besides `NSObject`, classes can also be declared with `NSProxy` as their
+/// superclass. Since that is a root class, the subclass is free to choose any
+/// [`ClassType::Mutability`].
+///
+/// Note that `NSProxy` does not implement `init`, so initializers must not
+/// call the superclass' `init` (as would usually be done); instead, they
+/// should just initialize the instance variables and return `this`. The
+/// macro itself never sends `init`, and the generated `dealloc` works the
+/// same as for `NSObject` subclasses, since `NSProxy` implements `dealloc`.
+///
+/// A proxy doesn't handle most messages itself, so the subclass will usually
+/// implement `methodSignatureForSelector:` and `forwardInvocation:` (or use
+/// one of the [message forwarding](#message-forwarding) attributes above) to
+/// forward them to some target object.
+///
+/// ```
+/// # use objc2::runtime::{NSObject, __NSProxy as NSProxy};
+/// # #[cfg(available_elsewhere)]
+/// use icrate::Foundation::{NSObject, NSProxy};
+/// use objc2::declare::IvarDrop;
+/// use objc2::rc::Id;
+/// use objc2::runtime::{AnyObject, Sel};
+/// use objc2::{declare_class, msg_send, msg_send_id, mutability, ClassType};
+///
+/// declare_class!(
+///     /// Prints the selector of every message sent to the target.
+///     struct TracingProxy {
+///         target: IvarDrop<Option<Id<NSObject>>, "_target">,
+///     }
+///
+///     mod ivars;
+///
+///     unsafe impl ClassType for TracingProxy {
+///         type Super = NSProxy;
+///         type Mutability = mutability::Mutable;
+///         const NAME: &'static str = "TracingProxy";
+///     }
+///
+///     unsafe impl TracingProxy {
+///         #[method(init)]
+///         fn init(this: &mut Self) -> Option<&mut Self> {
+///             // No need to call `[super init]`, there is no such method.
+///             Some(this)
+///         }
+///
+///         #[method(methodSignatureForSelector:)]
+///         fn method_signature_for_selector(&self, sel: Sel) -> *mut AnyObject {
+///             let target = self.target.as_deref().expect("target");
+///             unsafe { msg_send![target, methodSignatureForSelector: sel] }
+///         }
+///
+///         #[method(forwardInvocation:)]
+///         fn forward_invocation(&self, invocation: &AnyObject) {
+///             let target = self.target.as_deref().expect("target");
+///             let sel: Sel = unsafe { msg_send![invocation, selector] };
+///             println!("forwarding {sel} to {target:?}");
+///             unsafe { msg_send![invocation, invokeWithTarget: target] }
+///         }
+///     }
+/// );
+///
+/// impl TracingProxy {
+///     pub fn new(target: Id<NSObject>) -> Id<Self> {
+///         let mut proxy: Id<Self> = unsafe { msg_send_id![Self::alloc(), init] };
+///         *proxy.target = Some(target);
+///         proxy
+///     }
+/// }
+///
+/// let proxy = TracingProxy::new(NSObject::new());
+/// // Forwarded to the `NSObject` through `forwardInvocation:`
+/// let is_kind: bool = unsafe { msg_send![&proxy, isKindOfClass: NSObject::class()] };
+/// assert!(is_kind);
+/// ```
 ///
 ///
 /// ## Protocol implementations
@@ -582,6 +778,9 @@ macro_rules! __inner_declare_class {
                         // Note: ARC does this automatically, which means
                         // most Objective-C code in the wild don't contain
                         // this; but we _are_ ARC, so we must do this.
+                        //
+                        // Root classes like `NSProxy` implement `dealloc`
+                        // as well, so this also works for their subclasses.
                         unsafe {
                             $crate::MessageReceiver::__send_super_message_static(
                                 __objc2_self,
@@ -624,6 +823,9 @@ macro_rules! __inner_declare_class {
             }
         }
 
+        // SAFETY: Declared classes are not generic.
+        unsafe impl $crate::DowncastTarget for $for {}
+
         // Methods
         $crate::__declare_class_methods! {
             $($methods)*
@@ -1011,14 +1213,18 @@ macro_rules! __declare_class_method_out_inner {
         ($($args_converted:tt)*)
         ($($body_prefix:tt)*)
     } => {
-        $($m_checked)*
-        $($qualifiers)* extern "C" fn $name(
-            $($args_prefix)*
-            $($args_converted)*
-        ) $(-> <$ret as $crate::encode::__unstable::EncodeConvertReturn>::__Inner)? {
-            $($body_prefix)*
-            $crate::__convert_result! {
-                $body $(; $ret)?
+        $crate::__extern_method_fn! {
+            ($($m_checked)*)
+            ($($qualifiers)*) fn $name(
+                $($args_prefix)*
+                $($args_converted)*
+            ) $(-> <$ret as $crate::encode::__unstable::EncodeConvertReturn>::__Inner)? {
+                $crate::__convert_panics! {{
+                    $($body_prefix)*
+                    $crate::__convert_result! {
+                        $body $(; $ret)?
+                    }
+                }}
             }
         }
     };
@@ -1042,27 +1248,31 @@ macro_rules! __declare_class_method_out_inner {
         ($($args_converted:tt)*)
         ($($body_prefix:tt)*)
     } => {
-        $($m_checked)*
-        $($qualifiers)* extern "C" fn $name(
-            $($args_prefix)*
-            $($args_converted)*
-        ) -> $crate::declare::__IdReturnValue {
-            $($body_prefix)*
-
-            let __objc2_result = $body;
-
-            #[allow(unreachable_code)]
-            <$crate::__macro_helpers::RetainSemantics<{
-                $crate::__macro_helpers::retain_semantics(
-                    $crate::__sel_helper! {
-                        @()
-                        $($sel)*
-                    }
-                )
-            }> as $crate::__macro_helpers::MessageRecieveId<
-                $receiver_ty,
-                $ret,
-            >>::into_return(__objc2_result)
+        $crate::__extern_method_fn! {
+            ($($m_checked)*)
+            ($($qualifiers)*) fn $name(
+                $($args_prefix)*
+                $($args_converted)*
+            ) -> $crate::declare::__IdReturnValue {
+                $crate::__convert_panics! {{
+                    $($body_prefix)*
+
+                    let __objc2_result = $body;
+
+                    #[allow(unreachable_code)]
+                    <$crate::__macro_helpers::RetainSemantics<{
+                        $crate::__macro_helpers::retain_semantics(
+                            $crate::__sel_helper! {
+                                @()
+                                $($sel)*
+                            }
+                        )
+                    }> as $crate::__macro_helpers::MessageRecieveId<
+                        $receiver_ty,
+                        $ret,
+                    >>::into_return(__objc2_result)
+                }}
+            }
         }
     };
 
@@ -1091,6 +1301,95 @@ macro_rules! __declare_class_method_out_inner {
     };
 }
 
+/// Emit the function of a method with the ABI used by `declare_class!`.
+///
+/// With the `"panic-to-exception"` feature, panics are converted to
+/// exceptions that unwind out of the method, so it must be `"C-unwind"`.
+#[doc(hidden)]
+#[macro_export]
+#[cfg(feature = "panic-to-exception")]
+macro_rules! __extern_method_fn {
+    (
+        ($($attrs:tt)*)
+        ($($qualifiers:tt)*) fn $name:ident $args:tt $(-> $ret:ty)? $body:block
+    ) => {
+        $($attrs)*
+        $($qualifiers)* extern "C-unwind" fn $name $args $(-> $ret)? $body
+    };
+    (
+        ($($attrs:tt)*)
+        ($($qualifiers:tt)*) fn $name:ident [$($generics:tt)*] $args:tt $(-> $ret:ty)?
+        where [$($bounds:tt)*] $body:block
+    ) => {
+        $($attrs)*
+        $($qualifiers)* extern "C-unwind" fn $name<$($generics)*> $args $(-> $ret)?
+        where
+            $($bounds)*
+        $body
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+#[cfg(not(feature = "panic-to-exception"))]
+macro_rules! __extern_method_fn {
+    (
+        ($($attrs:tt)*)
+        ($($qualifiers:tt)*) fn $name:ident $args:tt $(-> $ret:ty)? $body:block
+    ) => {
+        $($attrs)*
+        $($qualifiers)* extern "C" fn $name $args $(-> $ret)? $body
+    };
+    (
+        ($($attrs:tt)*)
+        ($($qualifiers:tt)*) fn $name:ident [$($generics:tt)*] $args:tt $(-> $ret:ty)?
+        where [$($bounds:tt)*] $body:block
+    ) => {
+        $($attrs)*
+        $($qualifiers)* extern "C" fn $name<$($generics)*> $args $(-> $ret)?
+        where
+            $($bounds)*
+        $body
+    };
+}
+
+/// The function pointer type of functions created by `__extern_method_fn!`.
+#[doc(hidden)]
+#[macro_export]
+#[cfg(feature = "panic-to-exception")]
+macro_rules! __extern_method_fn_ptr {
+    (($($qualifiers:tt)*) ($($output:tt)*)) => {
+        $($qualifiers)* extern "C-unwind" fn($($output)*) -> _
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+#[cfg(not(feature = "panic-to-exception"))]
+macro_rules! __extern_method_fn_ptr {
+    (($($qualifiers:tt)*) ($($output:tt)*)) => {
+        $($qualifiers)* extern "C" fn($($output)*) -> _
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+#[cfg(feature = "panic-to-exception")]
+macro_rules! __convert_panics {
+    ($body:block) => {
+        $crate::__macro_helpers::convert_panics(move || $body)
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+#[cfg(not(feature = "panic-to-exception"))]
+macro_rules! __convert_panics {
+    ($body:block) => {
+        $body
+    };
+}
+
 #[doc(hidden)]
 #[macro_export]
 macro_rules! __convert_result {
@@ -1135,6 +1434,60 @@ macro_rules! __declare_class_register_out {
         }
     };
 
+    // #[method(resolveInstanceMethod:)], from `#[resolve_instance_method]`,
+    // on an instance method
+    {
+        ($builder:ident)
+        ($($qualifiers:tt)*)
+        ($name:ident)
+        ($($__ret:ty)?)
+        ($__body:block)
+
+        (add_method)
+        ($__receiver:expr)
+        ($__receiver_ty:ty)
+        ($($__args_prefix:tt)*)
+        ($($args_rest:tt)*)
+
+        (#[method(resolveInstanceMethod:)])
+        () // No optional
+        ($($m_checked:tt)*)
+    } => {
+        $crate::__extract_and_apply_cfg_attributes! {
+            @($($m_checked)*)
+            @($crate::__macro_helpers::compile_error!(
+                "`#[resolve_instance_method]` must be a class method (it cannot take `self`)"
+            ))
+        }
+    };
+
+    // #[method(resolveClassMethod:)], from `#[resolve_class_method]`, on an
+    // instance method
+    {
+        ($builder:ident)
+        ($($qualifiers:tt)*)
+        ($name:ident)
+        ($($__ret:ty)?)
+        ($__body:block)
+
+        (add_method)
+        ($__receiver:expr)
+        ($__receiver_ty:ty)
+        ($($__args_prefix:tt)*)
+        ($($args_rest:tt)*)
+
+        (#[method(resolveClassMethod:)])
+        () // No optional
+        ($($m_checked:tt)*)
+    } => {
+        $crate::__extract_and_apply_cfg_attributes! {
+            @($($m_checked)*)
+            @($crate::__macro_helpers::compile_error!(
+                "`#[resolve_class_method]` must be a class method (it cannot take `self`)"
+            ))
+        }
+    };
+
     // #[method(...)]
     {
         ($builder:ident)
@@ -1270,7 +1623,7 @@ macro_rules! __fn_ptr {
         ($($output:tt)*)
         $(,)?
     ) => {
-        $($qualifiers)* extern "C" fn($($output)*) -> _
+        $crate::__extern_method_fn_ptr!(($($qualifiers)*) ($($output)*))
     };
     (
         ($($qualifiers:tt)*)
@@ -21,6 +21,7 @@
 /// - [`RefEncode`][crate::RefEncode]
 /// - [`Message`][crate::Message]
 /// - [`ClassType`][crate::ClassType]
+/// - [`DowncastTarget`][crate::DowncastTarget]
 /// - [`Deref<Target = $superclass>`][core::ops::Deref]
 /// - [`DerefMut`][core::ops::DerefMut]
 /// - [`AsRef<$inheritance_chain>`][AsRef]
@@ -243,6 +244,10 @@ macro_rules! extern_class {
             }
         );
 
+        // SAFETY: The class is not generic.
+        $(#[$impl_m])*
+        unsafe impl $crate::DowncastTarget for $for {}
+
         $(#[$impl_m])*
         const _: () = {
             if $crate::__macro_helpers::size_of::<$name>() != 0 {
@@ -26,6 +26,12 @@
 /// [`Result`]. See the error section in [`msg_send!`] and [`msg_send_id!`]
 /// for details.
 ///
+/// If the last parameter is specified as `args: impl VariadicArguments`, and
+/// is not part of the selector, the method is assumed to be variadic, and
+/// `args` is passed as the variadic arguments, see the section on this in
+/// [`msg_send!`]. Like with `MainThreadMarker` below, this is a textual match
+/// on `impl`.
+///
 /// If you use `icrate::Foundation::MainThreadMarker` as a parameter type, the
 /// macro will ignore it, allowing you to neatly specify "this method must be
 /// run on the main thread". Note that due to type-system limitations, this is
@@ -69,7 +75,7 @@
 /// use objc2::ffi::NSUInteger;
 /// use objc2::rc::{Allocated, Id};
 /// use objc2::runtime::NSObject;
-/// use objc2::{declare_class, extern_methods, mutability, ClassType};
+/// use objc2::{declare_class, extern_methods, mutability, ClassType, VariadicArguments};
 ///
 /// // Shim
 /// type NSError = NSObject;
@@ -98,6 +104,11 @@
 ///         // Arbitary self types are not stable, but we can work around it
 ///         // with the special name `this`.
 ///         pub fn init(this: Option<Allocated<Self>>, val: usize) -> Id<Self>;
+///
+///         #[method_id(objectWithFormat:)]
+///         // The trailing `impl VariadicArguments` makes this a variadic
+///         // method.
+///         pub fn with_format(format: &NSObject, args: impl VariadicArguments) -> Id<Self>;
 ///     }
 ///
 ///     /// Instance accessor methods.
@@ -123,7 +134,7 @@
 /// # use objc2::ffi::NSUInteger;
 /// # use objc2::rc::{Allocated, Id};
 /// # use objc2::runtime::NSObject;
-/// # use objc2::{declare_class, extern_methods, mutability, ClassType};
+/// # use objc2::{declare_class, extern_methods, mutability, ClassType, VariadicArguments};
 /// #
 /// # // Shim
 /// # type NSError = NSObject;
@@ -153,6 +164,10 @@
 ///     pub fn init(this: Option<Allocated<Self>>, val: usize) -> Id<Self> {
 ///         unsafe { msg_send_id![this, initWithVal: val] }
 ///     }
+///
+///     pub fn with_format(format: &NSObject, args: impl VariadicArguments) -> Id<Self> {
+///         unsafe { msg_send_id![Self::class(), objectWithFormat: format, ...args] }
+///     }
 /// }
 ///
 /// /// Instance accessor methods.
@@ -750,7 +750,8 @@ macro_rules! __class_inner {
 /// references, try refactoring into a separate method or reborrowing the
 /// reference.
 ///
-/// Variadic arguments are currently not supported.
+/// Variadic arguments can be given after the last argument as `...args`,
+/// see below.
 ///
 /// [`MessageReceiver`]: crate::MessageReceiver
 /// [`rc::Id`]: crate::rc::Id
@@ -825,6 +826,28 @@ macro_rules! __class_inner {
 /// [swift-error]: https://developer.apple.com/documentation/swift/about-imported-cocoa-error-parameters
 ///
 ///
+/// # Variadic arguments
+///
+/// Methods such as `+[NSString stringWithFormat:]` and
+/// `+[NSArray arrayWithObjects:]` take a variable number of arguments after
+/// the fixed ones. These can be passed as a tuple after the last argument,
+/// prefixed with `...`, e.g. `msg_send![cls, stringWithFormat: format,
+/// ...(1i32, 2.0f64)]`.
+///
+/// The tuple must implement [`VariadicArguments`], which means that each
+/// element must implement [`VaArg`]. In particular, C's default argument
+/// promotions apply, so `bool`, `f32` and integers smaller than `int` must be
+/// converted before being passed.
+///
+/// Only the fixed arguments are verified against the method's encoding when
+/// `debug_assertions` are enabled, so you must make sure that the variadic
+/// arguments have the types that the method expects (e.g. by the format
+/// string), and that they're terminated appropriately (e.g. with `NULL`).
+///
+/// [`VariadicArguments`]: crate::VariadicArguments
+/// [`VaArg`]: crate::VaArg
+///
+///
 /// # Panics
 ///
 /// Panics if the `"catch-all"` feature is enabled and the Objective-C method
@@ -983,6 +1006,24 @@ macro_rules! __class_inner {
 ///
 /// # Ok::<(), Id<NSError>>(())
 /// ```
+///
+/// Sending a message with variadic arguments.
+///
+/// ```no_run
+/// use std::os::raw::c_int;
+/// use objc2::msg_send;
+/// use objc2::runtime::NSObject;
+///
+/// # type NSString = NSObject;
+/// let cls = objc2::class!(NSString);
+/// let format: &NSString;
+/// # format = todo!();
+/// let obj: &NSObject;
+/// # obj = todo!();
+/// let string: *mut NSString = unsafe {
+///     msg_send![cls, stringWithFormat: format, ...(42 as c_int, obj, 1.5f64)]
+/// };
+/// ```
 #[macro_export]
 macro_rules! msg_send {
     [super($obj:expr), $($selector_and_arguments:tt)+] => {
@@ -1045,6 +1086,21 @@ macro_rules! __msg_send_helper {
         result = $crate::MessageReceiver::$fn::<_, _>($($fn_args)+, $crate::sel!($($selector)*), ($($argument,)*));
         result
     });
+    {
+        @($fn:ident)
+        @($($fn_args:tt)+)
+        @($($selector:tt)*)
+        @($($argument:expr,)*)
+        @($varargs:expr)
+    } => ({
+        let result;
+        result = $crate::MessageReceiver::$fn::<_, _>(
+            $($fn_args)+,
+            $crate::sel!($($selector)*),
+            $crate::__macro_helpers::__Variadic(($($argument,)*), $varargs),
+        );
+        result
+    });
 }
 
 /// Deprecated. Use [`msg_send!`] instead.
@@ -1172,6 +1228,12 @@ macro_rules! msg_send_bool {
 /// specify `E` yourself, usually you'd use `icrate::Foundation::NSError`).
 ///
 ///
+/// # Variadic arguments
+///
+/// Like [`msg_send!`], variadic arguments can be given as a tuple after the
+/// last argument, prefixed with `...`.
+///
+///
 /// # Panics
 ///
 /// Panics if the return type is specified as `Id<_, _>` and the method
@@ -1340,4 +1402,43 @@ macro_rules! __msg_send_id_helper {
         );
         result
     });
+    // Same as above, but with variadic arguments.
+    {
+        @($fn:ident)
+        @($obj:expr)
+        @($retain_semantics:ident)
+        @($($selector:tt)*)
+        @($($argument:expr,)*)
+        @($varargs:expr)
+    } => ({
+        <$crate::__macro_helpers::$retain_semantics as $crate::__macro_helpers::MsgSendId<_, _>>::$fn::<_, _>(
+            $obj,
+            $crate::sel!($($selector)*),
+            $crate::__macro_helpers::__Variadic(($($argument,)*), $varargs),
+        )
+    });
+    {
+        @($fn:ident)
+        @($obj:expr)
+        @()
+        @($($selector:tt)*)
+        @($($argument:expr,)*)
+        @($varargs:expr)
+    } => ({
+        const __SELECTOR_DATA: &$crate::__macro_helpers::str = $crate::__sel_data!(
+            $($selector)*
+        );
+        let result;
+        result = <$crate::__macro_helpers::RetainSemantics<{
+            $crate::__macro_helpers::retain_semantics(__SELECTOR_DATA)
+        }> as $crate::__macro_helpers::MsgSendId<_, _>>::$fn::<_, _>(
+            $obj,
+            $crate::__sel_inner!(
+                __SELECTOR_DATA,
+                $crate::__hash_idents!($($selector)*)
+            ),
+            $crate::__macro_helpers::__Variadic(($($argument,)*), $varargs),
+        );
+        result
+    });
 }
@@ -32,7 +32,13 @@ where
     R: EncodeReturn,
 {
     let msg_send_fn = R::MSG_SEND;
-    unsafe { conditional_try!(|| A::__invoke(msg_send_fn, receiver, sel, args)) }
+    unsafe {
+        conditional_try!(
+            || A::__invoke(msg_send_fn, receiver, sel, args),
+            receiver,
+            sel,
+        )
+    }
 }
 
 #[inline]
@@ -52,9 +58,9 @@ where
         receiver: receiver.cast(),
         super_class: superclass.cast(),
     };
-    let receiver: *mut ffi::objc_super = &mut sup;
-    let receiver = receiver.cast();
+    let sup: *mut ffi::objc_super = &mut sup;
+    let sup = sup.cast();
 
     let msg_send_fn = R::MSG_SEND_SUPER;
-    unsafe { conditional_try!(|| A::__invoke(msg_send_fn, receiver, sel, args)) }
+    unsafe { conditional_try!(|| A::__invoke(msg_send_fn, sup, sel, args), receiver, sel,) }
 }
@@ -38,7 +38,13 @@ where
 
     let msg_send_fn = unsafe { ffi::objc_msg_lookup(receiver.cast(), sel.as_ptr()) };
     let msg_send_fn = unwrap_msg_send_fn(msg_send_fn);
-    unsafe { conditional_try!(|| A::__invoke(msg_send_fn, receiver, sel, args)) }
+    unsafe {
+        conditional_try!(
+            || A::__invoke(msg_send_fn, receiver, sel, args),
+            receiver,
+            sel,
+        )
+    }
 }
 
 #[track_caller]
@@ -64,5 +70,11 @@ where
     };
     let msg_send_fn = unsafe { ffi::objc_msg_lookup_super(&sup, sel.as_ptr()) };
     let msg_send_fn = unwrap_msg_send_fn(msg_send_fn);
-    unsafe { conditional_try!(|| A::__invoke(msg_send_fn, receiver, sel, args)) }
+    unsafe {
+        conditional_try!(
+            || A::__invoke(msg_send_fn, receiver, sel, args),
+            receiver,
+            sel,
+        )
+    }
 }
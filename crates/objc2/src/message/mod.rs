@@ -17,30 +17,57 @@ use crate::ClassType;
 /// This is a macro to help with monomorphization when the feature is
 /// disabled, as well as improving the final stack trace (`#[track_caller]`
 /// doesn't really work on closures).
+///
+/// The receiver and selector are only used to describe where the exception
+/// came from.
 #[cfg(not(feature = "catch-all"))]
 macro_rules! conditional_try {
-    (|| $expr:expr) => {
+    (|| $expr:expr, $receiver:expr, $sel:expr $(,)?) => {
         $expr
     };
 }
 
 #[cfg(feature = "catch-all")]
 macro_rules! conditional_try {
-    (|| $expr:expr) => {{
+    (|| $expr:expr, $receiver:expr, $sel:expr $(,)?) => {{
+        // Look up the class before sending the message, since the receiver
+        // may have been deallocated by the time the exception is thrown.
+        //
+        // SAFETY: The receiver is valid or NULL, since it is about to be
+        // sent a message (the macro is always used in an `unsafe` block).
+        let cls = $receiver.as_ref().map(|receiver| receiver.class());
         let f = core::panic::AssertUnwindSafe(|| $expr);
         match crate::exception::catch(f) {
             Ok(r) => r,
-            Err(exception) => {
-                if let Some(exception) = exception {
-                    panic!("uncaught {exception:?}")
-                } else {
-                    panic!("uncaught exception nil")
-                }
-            }
+            Err(exception) => crate::message::panic_exception(exception, cls, $sel),
         }
     }};
 }
 
+/// Panic with an exception that was caught by the `catch-all` feature, along
+/// with a description of the message send that threw it.
+#[cfg(feature = "catch-all")]
+#[track_caller]
+fn panic_exception(
+    exception: Option<Id<crate::exception::Exception>>,
+    cls: Option<&AnyClass>,
+    sel: Sel,
+) -> ! {
+    let location = core::panic::Location::caller();
+    let exception = match &exception {
+        Some(exception) => alloc::format!("{exception:?}"),
+        None => "exception nil".into(),
+    };
+    if let Some(cls) = cls {
+        panic!(
+            "uncaught {exception}, thrown by {}[{cls} {sel}] at {location}",
+            if cls.is_metaclass() { "+" } else { "-" },
+        )
+    } else {
+        panic!("uncaught {exception}, thrown by {sel} sent to nil at {location}")
+    }
+}
+
 /// Help with monomorphizing in `icrate`
 #[cfg(debug_assertions)]
 #[track_caller]
@@ -93,7 +120,10 @@ mod platform;
 #[path = "gnustep.rs"]
 mod platform;
 
+mod variadic;
+
 use self::platform::{send_super_unverified, send_unverified};
+pub use self::variadic::{__Variadic, VaArg, VariadicArguments};
 
 /// Types that can be sent Objective-C messages.
 ///
@@ -497,6 +527,17 @@ pub unsafe trait MessageArguments: EncodeArguments {
     /// with a dynamic selector, the [`MessageReceiver::send_message`] method.
     #[doc(hidden)]
     unsafe fn __invoke<R: EncodeReturn>(imp: Imp, obj: *mut AnyObject, sel: Sel, args: Self) -> R;
+
+    /// Invoke an [`Imp`] with the given object, selector, and arguments,
+    /// followed by the given variadic arguments.
+    #[doc(hidden)]
+    unsafe fn __invoke_variadic<V: VariadicArguments, R: EncodeReturn>(
+        imp: Imp,
+        obj: *mut AnyObject,
+        sel: Sel,
+        args: Self,
+        varargs: V,
+    ) -> R;
 }
 
 pub trait __TupleExtender<T> {
@@ -507,7 +548,7 @@ pub trait __TupleExtender<T> {
 }
 
 macro_rules! message_args_impl {
-    ($($a:ident: $t:ident),*) => (
+    ($invoke_variadic:ident; $($a:ident: $t:ident),*) => (
         unsafe impl<$($t: EncodeConvertArgument),*> MessageArguments for ($($t,)*) {
             #[inline]
             unsafe fn __invoke<R: EncodeReturn>(imp: Imp, obj: *mut AnyObject, sel: Sel, ($($a,)*): Self) -> R {
@@ -541,6 +582,28 @@ macro_rules! message_args_impl {
                 )*
                 result
             }
+
+            #[inline]
+            unsafe fn __invoke_variadic<V: VariadicArguments, R: EncodeReturn>(
+                imp: Imp,
+                obj: *mut AnyObject,
+                sel: Sel,
+                ($($a,)*): Self,
+                varargs: V,
+            ) -> R {
+                $(let $a = EncodeConvertArgument::__into_argument($a);)*
+
+                // The variadic arguments must be spelled out in the function
+                // pointer type along with the fixed ones, so the call is done
+                // by `VariadicArguments`.
+                let result = unsafe { varargs.$invoke_variadic(imp, obj, sel $(, $a.0)*) };
+
+                $(
+                    // SAFETY: Same as in `__invoke`.
+                    unsafe { <$t as EncodeConvertArgument>::__process_after_message_send($a.1) };
+                )*
+                result
+            }
         }
 
         impl<$($t,)* T> __TupleExtender<T> for ($($t,)*) {
@@ -554,18 +617,19 @@ macro_rules! message_args_impl {
     );
 }
 
-message_args_impl!();
-message_args_impl!(a: A);
-message_args_impl!(a: A, b: B);
-message_args_impl!(a: A, b: B, c: C);
-message_args_impl!(a: A, b: B, c: C, d: D);
-message_args_impl!(a: A, b: B, c: C, d: D, e: E);
-message_args_impl!(a: A, b: B, c: C, d: D, e: E, f: F);
-message_args_impl!(a: A, b: B, c: C, d: D, e: E, f: F, g: G);
-message_args_impl!(a: A, b: B, c: C, d: D, e: E, f: F, g: G, h: H);
-message_args_impl!(a: A, b: B, c: C, d: D, e: E, f: F, g: G, h: H, i: I);
-message_args_impl!(a: A, b: B, c: C, d: D, e: E, f: F, g: G, h: H, i: I, j: J);
+message_args_impl!(__invoke0;);
+message_args_impl!(__invoke1; a: A);
+message_args_impl!(__invoke2; a: A, b: B);
+message_args_impl!(__invoke3; a: A, b: B, c: C);
+message_args_impl!(__invoke4; a: A, b: B, c: C, d: D);
+message_args_impl!(__invoke5; a: A, b: B, c: C, d: D, e: E);
+message_args_impl!(__invoke6; a: A, b: B, c: C, d: D, e: E, f: F);
+message_args_impl!(__invoke7; a: A, b: B, c: C, d: D, e: E, f: F, g: G);
+message_args_impl!(__invoke8; a: A, b: B, c: C, d: D, e: E, f: F, g: G, h: H);
+message_args_impl!(__invoke9; a: A, b: B, c: C, d: D, e: E, f: F, g: G, h: H, i: I);
+message_args_impl!(__invoke10; a: A, b: B, c: C, d: D, e: E, f: F, g: G, h: H, i: I, j: J);
 message_args_impl!(
+    __invoke11;
     a: A,
     b: B,
     c: C,
@@ -579,6 +643,7 @@ message_args_impl!(
     k: K
 );
 message_args_impl!(
+    __invoke12;
     a: A,
     b: B,
     c: C,
@@ -593,6 +658,7 @@ message_args_impl!(
     l: L
 );
 message_args_impl!(
+    __invoke13;
     a: A,
     b: B,
     c: C,
@@ -608,6 +674,7 @@ message_args_impl!(
     m: M
 );
 message_args_impl!(
+    __invoke14;
     a: A,
     b: B,
     c: C,
@@ -624,6 +691,7 @@ message_args_impl!(
     n: N
 );
 message_args_impl!(
+    __invoke15;
     a: A,
     b: B,
     c: C,
@@ -641,6 +709,7 @@ message_args_impl!(
     o: O
 );
 message_args_impl!(
+    __invoke16;
     a: A,
     b: B,
     c: C,
@@ -666,7 +735,9 @@ mod tests {
     use crate::rc::Id;
     use crate::runtime::NSObject;
     use crate::test_utils;
-    use crate::{declare_class, msg_send, msg_send_id};
+    use crate::{class, declare_class, msg_send, msg_send_id};
+    use std::ffi::CStr;
+    use std::os::raw::{c_char, c_int};
 
     declare_class!(
         struct MutableObject;
@@ -792,4 +863,65 @@ mod tests {
         };
         // `obj` is consumed, can't use here
     }
+
+    crate::extern_class!(
+        struct NSString;
+
+        unsafe impl ClassType for NSString {
+            type Super = NSObject;
+            type Mutability = mutability::Immutable;
+        }
+    );
+
+    crate::extern_methods!(
+        unsafe impl NSString {
+            #[method_id(stringWithUTF8String:)]
+            unsafe fn from_c_str(s: *const c_char) -> Id<Self>;
+
+            #[method_id(stringWithFormat:)]
+            unsafe fn with_format(format: &NSString, args: impl VariadicArguments) -> Id<Self>;
+
+            #[method(UTF8String)]
+            fn utf8(&self) -> *const c_char;
+        }
+    );
+
+    impl NSString {
+        fn to_str(&self) -> &str {
+            unsafe { CStr::from_ptr(self.utf8()) }.to_str().unwrap()
+        }
+    }
+
+    #[test]
+    fn test_send_message_variadic() {
+        let format = unsafe { NSString::from_c_str(b"%d %@ %.1f\0".as_ptr().cast()) };
+        let obj = unsafe { NSString::from_c_str(b"abc\0".as_ptr().cast()) };
+
+        let s = unsafe { NSString::with_format(&format, (42 as c_int, &*obj, 1.5f64)) };
+        assert_eq!(s.to_str(), "42 abc 1.5");
+
+        let s: Id<NSString> = unsafe {
+            msg_send_id![
+                NSString::class(),
+                stringWithFormat: &*format,
+                ...(-1 as c_int, &*s, 0.5f64),
+            ]
+        };
+        assert_eq!(s.to_str(), "-1 42 abc 1.5 0.5");
+
+        // No variadic arguments
+        let s = unsafe { NSString::with_format(&obj, ()) };
+        assert_eq!(s.to_str(), "abc");
+
+        // Terminated by `nil`
+        let count: usize = unsafe {
+            let array: *mut NSObject = msg_send![
+                class!(NSArray),
+                arrayWithObjects: &*format,
+                ...(&*obj, &*s, ptr::null_mut::<NSObject>()),
+            ];
+            msg_send![array, count]
+        };
+        assert_eq!(count, 3);
+    }
 }
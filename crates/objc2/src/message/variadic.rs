@@ -0,0 +1,323 @@
+use core::ffi::c_void;
+use core::mem;
+use core::ptr::NonNull;
+
+use crate::encode::__unstable::EncodeReturn;
+use crate::encode::{Encode, Encoding, RefEncode};
+use crate::runtime::{AnyObject, Imp, Sel};
+use crate::MessageArguments;
+
+/// Types that can be passed as the variadic arguments of an Objective-C
+/// message, such as the arguments following the format string in
+/// `+[NSString stringWithFormat:]`.
+///
+/// C applies the "default argument promotions" to variadic arguments, which
+/// means that types smaller than `int` are passed as `int`, and `float` is
+/// passed as `double`. Such types therefore do not implement this trait, and
+/// must be converted by the caller; e.g. `bool` and [`Bool`] should be
+/// passed as `c_int`, and [`f32`] as [`f64`].
+///
+/// [`Bool`]: crate::runtime::Bool
+///
+///
+/// # Safety
+///
+/// The type must be passed in the same way as the C type it encodes to,
+/// after the default argument promotions have been applied.
+pub unsafe trait VaArg: Encode {}
+
+unsafe impl VaArg for i32 {}
+unsafe impl VaArg for u32 {}
+unsafe impl VaArg for i64 {}
+unsafe impl VaArg for u64 {}
+unsafe impl VaArg for isize {}
+unsafe impl VaArg for usize {}
+unsafe impl VaArg for f64 {}
+
+unsafe impl VaArg for Sel {}
+unsafe impl VaArg for Option<Sel> {}
+
+// Only implemented for sized types, since pointers to unsized types are not
+// passed as a single pointer.
+unsafe impl<T: RefEncode> VaArg for *const T {}
+unsafe impl<T: RefEncode> VaArg for *mut T {}
+unsafe impl<T: RefEncode> VaArg for NonNull<T> {}
+unsafe impl<T: RefEncode> VaArg for Option<NonNull<T>> {}
+unsafe impl<T: RefEncode> VaArg for &T {}
+unsafe impl<T: RefEncode> VaArg for &mut T {}
+unsafe impl<T: RefEncode> VaArg for Option<&T> {}
+unsafe impl<T: RefEncode> VaArg for Option<&mut T> {}
+
+// `c_void` is not `RefEncode`, but pointers to it are still useful.
+unsafe impl VaArg for *const c_void {}
+unsafe impl VaArg for *mut c_void {}
+unsafe impl VaArg for NonNull<c_void> {}
+unsafe impl VaArg for Option<NonNull<c_void>> {}
+
+mod private {
+    pub trait Sealed {}
+}
+
+macro_rules! invoke_fn {
+    ($($invoke:ident($($a:ident: $t:ident),*);)*) => {
+        $(
+            #[doc(hidden)]
+            #[allow(clippy::too_many_arguments)]
+            unsafe fn $invoke<$($t,)* R: EncodeReturn>(
+                self,
+                imp: Imp,
+                obj: *mut AnyObject,
+                sel: Sel,
+                $($a: $t,)*
+            ) -> R;
+        )*
+    };
+}
+
+/// Types that may be used as the variadic arguments of an Objective-C
+/// message.
+///
+/// This is implemented for tuples of up to 16 arguments, where each argument
+/// implements [`VaArg`].
+///
+/// These are passed after the fixed arguments with `...args` in
+/// [`msg_send!`] and [`msg_send_id!`], or with a trailing
+/// `args: impl VariadicArguments` parameter in [`extern_methods!`].
+///
+/// [`msg_send!`]: crate::msg_send
+/// [`msg_send_id!`]: crate::msg_send_id
+/// [`extern_methods!`]: crate::extern_methods
+///
+///
+/// # Safety
+///
+/// This is a sealed trait, and should not need to be implemented.
+pub unsafe trait VariadicArguments: private::Sealed + Sized {
+    /// The encodings for the arguments.
+    const ENCODINGS: &'static [Encoding];
+
+    // Invoke an `Imp` with the given fixed arguments followed by `self`.
+    //
+    // There is one of these for each number of fixed arguments, since the
+    // function pointer must be declared with both the fixed and the variadic
+    // arguments spelled out.
+    invoke_fn! {
+        __invoke0();
+        __invoke1(a: A);
+        __invoke2(a: A, b: B);
+        __invoke3(a: A, b: B, c: C);
+        __invoke4(a: A, b: B, c: C, d: D);
+        __invoke5(a: A, b: B, c: C, d: D, e: E);
+        __invoke6(a: A, b: B, c: C, d: D, e: E, f: F);
+        __invoke7(a: A, b: B, c: C, d: D, e: E, f: F, g: G);
+        __invoke8(a: A, b: B, c: C, d: D, e: E, f: F, g: G, h: H);
+        __invoke9(a: A, b: B, c: C, d: D, e: E, f: F, g: G, h: H, i: I);
+        __invoke10(a: A, b: B, c: C, d: D, e: E, f: F, g: G, h: H, i: I, j: J);
+        __invoke11(a: A, b: B, c: C, d: D, e: E, f: F, g: G, h: H, i: I, j: J, k: K);
+        __invoke12(a: A, b: B, c: C, d: D, e: E, f: F, g: G, h: H, i: I, j: J, k: K, l: L);
+        __invoke13(a: A, b: B, c: C, d: D, e: E, f: F, g: G, h: H, i: I, j: J, k: K, l: L, m: M);
+        __invoke14(a: A, b: B, c: C, d: D, e: E, f: F, g: G, h: H, i: I, j: J, k: K, l: L, m: M, n: N);
+        __invoke15(a: A, b: B, c: C, d: D, e: E, f: F, g: G, h: H, i: I, j: J, k: K, l: L, m: M, n: N, o: O);
+        __invoke16(a: A, b: B, c: C, d: D, e: E, f: F, g: G, h: H, i: I, j: J, k: K, l: L, m: M, n: N, o: O, p: P);
+    }
+}
+
+macro_rules! invoke_impl {
+    ($invoke:ident($($a:ident: $t:ident),*) with ($($v:ident),*)) => {
+        #[inline]
+        #[allow(clippy::too_many_arguments)]
+        unsafe fn $invoke<$($t,)* R: EncodeReturn>(
+            self,
+            imp: Imp,
+            obj: *mut AnyObject,
+            sel: Sel,
+            $($a: $t,)*
+        ) -> R {
+            let ($($v,)*) = self;
+
+            // Same as in `MessageArguments::__invoke`, except that the
+            // function pointer is variadic.
+            #[cfg(not(feature = "unstable-c-unwind"))]
+            let imp: unsafe extern "C" fn(*mut AnyObject, Sel $(, $t)*, ...) -> R = unsafe {
+                mem::transmute(imp)
+            };
+            #[cfg(feature = "unstable-c-unwind")]
+            let imp: unsafe extern "C-unwind" fn(*mut AnyObject, Sel $(, $t)*, ...) -> R = unsafe {
+                mem::transmute(imp)
+            };
+            unsafe { imp(obj, sel $(, $a)* $(, $v)*) }
+        }
+    };
+}
+
+macro_rules! variadic_args_impl {
+    ($($v:ident: $vt:ident),*) => (
+        impl<$($vt: VaArg),*> private::Sealed for ($($vt,)*) {}
+
+        unsafe impl<$($vt: VaArg),*> VariadicArguments for ($($vt,)*) {
+            const ENCODINGS: &'static [Encoding] = &[$($vt::ENCODING),*];
+
+            invoke_impl!(__invoke0() with ($($v),*));
+            invoke_impl!(__invoke1(a: A) with ($($v),*));
+            invoke_impl!(__invoke2(a: A, b: B) with ($($v),*));
+            invoke_impl!(__invoke3(a: A, b: B, c: C) with ($($v),*));
+            invoke_impl!(__invoke4(a: A, b: B, c: C, d: D) with ($($v),*));
+            invoke_impl!(__invoke5(a: A, b: B, c: C, d: D, e: E) with ($($v),*));
+            invoke_impl!(__invoke6(a: A, b: B, c: C, d: D, e: E, f: F) with ($($v),*));
+            invoke_impl!(__invoke7(a: A, b: B, c: C, d: D, e: E, f: F, g: G) with ($($v),*));
+            invoke_impl!(__invoke8(a: A, b: B, c: C, d: D, e: E, f: F, g: G, h: H) with ($($v),*));
+            invoke_impl!(__invoke9(a: A, b: B, c: C, d: D, e: E, f: F, g: G, h: H, i: I) with ($($v),*));
+            invoke_impl!(__invoke10(a: A, b: B, c: C, d: D, e: E, f: F, g: G, h: H, i: I, j: J) with ($($v),*));
+            invoke_impl!(__invoke11(a: A, b: B, c: C, d: D, e: E, f: F, g: G, h: H, i: I, j: J, k: K) with ($($v),*));
+            invoke_impl!(__invoke12(a: A, b: B, c: C, d: D, e: E, f: F, g: G, h: H, i: I, j: J, k: K, l: L) with ($($v),*));
+            invoke_impl!(__invoke13(a: A, b: B, c: C, d: D, e: E, f: F, g: G, h: H, i: I, j: J, k: K, l: L, m: M) with ($($v),*));
+            invoke_impl!(__invoke14(a: A, b: B, c: C, d: D, e: E, f: F, g: G, h: H, i: I, j: J, k: K, l: L, m: M, n: N) with ($($v),*));
+            invoke_impl!(__invoke15(a: A, b: B, c: C, d: D, e: E, f: F, g: G, h: H, i: I, j: J, k: K, l: L, m: M, n: N, o: O) with ($($v),*));
+            invoke_impl!(__invoke16(a: A, b: B, c: C, d: D, e: E, f: F, g: G, h: H, i: I, j: J, k: K, l: L, m: M, n: N, o: O, p: P) with ($($v),*));
+        }
+    );
+}
+
+variadic_args_impl!();
+variadic_args_impl!(v0: V0);
+variadic_args_impl!(v0: V0, v1: V1);
+variadic_args_impl!(v0: V0, v1: V1, v2: V2);
+variadic_args_impl!(v0: V0, v1: V1, v2: V2, v3: V3);
+variadic_args_impl!(v0: V0, v1: V1, v2: V2, v3: V3, v4: V4);
+variadic_args_impl!(v0: V0, v1: V1, v2: V2, v3: V3, v4: V4, v5: V5);
+variadic_args_impl!(v0: V0, v1: V1, v2: V2, v3: V3, v4: V4, v5: V5, v6: V6);
+variadic_args_impl!(v0: V0, v1: V1, v2: V2, v3: V3, v4: V4, v5: V5, v6: V6, v7: V7);
+variadic_args_impl!(v0: V0, v1: V1, v2: V2, v3: V3, v4: V4, v5: V5, v6: V6, v7: V7, v8: V8);
+variadic_args_impl!(
+    v0: V0,
+    v1: V1,
+    v2: V2,
+    v3: V3,
+    v4: V4,
+    v5: V5,
+    v6: V6,
+    v7: V7,
+    v8: V8,
+    v9: V9
+);
+variadic_args_impl!(
+    v0: V0,
+    v1: V1,
+    v2: V2,
+    v3: V3,
+    v4: V4,
+    v5: V5,
+    v6: V6,
+    v7: V7,
+    v8: V8,
+    v9: V9,
+    v10: V10
+);
+variadic_args_impl!(
+    v0: V0,
+    v1: V1,
+    v2: V2,
+    v3: V3,
+    v4: V4,
+    v5: V5,
+    v6: V6,
+    v7: V7,
+    v8: V8,
+    v9: V9,
+    v10: V10,
+    v11: V11
+);
+variadic_args_impl!(
+    v0: V0,
+    v1: V1,
+    v2: V2,
+    v3: V3,
+    v4: V4,
+    v5: V5,
+    v6: V6,
+    v7: V7,
+    v8: V8,
+    v9: V9,
+    v10: V10,
+    v11: V11,
+    v12: V12
+);
+variadic_args_impl!(
+    v0: V0,
+    v1: V1,
+    v2: V2,
+    v3: V3,
+    v4: V4,
+    v5: V5,
+    v6: V6,
+    v7: V7,
+    v8: V8,
+    v9: V9,
+    v10: V10,
+    v11: V11,
+    v12: V12,
+    v13: V13
+);
+variadic_args_impl!(
+    v0: V0,
+    v1: V1,
+    v2: V2,
+    v3: V3,
+    v4: V4,
+    v5: V5,
+    v6: V6,
+    v7: V7,
+    v8: V8,
+    v9: V9,
+    v10: V10,
+    v11: V11,
+    v12: V12,
+    v13: V13,
+    v14: V14
+);
+variadic_args_impl!(
+    v0: V0,
+    v1: V1,
+    v2: V2,
+    v3: V3,
+    v4: V4,
+    v5: V5,
+    v6: V6,
+    v7: V7,
+    v8: V8,
+    v9: V9,
+    v10: V10,
+    v11: V11,
+    v12: V12,
+    v13: V13,
+    v14: V14,
+    v15: V15
+);
+
+/// The fixed arguments of a message, followed by its variadic arguments.
+///
+/// This implements [`MessageArguments`] with the encodings of only the fixed
+/// arguments, since those are the only ones that the method's type encoding
+/// contains.
+///
+/// [`MessageArguments`]: crate::MessageArguments
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct __Variadic<A, V>(pub A, pub V);
+
+unsafe impl<A: MessageArguments, V: VariadicArguments> MessageArguments for __Variadic<A, V> {
+    #[inline]
+    unsafe fn __invoke<R: EncodeReturn>(imp: Imp, obj: *mut AnyObject, sel: Sel, args: Self) -> R {
+        unsafe { A::__invoke_variadic(imp, obj, sel, args.0, args.1) }
+    }
+
+    unsafe fn __invoke_variadic<V2: VariadicArguments, R: EncodeReturn>(
+        _imp: Imp,
+        _obj: *mut AnyObject,
+        _sel: Sel,
+        _args: Self,
+        _varargs: V2,
+    ) -> R {
+        unreachable!("cannot pass variadic arguments twice")
+    }
+}
@@ -7,8 +7,8 @@ use core::ptr::{self, NonNull};
 
 use super::AutoreleasePool;
 use crate::mutability::{IsIdCloneable, IsMutable};
-use crate::runtime::{objc_release_fast, objc_retain_fast};
-use crate::{ffi, ClassType, Message};
+use crate::runtime::{objc_release_fast, objc_retain_fast, AnyObject};
+use crate::{ffi, ClassType, DowncastTarget, Message};
 
 /// A reference counted pointer type for Objective-C objects.
 ///
@@ -618,6 +618,48 @@ where
     }
 }
 
+impl Id<AnyObject> {
+    /// Attempt to downcast the object to a class of type `T`.
+    ///
+    /// This succeeds if the object is an instance of `T` or one of its
+    /// subclasses, as determined by the object's class (not by sending it
+    /// `isKindOfClass:`, which proxies may forward). Otherwise, the object is
+    /// returned back in [`Err`].
+    ///
+    /// The class must be cloneable, since the object may be shared with
+    /// other code that expects it to be immutable (e.g. an
+    /// `NSMutableString` that was returned as an `NSString`).
+    ///
+    /// See [`AnyObject::downcast_ref`] for doing the same on a reference.
+    ///
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use objc2::rc::Id;
+    /// use objc2::runtime::{AnyObject, NSObject};
+    ///
+    /// let obj: Id<AnyObject> = Id::into_super(NSObject::new());
+    /// let obj: Id<NSObject> = Id::downcast(obj).unwrap();
+    /// ```
+    #[inline]
+    pub fn downcast<T>(this: Self) -> Result<Id<T>, Self>
+    where
+        T: DowncastTarget + IsIdCloneable + 'static,
+    {
+        if this.is_kind_of_class(T::class()) {
+            // SAFETY:
+            // - Just checked that the object is an instance of `T`, which
+            //   `DowncastTarget` guarantees is valid to use as `T`.
+            // - The type is cloneable, so we don't assume unique ownership.
+            // - `T` is `'static`, so no lifetime information is added.
+            Ok(unsafe { Self::cast::<T>(this) })
+        } else {
+            Err(this)
+        }
+    }
+}
+
 // TODO: Add ?Sized bound
 impl<T: IsIdCloneable> Clone for Id<T> {
     /// Makes a clone of the shared object.
@@ -979,6 +1021,33 @@ mod tests {
         expected.assert_current();
     }
 
+    #[test]
+    fn test_downcast() {
+        let obj = __RcTestObject::new();
+        let ptr: *const __RcTestObject = &*obj;
+        let mut expected = __ThreadTestData::current();
+
+        let obj: Id<AnyObject> = Id::into_super(Id::into_super(obj));
+        assert!(obj.downcast_ref::<NSObject>().is_some());
+        assert!(ptr::eq(obj.downcast_ref::<__RcTestObject>().unwrap(), ptr));
+
+        let obj = Id::downcast::<NSObject>(obj).unwrap();
+        let obj: Id<AnyObject> = Id::into_super(obj);
+        let obj = Id::downcast::<__RcTestObject>(obj).unwrap();
+        assert!(ptr::eq(&*obj, ptr));
+        expected.assert_current();
+
+        drop(obj);
+        expected.release += 1;
+        expected.dealloc += 1;
+        expected.assert_current();
+
+        let obj: Id<AnyObject> = Id::into_super(NSObject::new());
+        assert!(obj.downcast_ref::<__RcTestObject>().is_none());
+        let obj = Id::downcast::<__RcTestObject>(obj).unwrap_err();
+        assert!(obj.downcast_ref::<NSObject>().is_some());
+    }
+
     #[repr(C)]
     struct MyObject<'a> {
         inner: NSObject,
@@ -78,7 +78,7 @@ impl<T: Message> WeakId<T> {
     /// # Safety
     ///
     /// The object must be valid or null.
-    unsafe fn new_inner(obj: *const T) -> Self {
+    pub(crate) unsafe fn new_inner(obj: *const T) -> Self {
         let inner = Box::new(UnsafeCell::new(ptr::null_mut()));
         // SAFETY: `ptr` will never move, and the caller verifies `obj`
         let _ = unsafe { ffi::objc_initWeak(inner.get(), (obj as *mut T).cast()) };
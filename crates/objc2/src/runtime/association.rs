@@ -0,0 +1,365 @@
+//! Typed wrappers around Objective-C's associated objects.
+use alloc::boxed::Box;
+use core::ffi::c_void;
+use core::fmt;
+use core::marker::PhantomData;
+
+use crate::declare::{Ivar, IvarDrop};
+use crate::mutability::{Immutable, IsIdCloneable};
+use crate::rc::{Id, WeakId};
+use crate::runtime::{AnyObject, NSObject};
+use crate::{declare_class, ffi, msg_send, msg_send_id, ClassType};
+
+/// The memory management policy of an associated object.
+///
+/// See [Apple's documentation](https://developer.apple.com/documentation/objectivec/objc_associationpolicy?language=objc).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[doc(alias = "objc_AssociationPolicy")]
+pub enum AssociationPolicy {
+    /// A weak reference to the associated object, that is _not_ cleared when
+    /// the associated object is deallocated.
+    ///
+    /// Use [`WeakId`] as the key's value type if you want a zeroing weak
+    /// reference.
+    #[doc(alias = "OBJC_ASSOCIATION_ASSIGN")]
+    Assign,
+    /// A strong reference to the associated object, not set atomically.
+    #[doc(alias = "OBJC_ASSOCIATION_RETAIN_NONATOMIC")]
+    RetainNonatomic,
+    /// The associated object is copied, and not set atomically.
+    #[doc(alias = "OBJC_ASSOCIATION_COPY_NONATOMIC")]
+    CopyNonatomic,
+    /// A strong reference to the associated object, set atomically.
+    #[doc(alias = "OBJC_ASSOCIATION_RETAIN")]
+    Retain,
+    /// The associated object is copied, and set atomically.
+    #[doc(alias = "OBJC_ASSOCIATION_COPY")]
+    Copy,
+}
+
+impl AssociationPolicy {
+    /// Convert the policy to the raw value that the runtime expects.
+    #[inline]
+    pub const fn as_raw(self) -> ffi::objc_AssociationPolicy {
+        match self {
+            Self::Assign => ffi::OBJC_ASSOCIATION_ASSIGN,
+            Self::RetainNonatomic => ffi::OBJC_ASSOCIATION_RETAIN_NONATOMIC,
+            Self::CopyNonatomic => ffi::OBJC_ASSOCIATION_COPY_NONATOMIC,
+            Self::Retain => ffi::OBJC_ASSOCIATION_RETAIN,
+            Self::Copy => ffi::OBJC_ASSOCIATION_COPY,
+        }
+    }
+}
+
+/// A typed key for storing associated objects on arbitrary objects.
+///
+/// Associated objects allow attaching extra objects to existing objects at
+/// runtime (even to objects whose class you do not control), and are
+/// automatically released when the object they're attached to is
+/// deallocated.
+///
+/// The key is identified by its address, so it must be stored in a
+/// `static`. The value type `V` is either [`Id<T>`], which stores a strong
+/// reference, or [`WeakId<T>`], which stores a weak reference that is
+/// cleared when the associated object is deallocated.
+///
+/// See [Apple's documentation](https://developer.apple.com/documentation/objectivec/1418509-objc_setassociatedobject?language=objc).
+///
+///
+/// # Example
+///
+/// ```
+/// use objc2::rc::{autoreleasepool, Id, WeakId};
+/// use objc2::runtime::{AssociationKey, NSObject};
+///
+/// static STRONG: AssociationKey<Id<NSObject>> = AssociationKey::new();
+/// static WEAK: AssociationKey<WeakId<NSObject>> = AssociationKey::new();
+///
+/// let obj = NSObject::new();
+/// let value = NSObject::new();
+///
+/// STRONG.set(&obj, Some(&value));
+/// WEAK.set(&obj, Some(&value));
+/// autoreleasepool(|_| {
+///     assert_eq!(STRONG.get(&obj).as_ref(), Some(&value));
+///     assert_eq!(WEAK.get(&obj).as_ref(), Some(&value));
+/// });
+///
+/// // The weak reference is cleared once the last strong reference is gone.
+/// STRONG.remove(&obj);
+/// drop(value);
+/// assert!(STRONG.get(&obj).is_none());
+/// assert!(WEAK.get(&obj).is_none());
+/// ```
+pub struct AssociationKey<V> {
+    // Also ensures that the key is not zero-sized, which is important since
+    // the runtime uses the address of the key to identify it.
+    policy: AssociationPolicy,
+    p: PhantomData<fn() -> V>,
+}
+
+impl<V> AssociationKey<V> {
+    /// Create a new key.
+    ///
+    /// If the value type is [`Id<T>`], the key stores a strong reference to
+    /// the associated object, and sets it atomically.
+    ///
+    /// If the value type is [`WeakId<T>`], the key stores a weak reference
+    /// to the associated object. The runtime doesn't support this natively,
+    /// so the weak reference is stored inside a small helper object.
+    #[inline]
+    #[allow(clippy::new_without_default)]
+    pub const fn new() -> Self {
+        Self {
+            policy: AssociationPolicy::Retain,
+            p: PhantomData,
+        }
+    }
+
+    #[inline]
+    fn as_ptr(&'static self) -> *const c_void {
+        let ptr: *const Self = self;
+        ptr.cast()
+    }
+
+    /// # Safety
+    ///
+    /// The value must be valid for the key's policy.
+    unsafe fn set_raw(&'static self, obj: &AnyObject, value: *mut AnyObject) {
+        // SAFETY: The object is valid, and the key is a unique, static
+        // pointer. The rest is upheld by the caller.
+        unsafe {
+            ffi::objc_setAssociatedObject(
+                obj.as_ptr() as *mut ffi::objc_object,
+                self.as_ptr(),
+                value.cast(),
+                self.policy.as_raw(),
+            )
+        }
+    }
+
+    fn get_raw(&'static self, obj: &AnyObject) -> *mut AnyObject {
+        // SAFETY: The object is valid, and the key is a unique, static
+        // pointer.
+        let ptr = unsafe { ffi::objc_getAssociatedObject(obj.as_ptr(), self.as_ptr()) };
+        ptr as *mut AnyObject
+    }
+
+    /// Remove the value associated with this key on the given object.
+    ///
+    /// This is equivalent to calling `set` with [`None`].
+    #[inline]
+    pub fn remove(&'static self, obj: &AnyObject) {
+        // SAFETY: Setting NULL is always valid.
+        unsafe { self.set_raw(obj, core::ptr::null_mut()) }
+    }
+}
+
+impl<T: ?Sized> AssociationKey<Id<T>> {
+    /// Create a new key that uses the given memory management policy.
+    ///
+    ///
+    /// # Safety
+    ///
+    /// If the policy is [`AssociationPolicy::Copy`] or
+    /// [`AssociationPolicy::CopyNonatomic`], the result of calling `copy` on
+    /// the value must be an instance of `T`.
+    ///
+    /// If the policy is [`AssociationPolicy::Assign`], you must ensure that
+    /// the value outlives the object it is associated with (or is removed
+    /// before it is deallocated).
+    ///
+    /// If the policy is non-atomic, you must ensure that the association is
+    /// not modified on another thread while [`get`][Self::get] is running.
+    #[inline]
+    pub const unsafe fn with_policy(policy: AssociationPolicy) -> Self {
+        Self {
+            policy,
+            p: PhantomData,
+        }
+    }
+
+    /// The memory management policy that this key uses.
+    #[inline]
+    pub const fn policy(&self) -> AssociationPolicy {
+        self.policy
+    }
+}
+
+impl<T: IsIdCloneable> AssociationKey<Id<T>> {
+    /// Set the object associated with this key on the given object,
+    /// replacing any previous association.
+    ///
+    /// Passing [`None`] removes the association.
+    #[doc(alias = "objc_setAssociatedObject")]
+    pub fn set(&'static self, obj: &AnyObject, value: Option<&Id<T>>) {
+        let value = value.map_or(core::ptr::null(), Id::as_ptr);
+        // SAFETY: The value is a valid object of type `T` (or NULL), and the
+        // policy is upheld by the creator of the key.
+        unsafe { self.set_raw(obj, value as *mut AnyObject) }
+    }
+
+    /// Get the object associated with this key on the given object, if any.
+    #[doc(alias = "objc_getAssociatedObject")]
+    pub fn get(&'static self, obj: &AnyObject) -> Option<Id<T>> {
+        let ptr: *mut T = self.get_raw(obj).cast();
+        // SAFETY: The associated value was set through this key, so it is of
+        // type `T`, and the key's policy ensures that it is still alive.
+        //
+        // `T` is `IsIdCloneable`, so retaining it again is fine.
+        unsafe { Id::retain(ptr) }
+    }
+}
+
+impl<T: IsIdCloneable> AssociationKey<WeakId<T>> {
+    /// Set a weak reference to the object associated with this key on the
+    /// given object, replacing any previous association.
+    ///
+    /// Passing [`None`] removes the association.
+    #[doc(alias = "objc_setAssociatedObject")]
+    pub fn set(&'static self, obj: &AnyObject, value: Option<&Id<T>>) {
+        match value {
+            Some(value) => {
+                let value: *const AnyObject = Id::as_ptr(value).cast();
+                let weak = WeakAssociation::new(value);
+                // SAFETY: The helper object is retained by the runtime.
+                unsafe { self.set_raw(obj, Id::as_ptr(&weak) as *mut AnyObject) }
+            }
+            None => self.remove(obj),
+        }
+    }
+
+    /// Load the object associated with this key on the given object, if it
+    /// has been set and still exists.
+    #[doc(alias = "objc_getAssociatedObject")]
+    pub fn get(&'static self, obj: &AnyObject) -> Option<Id<T>> {
+        let ptr: *mut WeakAssociation = self.get_raw(obj).cast();
+        // SAFETY: Values associated with this key are always instances of
+        // `WeakAssociation`, which are retained by the runtime.
+        let weak = unsafe { Id::retain(ptr) }?;
+        let obj = weak.weak.load()?;
+        // SAFETY: The weak reference was created from an `Id<T>`.
+        Some(unsafe { Id::cast::<T>(obj) })
+    }
+}
+
+impl<V> fmt::Debug for AssociationKey<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AssociationKey")
+            .field("policy", &self.policy)
+            .finish_non_exhaustive()
+    }
+}
+
+declare_class!(
+    /// Helper object for storing a weak reference as an associated object.
+    struct WeakAssociation {
+        weak: IvarDrop<Box<WeakId<AnyObject>>, "_objc2_weak">,
+    }
+
+    mod ivars;
+
+    unsafe impl ClassType for WeakAssociation {
+        type Super = NSObject;
+        type Mutability = Immutable;
+        const NAME: &'static str = "__Objc2WeakAssociation";
+    }
+
+    unsafe impl WeakAssociation {
+        #[method(initWithObject:)]
+        unsafe fn init_with_object(this: *mut Self, obj: *const AnyObject) -> *mut Self {
+            let this: Option<&mut Self> = unsafe { msg_send![super(this), init] };
+            match this {
+                Some(this) => {
+                    // SAFETY: The object is valid, as checked by the caller.
+                    let weak = unsafe { WeakId::new_inner(obj) };
+                    Ivar::write(&mut this.weak, Box::new(weak));
+                    this
+                }
+                None => core::ptr::null_mut(),
+            }
+        }
+    }
+);
+
+impl WeakAssociation {
+    fn new(obj: *const AnyObject) -> Id<Self> {
+        unsafe { msg_send_id![Self::alloc(), initWithObject: obj] }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rc::{__RcTestObject, __ThreadTestData, autoreleasepool};
+
+    #[test]
+    fn test_strong() {
+        static KEY: AssociationKey<Id<__RcTestObject>> = AssociationKey::new();
+
+        let obj = NSObject::new();
+        let value = __RcTestObject::new();
+        let mut expected = __ThreadTestData::current();
+
+        KEY.set(&obj, Some(&value));
+        expected.retain += 1;
+        expected.assert_current();
+
+        autoreleasepool(|_| {
+            let loaded = KEY.get(&obj).unwrap();
+            assert_eq!(Id::as_ptr(&loaded), Id::as_ptr(&value));
+        });
+
+        KEY.remove(&obj);
+        assert!(KEY.get(&obj).is_none());
+
+        // Associated objects are released when the object is deallocated
+        KEY.set(&obj, Some(&value));
+        let mut expected = __ThreadTestData::current();
+        drop(obj);
+        expected.release += 1;
+        expected.assert_current();
+    }
+
+    #[test]
+    fn test_weak() {
+        static KEY: AssociationKey<WeakId<__RcTestObject>> = AssociationKey::new();
+
+        let obj = NSObject::new();
+        assert!(KEY.get(&obj).is_none());
+
+        let value = __RcTestObject::new();
+        KEY.set(&obj, Some(&value));
+        autoreleasepool(|_| {
+            let loaded = KEY.get(&obj).unwrap();
+            assert_eq!(Id::as_ptr(&loaded), Id::as_ptr(&value));
+        });
+
+        let mut expected = __ThreadTestData::current();
+        drop(value);
+        expected.release += 1;
+        expected.dealloc += 1;
+        expected.assert_current();
+        autoreleasepool(|_| assert!(KEY.get(&obj).is_none()));
+
+        KEY.set(&obj, None);
+        assert!(KEY.get(&obj).is_none());
+    }
+
+    #[test]
+    fn test_keys_are_distinct() {
+        static KEY1: AssociationKey<Id<NSObject>> = AssociationKey::new();
+        static KEY2: AssociationKey<Id<NSObject>> = AssociationKey::new();
+
+        let obj = NSObject::new();
+        let value = NSObject::new();
+        KEY1.set(&obj, Some(&value));
+        autoreleasepool(|_| {
+            assert!(KEY1.get(&obj).is_some());
+            assert!(KEY2.get(&obj).is_none());
+        });
+
+        unsafe { obj.remove_associated_objects() };
+        assert!(KEY1.get(&obj).is_none());
+    }
+}
@@ -0,0 +1,1169 @@
+//! Sending messages whose types are only known at runtime.
+//!
+//! Instead of going through a library like `libffi`, we implement the parts
+//! of the calling conventions that we need ourselves: Every argument is
+//! classified into integer registers, floating point registers or stack
+//! memory, and the implementation is then called through a function pointer
+//! that takes all of the registers that could be used for arguments,
+//! followed by enough words to cover the stack memory.
+//!
+//! This works because the registers used for integers and floating point
+//! values are allocated independently of each other, so a method taking
+//! `(i32, f64, i32)` receives its arguments in the same registers as one
+//! taking `(i32, i32, f64)`.
+//!
+//! See the System V x86-64 psABI and the AAPCS64 for details, as well as
+//! Apple's deviations from the latter:
+//! <https://developer.apple.com/documentation/xcode/writing-arm64-code-for-apple-platforms>
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ffi::c_void;
+use core::mem::{self, MaybeUninit};
+
+use crate::encode::{Encoding, EncodingBox, PointerWidth};
+use crate::runtime::{AnyClass, AnyObject, Imp, Sel};
+
+/// A value whose type is only known at runtime.
+///
+/// This is used for the arguments and the return value of
+/// [`AnyObject::send_dynamic`], and each variant corresponds to one or more
+/// of the cases of [`EncodingBox`]:
+///
+/// | Variant        | Encodings                                          |
+/// | -------------- | -------------------------------------------------- |
+/// | `Void`         | `v`                                                |
+/// | `Bool`         | `B`                                                |
+/// | `Char`, ...    | `c`, `s`, `i`, `l`, `q`, `C`, `S`, `I`, `L`, `Q`   |
+/// | `Float`        | `f`                                                |
+/// | `Double`       | `d`                                                |
+/// | `Object`       | `@` and `@?` (blocks)                              |
+/// | `Class`        | `#`                                                |
+/// | `Sel`          | `:`                                                |
+/// | `Pointer`      | `^...`, `*` and `?`                                |
+/// | `Struct`       | `{name=...}`, with one value for each field        |
+///
+/// Note that `BOOL` is encoded as `c` on some platforms, in which case it is
+/// passed as [`DynValue::Char`].
+///
+/// Objects are passed as raw pointers, since the memory management of the
+/// return value depends on the method.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DynValue {
+    /// The return value of methods that don't return anything.
+    Void,
+    /// A C `_Bool` / Rust [`bool`].
+    Bool(bool),
+    /// A C `char` (signed).
+    Char(i8),
+    /// A C `short`.
+    Short(i16),
+    /// A C `int`.
+    Int(i32),
+    /// A 32-bit integer encoded as `l`.
+    ///
+    /// The runtime always treats `l` as a 32-bit quantity, and a C `long` on
+    /// 64-bit targets is instead encoded as `q`, see
+    /// [`Encoding::C_LONG`].
+    Long(i32),
+    /// A C `long long`.
+    LongLong(i64),
+    /// A C `unsigned char`.
+    UChar(u8),
+    /// A C `unsigned short`.
+    UShort(u16),
+    /// A C `unsigned int`.
+    UInt(u32),
+    /// A 32-bit unsigned integer encoded as `L`.
+    ///
+    /// See [`DynValue::Long`].
+    ULong(u32),
+    /// A C `unsigned long long`.
+    ULongLong(u64),
+    /// A C `float`.
+    Float(f32),
+    /// A C `double`.
+    Double(f64),
+    /// An object or a block, possibly NULL.
+    Object(*mut AnyObject),
+    /// A class, possibly NULL.
+    Class(*const AnyClass),
+    /// A selector, possibly NULL.
+    Sel(Option<Sel>),
+    /// Any other pointer, including C strings and function pointers.
+    Pointer(*mut c_void),
+    /// A struct, containing the values of each of its fields.
+    Struct(Vec<DynValue>),
+}
+
+/// A scalar part of a type, and its offset from the start of the type.
+#[derive(Debug, Clone, Copy)]
+struct Leaf {
+    offset: usize,
+    size: usize,
+    float: bool,
+}
+
+const fn round_up(n: usize, align: usize) -> usize {
+    (n + align - 1) / align * align
+}
+
+/// Compute the size and alignment of the type, and append its scalar parts
+/// to `leaves`.
+///
+/// Returns [`None`] if the type is not supported.
+fn flatten(enc: &EncodingBox, offset: usize, leaves: &mut Vec<Leaf>) -> Option<(usize, usize)> {
+    let width = PointerWidth::NATIVE;
+    let (size, align) = match enc {
+        EncodingBox::Char
+        | EncodingBox::UChar
+        | EncodingBox::Bool
+        | EncodingBox::Short
+        | EncodingBox::UShort
+        | EncodingBox::Int
+        | EncodingBox::UInt
+        | EncodingBox::Long
+        | EncodingBox::ULong
+        | EncodingBox::LongLong
+        | EncodingBox::ULongLong
+        | EncodingBox::Float
+        | EncodingBox::Double
+        | EncodingBox::Object
+        | EncodingBox::Block
+        | EncodingBox::Class
+        | EncodingBox::Sel
+        | EncodingBox::String
+        | EncodingBox::Pointer(_) => (enc.size(width)?, enc.align(width)?),
+        // `?` is passed as a function pointer, which has no layout on its
+        // own.
+        EncodingBox::Unknown => {
+            let ptr = Encoding::Pointer(&Encoding::Unknown);
+            (ptr.size(width)?, ptr.align(width)?)
+        }
+        EncodingBox::Atomic(inner) => return flatten(inner, offset, leaves),
+        EncodingBox::Struct(_, Some(fields)) if !fields.is_empty() => {
+            let mut size = 0;
+            let mut align = 1;
+            for field in fields {
+                let field_align = layout(field)?.1;
+                size = round_up(size, field_align);
+                size += flatten(field, offset + size, leaves)?.0;
+                align = align.max(field_align);
+            }
+            return Some((round_up(size, align), align));
+        }
+        _ => return None,
+    };
+    leaves.push(Leaf {
+        offset,
+        size,
+        float: matches!(enc, EncodingBox::Float | EncodingBox::Double),
+    });
+    Some((size, align))
+}
+
+fn layout(enc: &EncodingBox) -> Option<(usize, usize)> {
+    flatten(enc, 0, &mut Vec::new())
+}
+
+impl DynValue {
+    /// Write the value to the start of `buf`, if it matches the encoding.
+    #[must_use]
+    fn write(&self, enc: &EncodingBox, buf: &mut [u8]) -> bool {
+        macro_rules! put {
+            ($value:expr) => {{
+                let bytes = $value.to_ne_bytes();
+                buf[..bytes.len()].copy_from_slice(&bytes);
+                true
+            }};
+        }
+        match (self, enc) {
+            (_, EncodingBox::Atomic(inner)) => self.write(inner, buf),
+            (Self::Bool(v), EncodingBox::Bool) => put!(*v as u8),
+            (Self::Char(v), EncodingBox::Char) => put!(v),
+            (Self::Short(v), EncodingBox::Short) => put!(v),
+            (Self::Int(v), EncodingBox::Int) => put!(v),
+            (Self::Long(v), EncodingBox::Long) => put!(v),
+            (Self::LongLong(v), EncodingBox::LongLong) => put!(v),
+            (Self::UChar(v), EncodingBox::UChar) => put!(v),
+            (Self::UShort(v), EncodingBox::UShort) => put!(v),
+            (Self::UInt(v), EncodingBox::UInt) => put!(v),
+            (Self::ULong(v), EncodingBox::ULong) => put!(v),
+            (Self::ULongLong(v), EncodingBox::ULongLong) => put!(v),
+            (Self::Float(v), EncodingBox::Float) => put!(v),
+            (Self::Double(v), EncodingBox::Double) => put!(v),
+            (Self::Object(v), EncodingBox::Object | EncodingBox::Block) => put!(*v as usize),
+            (Self::Class(v), EncodingBox::Class) => put!(*v as usize),
+            (Self::Sel(v), EncodingBox::Sel) => put!(v.map_or(0, |sel| sel.as_ptr() as usize)),
+            (
+                Self::Pointer(v),
+                EncodingBox::Pointer(_) | EncodingBox::String | EncodingBox::Unknown,
+            ) => put!(*v as usize),
+            (Self::Struct(values), EncodingBox::Struct(_, Some(fields)))
+                if values.len() == fields.len() =>
+            {
+                let mut offset = 0;
+                for (value, field) in values.iter().zip(fields) {
+                    let (size, align) = match layout(field) {
+                        Some(layout) => layout,
+                        None => return false,
+                    };
+                    offset = round_up(offset, align);
+                    if !value.write(field, &mut buf[offset..offset + size]) {
+                        return false;
+                    }
+                    offset += size;
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Read a value with the given encoding from the start of `buf`.
+    ///
+    /// The encoding must have been checked to be supported.
+    fn read(enc: &EncodingBox, buf: &[u8]) -> Self {
+        macro_rules! get {
+            ($t:ty) => {
+                <$t>::from_ne_bytes(buf[..mem::size_of::<$t>()].try_into().unwrap())
+            };
+        }
+        match enc {
+            EncodingBox::Void => Self::Void,
+            EncodingBox::Bool => Self::Bool(buf[0] != 0),
+            EncodingBox::Char => Self::Char(get!(i8)),
+            EncodingBox::Short => Self::Short(get!(i16)),
+            EncodingBox::Int => Self::Int(get!(i32)),
+            EncodingBox::Long => Self::Long(get!(i32)),
+            EncodingBox::LongLong => Self::LongLong(get!(i64)),
+            EncodingBox::UChar => Self::UChar(get!(u8)),
+            EncodingBox::UShort => Self::UShort(get!(u16)),
+            EncodingBox::UInt => Self::UInt(get!(u32)),
+            EncodingBox::ULong => Self::ULong(get!(u32)),
+            EncodingBox::ULongLong => Self::ULongLong(get!(u64)),
+            EncodingBox::Float => Self::Float(get!(f32)),
+            EncodingBox::Double => Self::Double(get!(f64)),
+            EncodingBox::Object | EncodingBox::Block => Self::Object(get!(usize) as *mut _),
+            EncodingBox::Class => Self::Class(get!(usize) as *const _),
+            // SAFETY: Selectors returned from methods are valid (or NULL).
+            EncodingBox::Sel => Self::Sel(unsafe { Sel::from_ptr(get!(usize) as *const _) }),
+            EncodingBox::Pointer(_) | EncodingBox::String | EncodingBox::Unknown => {
+                Self::Pointer(get!(usize) as *mut _)
+            }
+            EncodingBox::Atomic(inner) => Self::read(inner, buf),
+            EncodingBox::Struct(_, Some(fields)) => {
+                let mut offset = 0;
+                let values = fields
+                    .iter()
+                    .map(|field| {
+                        let (size, align) = layout(field).unwrap();
+                        offset = round_up(offset, align);
+                        let value = Self::read(field, &buf[offset..]);
+                        offset += size;
+                        value
+                    })
+                    .collect();
+                Self::Struct(values)
+            }
+            _ => unreachable!("unsupported encoding {enc}"),
+        }
+    }
+
+    /// The value as a register, sign- or zero-extended to 64 bits.
+    fn extended(enc: &EncodingBox, bytes: &[u8]) -> u64 {
+        match Self::read(enc, bytes) {
+            Self::Char(v) => v as u64,
+            Self::Short(v) => v as u64,
+            Self::Int(v) => v as u64,
+            Self::Long(v) => v as u64,
+            _ => {
+                let mut word = [0; 8];
+                word[..bytes.len()].copy_from_slice(bytes);
+                u64::from_ne_bytes(word)
+            }
+        }
+    }
+}
+
+/// The number of integer registers available for arguments after the
+/// receiver and the selector.
+#[cfg(target_arch = "x86_64")]
+const INT_REGISTERS: usize = 4;
+#[cfg(target_arch = "aarch64")]
+const INT_REGISTERS: usize = 6;
+
+/// The number of floating point registers available for arguments.
+const FLOAT_REGISTERS: usize = 8;
+
+/// The number of words of stack memory we can pass.
+const STACK_WORDS: usize = 16;
+
+/// The maximum size of structs that are returned in memory.
+const MAX_INDIRECT_RETURN: usize = 256;
+
+/// The arguments, laid out according to the calling convention.
+#[derive(Debug)]
+struct Arguments {
+    int_limit: usize,
+    ints: Vec<u64>,
+    floats: Vec<u64>,
+    stack: Vec<u8>,
+    /// Copies of the arguments that are passed by reference.
+    #[cfg_attr(target_arch = "x86_64", allow(dead_code))]
+    indirect: Vec<Vec<u8>>,
+}
+
+impl Arguments {
+    fn new(int_limit: usize) -> Self {
+        Self {
+            int_limit,
+            ints: Vec::new(),
+            floats: Vec::new(),
+            stack: Vec::new(),
+            indirect: Vec::new(),
+        }
+    }
+
+    fn ints_left(&self) -> usize {
+        self.int_limit - self.ints.len()
+    }
+
+    fn floats_left(&self) -> usize {
+        FLOAT_REGISTERS - self.floats.len()
+    }
+
+    fn push_stack(&mut self, bytes: &[u8], align: usize, slot: usize) {
+        self.stack.resize(round_up(self.stack.len(), align), 0);
+        self.stack.extend_from_slice(bytes);
+        self.stack.resize(round_up(self.stack.len(), slot), 0);
+    }
+
+    fn push_scalar(&mut self, enc: &EncodingBox, leaf: Leaf, bytes: &[u8]) {
+        let word = DynValue::extended(enc, bytes);
+        if leaf.float && self.floats_left() > 0 {
+            self.floats.push(word);
+        } else if !leaf.float && self.ints_left() > 0 {
+            self.ints.push(word);
+        } else if cfg!(all(target_arch = "aarch64", target_vendor = "apple")) {
+            // Apple packs arguments on the stack according to their size.
+            self.push_stack(bytes, leaf.size, 1);
+        } else {
+            self.push_stack(&word.to_ne_bytes(), 8, 8);
+        }
+    }
+
+    /// Push the struct in registers if it's small enough, and if there are
+    /// enough registers left, otherwise on the stack.
+    #[cfg(target_arch = "x86_64")]
+    fn push_struct(&mut self, bytes: &[u8], leaves: &[Leaf]) {
+        if bytes.len() <= 16 {
+            // Each eightbyte is passed in a floating point register if it
+            // only contains floating point values.
+            let words: Vec<_> = bytes
+                .chunks(8)
+                .enumerate()
+                .map(|(i, chunk)| {
+                    let mut word = [0; 8];
+                    word[..chunk.len()].copy_from_slice(chunk);
+                    let float = leaves
+                        .iter()
+                        .filter(|leaf| leaf.offset / 8 == i)
+                        .all(|leaf| leaf.float);
+                    (u64::from_ne_bytes(word), float)
+                })
+                .collect();
+            let floats = words.iter().filter(|(_, float)| *float).count();
+            let ints = words.len() - floats;
+            if ints <= self.ints_left() && floats <= self.floats_left() {
+                for (word, float) in words {
+                    if float {
+                        self.floats.push(word);
+                    } else {
+                        self.ints.push(word);
+                    }
+                }
+                return;
+            }
+        }
+        self.push_stack(bytes, 8, 8);
+    }
+
+    /// Push homogeneous floating point aggregates in floating point
+    /// registers, other small structs in integer registers, and pass large
+    /// structs by reference.
+    #[cfg(target_arch = "aarch64")]
+    fn push_struct(&mut self, bytes: &[u8], leaves: &[Leaf]) {
+        if is_hfa(leaves) {
+            if leaves.len() <= self.floats_left() {
+                for leaf in leaves {
+                    let mut word = [0; 8];
+                    word[..leaf.size].copy_from_slice(&bytes[leaf.offset..leaf.offset + leaf.size]);
+                    self.floats.push(u64::from_ne_bytes(word));
+                }
+                return;
+            }
+            self.floats.resize(FLOAT_REGISTERS, 0);
+        } else if bytes.len() <= 16 {
+            let words = round_up(bytes.len(), 8) / 8;
+            if words <= self.ints_left() {
+                for chunk in bytes.chunks(8) {
+                    let mut word = [0; 8];
+                    word[..chunk.len()].copy_from_slice(chunk);
+                    self.ints.push(u64::from_ne_bytes(word));
+                }
+                return;
+            }
+            self.ints.resize(self.int_limit, 0);
+        } else {
+            let copy = bytes.to_vec();
+            let ptr = copy.as_ptr() as u64;
+            self.indirect.push(copy);
+            let leaf = Leaf {
+                offset: 0,
+                size: 8,
+                float: false,
+            };
+            self.push_scalar(&EncodingBox::ULongLong, leaf, &ptr.to_ne_bytes());
+            return;
+        }
+        self.push_stack(bytes, 8, 8);
+    }
+
+    fn push(&mut self, enc: &EncodingBox, value: &DynValue, i: usize) {
+        let mut leaves = Vec::new();
+        let (size, _) = flatten(enc, 0, &mut leaves)
+            .unwrap_or_else(|| panic!("unsupported type of argument {i}: '{enc}'"));
+        let mut bytes = vec![0; size];
+        if !value.write(enc, &mut bytes) {
+            panic!("expected argument {i} to have type code '{enc}', but found {value:?}");
+        }
+        if is_struct(enc) {
+            self.push_struct(&bytes, &leaves);
+        } else {
+            self.push_scalar(enc, leaves[0], &bytes);
+        }
+    }
+}
+
+/// Whether the struct is a homogeneous floating point aggregate, which
+/// AArch64 passes in floating point registers.
+#[cfg_attr(target_arch = "x86_64", allow(dead_code))]
+fn is_hfa(leaves: &[Leaf]) -> bool {
+    leaves.len() <= 4
+        && leaves
+            .iter()
+            .all(|leaf| leaf.float && leaf.size == leaves[0].size)
+}
+
+fn is_struct(enc: &EncodingBox) -> bool {
+    match enc {
+        EncodingBox::Struct(_, _) => true,
+        EncodingBox::Atomic(inner) => is_struct(inner),
+        _ => false,
+    }
+}
+
+/// The registers and stack memory, as passed to the implementation.
+#[derive(Debug)]
+struct Registers {
+    ints: [u64; INT_REGISTERS],
+    floats: [f64; FLOAT_REGISTERS],
+    stack: [u64; STACK_WORDS],
+}
+
+impl Registers {
+    fn new(args: &Arguments) -> Self {
+        // Stack memory that follows the arguments in integer registers
+        let mut words: Vec<u64> = args.ints.clone();
+        words.resize(args.int_limit, 0);
+        words.extend(args.stack.chunks(8).map(|chunk| {
+            let mut word = [0; 8];
+            word[..chunk.len()].copy_from_slice(chunk);
+            u64::from_ne_bytes(word)
+        }));
+        if INT_REGISTERS + STACK_WORDS < words.len() {
+            panic!("too many arguments");
+        }
+        words.resize(INT_REGISTERS + STACK_WORDS, 0);
+
+        let mut floats = [0.0; FLOAT_REGISTERS];
+        for (float, bits) in floats.iter_mut().zip(&args.floats) {
+            *float = f64::from_bits(*bits);
+        }
+        Self {
+            ints: words[..INT_REGISTERS].try_into().unwrap(),
+            floats,
+            stack: words[INT_REGISTERS..].try_into().unwrap(),
+        }
+    }
+}
+
+macro_rules! word {
+    ($i:literal) => {
+        u64
+    };
+}
+
+macro_rules! float {
+    ($i:literal) => {
+        f64
+    };
+}
+
+/// Call the implementation with every register that may contain arguments,
+/// followed by the stack memory.
+macro_rules! call_imp {
+    (
+        $imp:expr, $receiver:expr, $sel:expr, $regs:expr,
+        ints: [$($i:literal)*],
+        floats: [$($f:literal)*],
+        stack: [$($s:literal)*],
+    ) => {{
+        let regs: &Registers = $regs;
+        #[cfg(not(feature = "unstable-c-unwind"))]
+        let imp: unsafe extern "C" fn(
+            *mut AnyObject,
+            Sel,
+            $(word!($i),)*
+            $(float!($f),)*
+            $(word!($s),)*
+        ) -> R = unsafe { mem::transmute($imp) };
+        #[cfg(feature = "unstable-c-unwind")]
+        let imp: unsafe extern "C-unwind" fn(
+            *mut AnyObject,
+            Sel,
+            $(word!($i),)*
+            $(float!($f),)*
+            $(word!($s),)*
+        ) -> R = unsafe { mem::transmute($imp) };
+        unsafe {
+            imp(
+                $receiver,
+                $sel,
+                $(regs.ints[$i],)*
+                $(regs.floats[$f],)*
+                $(regs.stack[$s],)*
+            )
+        }
+    }};
+}
+
+/// # Safety
+///
+/// The implementation must be callable with the arguments in the registers,
+/// and must return a value that is passed like `R`.
+unsafe fn call<R>(imp: Imp, receiver: *mut AnyObject, sel: Sel, regs: &Registers) -> R {
+    #[cfg(target_arch = "x86_64")]
+    {
+        call_imp!(
+            imp, receiver, sel, regs,
+            ints: [0 1 2 3],
+            floats: [0 1 2 3 4 5 6 7],
+            stack: [0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15],
+        )
+    }
+    #[cfg(target_arch = "aarch64")]
+    {
+        call_imp!(
+            imp, receiver, sel, regs,
+            ints: [0 1 2 3 4 5],
+            floats: [0 1 2 3 4 5 6 7],
+            stack: [0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15],
+        )
+    }
+}
+
+/// Two values returned in registers.
+#[repr(C)]
+struct Pair<A, B>(A, B);
+
+/// A homogeneous floating point aggregate returned in registers.
+#[cfg(target_arch = "aarch64")]
+#[repr(C)]
+struct Hfa(f64, f64, f64, f64);
+
+/// A struct returned in memory.
+#[repr(C)]
+struct Indirect([MaybeUninit<u8>; MAX_INDIRECT_RETURN]);
+
+fn words_to_bytes(words: &[u64]) -> Vec<u8> {
+    words.iter().flat_map(|word| word.to_ne_bytes()).collect()
+}
+
+/// Whether the struct is returned in memory.
+fn returned_in_memory(size: usize, leaves: &[Leaf]) -> bool {
+    if cfg!(target_arch = "x86_64") {
+        16 < size
+    } else {
+        16 < size && !is_hfa(leaves)
+    }
+}
+
+/// # Safety
+///
+/// The implementation must be callable with the arguments in the registers,
+/// and must return a value with the given encoding.
+unsafe fn call_and_read(
+    imp: Imp,
+    receiver: *mut AnyObject,
+    sel: Sel,
+    args: &Arguments,
+    ret: &EncodingBox,
+) -> DynValue {
+    let regs = Registers::new(args);
+    if let EncodingBox::Void = ret {
+        // SAFETY: Upheld by caller.
+        let _: u64 = unsafe { call(imp, receiver, sel, &regs) };
+        return DynValue::Void;
+    }
+
+    let mut leaves = Vec::new();
+    let (size, _) =
+        flatten(ret, 0, &mut leaves).unwrap_or_else(|| panic!("unsupported return type: '{ret}'"));
+
+    let bytes = if !is_struct(ret) {
+        // SAFETY: Upheld by caller.
+        let word = if leaves[0].float {
+            unsafe { call::<f64>(imp, receiver, sel, &regs) }.to_bits()
+        } else {
+            unsafe { call::<u64>(imp, receiver, sel, &regs) }
+        };
+        word.to_ne_bytes().to_vec()
+    } else if returned_in_memory(size, &leaves) {
+        if MAX_INDIRECT_RETURN < size {
+            panic!("unsupported return type: '{ret}'");
+        }
+        // SAFETY: Upheld by caller.
+        let Indirect(buf) = unsafe { call(imp, receiver, sel, &regs) };
+        // SAFETY: The implementation initialized `size` bytes.
+        buf[..size]
+            .iter()
+            .map(|byte| unsafe { byte.assume_init() })
+            .collect()
+    } else {
+        // SAFETY (for all calls below): Upheld by caller.
+        #[cfg(target_arch = "x86_64")]
+        {
+            let float = |i: usize| {
+                leaves
+                    .iter()
+                    .filter(|leaf| leaf.offset / 8 == i)
+                    .all(|leaf| leaf.float)
+            };
+            let words = match (float(0), float(1)) {
+                (false, false) => {
+                    let Pair(a, b) = unsafe { call::<Pair<u64, u64>>(imp, receiver, sel, &regs) };
+                    [a, b]
+                }
+                (false, true) => {
+                    let Pair(a, b) = unsafe { call::<Pair<u64, f64>>(imp, receiver, sel, &regs) };
+                    [a, b.to_bits()]
+                }
+                (true, false) => {
+                    let Pair(a, b) = unsafe { call::<Pair<f64, u64>>(imp, receiver, sel, &regs) };
+                    [a.to_bits(), b]
+                }
+                (true, true) => {
+                    let Pair(a, b) = unsafe { call::<Pair<f64, f64>>(imp, receiver, sel, &regs) };
+                    [a.to_bits(), b.to_bits()]
+                }
+            };
+            words_to_bytes(&words)
+        }
+        #[cfg(target_arch = "aarch64")]
+        {
+            if is_hfa(&leaves) {
+                // Each member is returned in the low bits of a separate
+                // floating point register.
+                let Hfa(a, b, c, d) = unsafe { call(imp, receiver, sel, &regs) };
+                let registers =
+                    words_to_bytes(&[a.to_bits(), b.to_bits(), c.to_bits(), d.to_bits()]);
+                let mut bytes = vec![0; size];
+                for (i, leaf) in leaves.iter().enumerate() {
+                    bytes[leaf.offset..leaf.offset + leaf.size]
+                        .copy_from_slice(&registers[i * 8..i * 8 + leaf.size]);
+                }
+                bytes
+            } else {
+                let Pair(a, b) = unsafe { call::<Pair<u64, u64>>(imp, receiver, sel, &regs) };
+                words_to_bytes(&[a, b])
+            }
+        }
+    };
+    DynValue::read(ret, &bytes)
+}
+
+impl AnyObject {
+    /// Send a message to the object, with the types of the arguments and the
+    /// return value determined at runtime.
+    ///
+    /// This looks up the method implementing `sel` on the object's class,
+    /// and passes the arguments according to the method's type encoding
+    /// (which can be inspected beforehand with [`Method`]).
+    ///
+    /// This is useful for scripting bridges and the like; when the types are
+    /// known at compile time, you should use [`msg_send!`] instead.
+    ///
+    /// The receiver may be a class, in which case a class method is called.
+    ///
+    /// This is only available on 64-bit Unix platforms on x86-64 and
+    /// AArch64.
+    ///
+    /// [`Method`]: crate::runtime::Method
+    /// [`msg_send!`]: crate::msg_send
+    ///
+    ///
+    /// # Panics
+    ///
+    /// Panics if the object's class does not implement the method (message
+    /// forwarding is not supported), if the number of arguments or the type
+    /// of one of them does not match the method's type encoding, or if the
+    /// method uses types that are not supported (unions, arrays, bitfields,
+    /// `long double` and complex numbers).
+    ///
+    /// Also panics if the arguments take up more than 128 bytes of stack
+    /// memory.
+    ///
+    ///
+    /// # Safety
+    ///
+    /// The method's type encoding must be correct, and the arguments must be
+    /// valid for the method; e.g. pointers must be valid, and the memory
+    /// management rules of the method must be followed.
+    ///
+    /// Same as with [`msg_send!`], the method must not unwind, unless the
+    /// `"unstable-c-unwind"` feature is enabled.
+    ///
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use objc2::runtime::{AnyObject, DynValue, NSObject};
+    /// use objc2::sel;
+    ///
+    /// let obj = NSObject::new();
+    /// let ptr: *const NSObject = &*obj;
+    /// let arg = DynValue::Object(ptr as *mut AnyObject);
+    /// let res = unsafe { obj.send_dynamic(sel!(isEqual:), &[arg]) };
+    /// // `BOOL` is encoded differently depending on the platform
+    /// assert!(matches!(
+    ///     res,
+    ///     DynValue::Bool(true) | DynValue::Char(1) | DynValue::UChar(1)
+    /// ));
+    /// ```
+    pub unsafe fn send_dynamic(&self, sel: Sel, args: &[DynValue]) -> DynValue {
+        let method = self
+            .class()
+            .instance_method(sel)
+            .unwrap_or_else(|| panic!("{} does not respond to {sel}", self.class()));
+        let types = method.types();
+        let mut encodings = types.map(|res| match res {
+            Ok((encoding, _stack_layout)) => encoding,
+            Err(err) => panic!("invalid type encoding of {sel}: {err}"),
+        });
+        let ret = encodings
+            .next()
+            .unwrap_or_else(|| panic!("missing return type of {sel}"));
+        // Skip the receiver and the selector
+        let arg_types: Vec<_> = encodings.skip(2).collect();
+        if arg_types.len() != args.len() {
+            panic!(
+                "expected {} arguments, but {} were given",
+                arg_types.len(),
+                args.len()
+            );
+        }
+
+        let mut leaves = Vec::new();
+        let ret_in_memory = is_struct(&ret)
+            && flatten(&ret, 0, &mut leaves)
+                .map_or(false, |(size, _)| returned_in_memory(size, &leaves));
+        // On x86-64, the pointer to a return value in memory is passed in the
+        // first integer register. The remaining arguments are shifted, such
+        // that the last integer register ends up as the first stack word.
+        let int_limit = if cfg!(target_arch = "x86_64") && ret_in_memory {
+            INT_REGISTERS - 1
+        } else {
+            INT_REGISTERS
+        };
+        let mut arguments = Arguments::new(int_limit);
+        for (i, (enc, value)) in arg_types.iter().zip(args).enumerate() {
+            arguments.push(enc, value, i);
+        }
+
+        let receiver = self.as_ptr() as *mut AnyObject;
+        // SAFETY: The arguments have been checked to match the method's type
+        // encoding, which the caller ensures is correct.
+        unsafe { call_and_read(method.implementation(), receiver, sel, &arguments, &ret) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Once;
+
+    use crate::declare::ClassBuilder;
+    use crate::encode::{Encode, Encoding};
+    use crate::rc::Id;
+    use crate::runtime::NSObject;
+    use crate::{msg_send_id, sel, ClassType};
+
+    #[repr(C)]
+    #[derive(Debug, Clone, Copy)]
+    struct Point {
+        x: f64,
+        y: f64,
+    }
+
+    unsafe impl Encode for Point {
+        const ENCODING: Encoding = Encoding::Struct("Point", &[f64::ENCODING, f64::ENCODING]);
+    }
+
+    #[repr(C)]
+    #[derive(Debug, Clone, Copy)]
+    struct Floats {
+        a: f32,
+        b: f32,
+        c: f32,
+    }
+
+    unsafe impl Encode for Floats {
+        const ENCODING: Encoding =
+            Encoding::Struct("Floats", &[f32::ENCODING, f32::ENCODING, f32::ENCODING]);
+    }
+
+    #[repr(C)]
+    #[derive(Debug, Clone, Copy)]
+    struct Mixed {
+        a: i32,
+        b: f32,
+        c: f64,
+    }
+
+    unsafe impl Encode for Mixed {
+        const ENCODING: Encoding =
+            Encoding::Struct("Mixed", &[i32::ENCODING, f32::ENCODING, f64::ENCODING]);
+    }
+
+    #[repr(C)]
+    #[derive(Debug, Clone, Copy)]
+    struct Big {
+        a: i64,
+        b: Point,
+        c: u8,
+    }
+
+    unsafe impl Encode for Big {
+        const ENCODING: Encoding =
+            Encoding::Struct("Big", &[i64::ENCODING, Point::ENCODING, u8::ENCODING]);
+    }
+
+    /// A C `_Bool`.
+    #[repr(transparent)]
+    struct CBool(bool);
+
+    unsafe impl Encode for CBool {
+        const ENCODING: Encoding = Encoding::Bool;
+    }
+
+    /// A 32-bit integer encoded as `l`.
+    #[repr(transparent)]
+    struct Long(i32);
+
+    unsafe impl Encode for Long {
+        const ENCODING: Encoding = Encoding::Long;
+    }
+
+    fn class() -> &'static AnyClass {
+        static REGISTER_CLASS: Once = Once::new();
+
+        REGISTER_CLASS.call_once(|| {
+            extern "C" fn mix(_: &AnyObject, _: Sel, a: i8, b: f32, c: u16, d: f64) -> f64 {
+                a as f64 + b as f64 + c as f64 + d
+            }
+            extern "C" fn point(_: &AnyObject, _: Sel, p: Point, scale: f64) -> Point {
+                Point {
+                    x: p.y * scale,
+                    y: p.x * scale,
+                }
+            }
+            extern "C" fn floats(_: &AnyObject, _: Sel, f: Floats) -> Floats {
+                Floats {
+                    a: f.c,
+                    b: f.b,
+                    c: f.a,
+                }
+            }
+            extern "C" fn mixed(_: &AnyObject, _: Sel, m: Mixed) -> Mixed {
+                Mixed {
+                    a: -m.a,
+                    b: m.b * 2.0,
+                    c: m.c + 1.0,
+                }
+            }
+            extern "C" fn big(_: &AnyObject, _: Sel, b: Big, c: i32) -> Big {
+                Big {
+                    a: b.a + c as i64,
+                    b: Point { x: b.b.y, y: b.b.x },
+                    c: b.c + 1,
+                }
+            }
+            #[allow(clippy::too_many_arguments)]
+            extern "C" fn many(
+                _: &AnyObject,
+                _: Sel,
+                a: i64,
+                b: f64,
+                c: i64,
+                d: f64,
+                e: i64,
+                f: f64,
+                g: i64,
+                h: f64,
+                i: i64,
+                j: f64,
+                k: i64,
+                l: f64,
+                m: f64,
+                n: f64,
+                o: f64,
+                p: i8,
+            ) -> f64 {
+                [
+                    a as f64, b, c as f64, d, e as f64, f, g as f64, h, i as f64, j, k as f64, l,
+                    m, n, o, p as f64,
+                ]
+                .iter()
+                .fold(0.0, |acc, x| acc * 2.0 + x)
+            }
+            extern "C" fn pointers(
+                _: &AnyObject,
+                _: Sel,
+                obj: *mut AnyObject,
+                cls: *const AnyClass,
+                ptr: *mut c_void,
+                flag: CBool,
+            ) -> *mut AnyObject {
+                if flag.0 && !cls.is_null() && !ptr.is_null() {
+                    obj
+                } else {
+                    core::ptr::null_mut()
+                }
+            }
+            extern "C" fn selector(_: &AnyObject, _: Sel, sel: Sel) -> Sel {
+                sel
+            }
+            extern "C" fn nothing(_: &AnyObject, _: Sel) {}
+            extern "C" fn long(_: &AnyObject, _: Sel, a: Long, b: i64) -> Long {
+                Long(a.0 + b as i32)
+            }
+            extern "C" fn class_number(_: &AnyClass, _: Sel, a: u32) -> u32 {
+                a + 1
+            }
+
+            let mut builder = ClassBuilder::new("DynamicSendTest", NSObject::class()).unwrap();
+            unsafe {
+                builder.add_method(
+                    sel!(mix:b:c:d:),
+                    mix as extern "C" fn(_, _, _, _, _, _) -> _,
+                );
+                builder.add_method(sel!(point:scale:), point as extern "C" fn(_, _, _, _) -> _);
+                builder.add_method(sel!(floats:), floats as extern "C" fn(_, _, _) -> _);
+                builder.add_method(sel!(mixed:), mixed as extern "C" fn(_, _, _) -> _);
+                builder.add_method(sel!(big:c:), big as extern "C" fn(_, _, _, _) -> _);
+                builder.add_method(
+                    sel!(a:b:c:d:e:f:g:h:i:j:k:l:m:n:o:p:),
+                    many as extern "C" fn(
+                        _,
+                        _,
+                        _,
+                        _,
+                        _,
+                        _,
+                        _,
+                        _,
+                        _,
+                        _,
+                        _,
+                        _,
+                        _,
+                        _,
+                        _,
+                        _,
+                        _,
+                        _,
+                    ) -> _,
+                );
+                builder.add_method(
+                    sel!(object:class:pointer:flag:),
+                    pointers as extern "C" fn(_, _, _, _, _, _) -> _,
+                );
+                builder.add_method(sel!(selector:), selector as extern "C" fn(_, _, _) -> _);
+                builder.add_method(sel!(nothing), nothing as extern "C" fn(_, _));
+                builder.add_method(sel!(long:b:), long as extern "C" fn(_, _, _, _) -> _);
+                builder.add_class_method(
+                    sel!(classNumber:),
+                    class_number as extern "C" fn(_, _, _) -> _,
+                );
+            }
+            builder.register();
+        });
+
+        AnyClass::get("DynamicSendTest").unwrap()
+    }
+
+    fn object() -> Id<AnyObject> {
+        unsafe { msg_send_id![class(), new] }
+    }
+
+    #[test]
+    fn test_scalars() {
+        let obj = object();
+        let args = [
+            DynValue::Char(-1),
+            DynValue::Float(1.5),
+            DynValue::UShort(3),
+            DynValue::Double(0.25),
+        ];
+        let res = unsafe { obj.send_dynamic(sel!(mix:b:c:d:), &args) };
+        assert_eq!(res, DynValue::Double(3.75));
+
+        let res = unsafe { obj.send_dynamic(sel!(nothing), &[]) };
+        assert_eq!(res, DynValue::Void);
+
+        let args = [DynValue::Sel(Some(sel!(foo:)))];
+        let res = unsafe { obj.send_dynamic(sel!(selector:), &args) };
+        assert_eq!(res, DynValue::Sel(Some(sel!(foo:))));
+
+        // `l` is always 32 bits
+        let args = [DynValue::Long(-2), DynValue::LongLong(5)];
+        let res = unsafe { obj.send_dynamic(sel!(long:b:), &args) };
+        assert_eq!(res, DynValue::Long(3));
+    }
+
+    #[test]
+    fn test_pointers() {
+        let obj = object();
+        let ptr = Id::as_ptr(&obj) as *mut AnyObject;
+        let mut data = 0u8;
+        let args = [
+            DynValue::Object(ptr),
+            DynValue::Class(class()),
+            DynValue::Pointer((&mut data as *mut u8).cast()),
+            DynValue::Bool(true),
+        ];
+        let res = unsafe { obj.send_dynamic(sel!(object:class:pointer:flag:), &args) };
+        assert_eq!(res, DynValue::Object(ptr));
+    }
+
+    #[test]
+    fn test_structs() {
+        let obj = object();
+
+        let point = DynValue::Struct(vec![DynValue::Double(1.0), DynValue::Double(2.0)]);
+        let res = unsafe { obj.send_dynamic(sel!(point:scale:), &[point, DynValue::Double(2.0)]) };
+        let expected = DynValue::Struct(vec![DynValue::Double(4.0), DynValue::Double(2.0)]);
+        assert_eq!(res, expected);
+
+        let floats = DynValue::Struct(vec![
+            DynValue::Float(1.0),
+            DynValue::Float(2.0),
+            DynValue::Float(3.0),
+        ]);
+        let res = unsafe { obj.send_dynamic(sel!(floats:), &[floats]) };
+        let expected = DynValue::Struct(vec![
+            DynValue::Float(3.0),
+            DynValue::Float(2.0),
+            DynValue::Float(1.0),
+        ]);
+        assert_eq!(res, expected);
+
+        let mixed = DynValue::Struct(vec![
+            DynValue::Int(5),
+            DynValue::Float(1.5),
+            DynValue::Double(2.5),
+        ]);
+        let res = unsafe { obj.send_dynamic(sel!(mixed:), &[mixed]) };
+        let expected = DynValue::Struct(vec![
+            DynValue::Int(-5),
+            DynValue::Float(3.0),
+            DynValue::Double(3.5),
+        ]);
+        assert_eq!(res, expected);
+
+        let big = DynValue::Struct(vec![
+            DynValue::LongLong(40),
+            DynValue::Struct(vec![DynValue::Double(1.0), DynValue::Double(2.0)]),
+            DynValue::UChar(7),
+        ]);
+        let res = unsafe { obj.send_dynamic(sel!(big:c:), &[big, DynValue::Int(2)]) };
+        let expected = DynValue::Struct(vec![
+            DynValue::LongLong(42),
+            DynValue::Struct(vec![DynValue::Double(2.0), DynValue::Double(1.0)]),
+            DynValue::UChar(8),
+        ]);
+        assert_eq!(res, expected);
+    }
+
+    #[test]
+    fn test_stack_arguments() {
+        let obj = object();
+        let args = [
+            DynValue::LongLong(1),
+            DynValue::Double(2.0),
+            DynValue::LongLong(3),
+            DynValue::Double(4.0),
+            DynValue::LongLong(5),
+            DynValue::Double(6.0),
+            DynValue::LongLong(7),
+            DynValue::Double(8.0),
+            DynValue::LongLong(9),
+            DynValue::Double(10.0),
+            DynValue::LongLong(11),
+            DynValue::Double(12.0),
+            DynValue::Double(13.0),
+            DynValue::Double(14.0),
+            DynValue::Double(15.0),
+            DynValue::Char(16),
+        ];
+        let expected = (1..=16).fold(0.0, |acc, x| acc * 2.0 + x as f64);
+        let res = unsafe { obj.send_dynamic(sel!(a:b:c:d:e:f:g:h:i:j:k:l:m:n:o:p:), &args) };
+        assert_eq!(res, DynValue::Double(expected));
+    }
+
+    #[test]
+    fn test_class_method() {
+        let cls: *const AnyClass = class();
+        let cls: &AnyObject = unsafe { &*cls.cast() };
+        let res = unsafe { cls.send_dynamic(sel!(classNumber:), &[DynValue::UInt(41)]) };
+        assert_eq!(res, DynValue::UInt(42));
+    }
+
+    #[test]
+    #[should_panic = "expected 4 arguments, but 1 were given"]
+    fn test_wrong_number_of_arguments() {
+        let obj = object();
+        let _ = unsafe { obj.send_dynamic(sel!(mix:b:c:d:), &[DynValue::Char(1)]) };
+    }
+
+    #[test]
+    #[should_panic = "expected argument 0 to have type code 'c', but found Int(1)"]
+    fn test_wrong_argument_type() {
+        let obj = object();
+        let args = [
+            DynValue::Int(1),
+            DynValue::Float(1.0),
+            DynValue::UShort(1),
+            DynValue::Double(1.0),
+        ];
+        let _ = unsafe { obj.send_dynamic(sel!(mix:b:c:d:), &args) };
+    }
+
+    #[test]
+    #[should_panic = "DynamicSendTest does not respond to unknownMethod"]
+    fn test_unknown_method() {
+        let obj = object();
+        let _ = unsafe { obj.send_dynamic(sel!(unknownMethod), &[]) };
+    }
+}
@@ -0,0 +1,143 @@
+use core::fmt;
+use core::hash;
+use core::ptr::NonNull;
+use core::str;
+use std::ffi::CStr;
+use std::os::raw::c_char;
+#[cfg(feature = "malloc")]
+use std::os::raw::c_uint;
+
+#[cfg(feature = "malloc")]
+use alloc::vec::Vec;
+#[cfg(feature = "malloc")]
+use malloc_buf::Malloc;
+
+#[cfg(feature = "malloc")]
+use crate::ffi;
+#[cfg(feature = "malloc")]
+use crate::runtime::AnyClass;
+
+/// An image (the main executable, a framework, a bundle or a dynamic
+/// library) that has been loaded by the Objective-C runtime.
+///
+/// This can be used to find the classes that were registered by a specific
+/// library or bundle, e.g. after loading it with `dlopen`.
+///
+/// This is only available on Apple platforms.
+#[repr(transparent)]
+#[derive(Clone, Copy)]
+pub struct Image {
+    name: NonNull<c_char>,
+}
+
+// SAFETY: The name is an immutable C-string that lives for as long as the
+// image is loaded.
+unsafe impl Send for Image {}
+unsafe impl Sync for Image {}
+
+impl Image {
+    /// Returns all the images that have been loaded by the Objective-C
+    /// runtime.
+    ///
+    /// See [Apple's documentation](https://developer.apple.com/documentation/objectivec/1418565-objc_copyimagenames?language=objc).
+    #[cfg(feature = "malloc")]
+    #[doc(alias = "objc_copyImageNames")]
+    pub fn all() -> Malloc<[Self]> {
+        unsafe {
+            let mut count: c_uint = 0;
+            // `Image` is `repr(transparent)` over a non-null C-string.
+            let images: *mut Self = ffi::objc_copyImageNames(&mut count).cast();
+            Malloc::from_array(images, count as usize)
+        }
+    }
+
+    /// # Safety
+    ///
+    /// The pointer must be a valid C-string that lives for as long as the
+    /// image is loaded.
+    pub(crate) unsafe fn from_raw(name: *const c_char) -> Option<Self> {
+        NonNull::new(name as *mut c_char).map(|name| Self { name })
+    }
+
+    /// The path of the image.
+    pub fn name(&self) -> &'static str {
+        // SAFETY: The pointer is a valid C-string, and images containing
+        // Objective-C classes can't be unloaded, so it is valid for
+        // `'static`.
+        let name = unsafe { CStr::from_ptr(self.name.as_ptr()) };
+        str::from_utf8(name.to_bytes()).unwrap()
+    }
+
+    /// Returns the classes that were registered by this image.
+    ///
+    /// See [Apple's documentation](https://developer.apple.com/documentation/objectivec/1418485-objc_copyclassnamesforimage?language=objc).
+    #[cfg(feature = "malloc")]
+    #[doc(alias = "objc_copyClassNamesForImage")]
+    pub fn classes(&self) -> Vec<&'static AnyClass> {
+        let names: Malloc<[*const c_char]> = unsafe {
+            let mut count: c_uint = 0;
+            let names = ffi::objc_copyClassNamesForImage(self.name.as_ptr(), &mut count);
+            Malloc::from_array(names, count as usize)
+        };
+        names
+            .iter()
+            .filter_map(|&name| {
+                // SAFETY: The name is a valid C-string, and a class with that
+                // name has been registered (classes can't be unregistered).
+                let cls = unsafe { ffi::objc_getClass(name) };
+                unsafe { cls.cast::<AnyClass>().as_ref() }
+            })
+            .collect()
+    }
+}
+
+impl PartialEq for Image {
+    fn eq(&self, other: &Self) -> bool {
+        self.name() == other.name()
+    }
+}
+
+impl Eq for Image {}
+
+impl hash::Hash for Image {
+    fn hash<H: hash::Hasher>(&self, state: &mut H) {
+        self.name().hash(state);
+    }
+}
+
+impl fmt::Debug for Image {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Image").field(&self.name()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "malloc")]
+    use super::*;
+    use crate::declare::ClassBuilder;
+    use crate::runtime::NSObject;
+    use crate::ClassType;
+
+    #[test]
+    fn test_class_image() {
+        let image = NSObject::class().image().unwrap();
+        assert!(image.name().contains("libobjc"), "{image:?}");
+        assert_eq!(NSObject::class().image_name(), Some(image.name()));
+
+        #[cfg(feature = "malloc")]
+        {
+            assert!(Image::all().iter().any(|i| i.name() == image.name()));
+            assert!(image.classes().contains(&NSObject::class()));
+        }
+    }
+
+    #[test]
+    fn test_dynamic_class() {
+        let cls = ClassBuilder::new("ImageDynamicClassTest", NSObject::class())
+            .unwrap()
+            .register();
+        assert_eq!(cls.image(), None);
+        assert_eq!(cls.image_name(), None);
+    }
+}
@@ -13,7 +13,7 @@
 #![doc = include_str!("../../examples/introspection.rs")]
 //! ```
 
-#[cfg(feature = "malloc")]
+use alloc::format;
 use alloc::vec::Vec;
 use core::fmt;
 use core::hash;
@@ -31,21 +31,34 @@ use std::os::raw::c_uint;
 // since `icrate` relies on it.
 #[doc(hidden)]
 pub mod __nsstring;
+mod association;
 mod bool;
+#[cfg(all(
+    any(target_arch = "x86_64", target_arch = "aarch64"),
+    target_pointer_width = "64",
+    not(windows)
+))]
+mod dynamic;
+#[cfg(feature = "apple")]
+mod image;
 mod method_encoding_iter;
 mod nscopying;
 mod nsobject;
 mod nsproxy;
 mod nszone;
+mod property_attributes;
 mod protocol_object;
 mod retain_release_fast;
+mod synchronized;
 
 pub(crate) use self::method_encoding_iter::{EncodingParseError, MethodEncodingIter};
+pub(crate) use self::property_attributes::PropertyAttributeList;
 pub(crate) use self::retain_release_fast::{objc_release_fast, objc_retain_fast};
+use crate::declare::{verified_method_type_encoding, MethodImplementation};
 use crate::encode::__unstable::{EncodeArguments, EncodeConvertReturn, EncodeReturn};
-use crate::encode::{Encode, Encoding, OptionEncode, RefEncode};
+use crate::encode::{Encode, Encoding, EncodingBox, OptionEncode, RefEncode};
 use crate::verify::{verify_method_signature, Inner};
-use crate::{ffi, Message};
+use crate::{ffi, DowncastTarget, Message};
 
 // Note: While these are not public, they are still a breaking change to
 // remove, since `icrate` relies on them.
@@ -56,10 +69,21 @@ pub use self::nscopying::{
 #[doc(hidden)]
 pub use self::nsproxy::NSProxy as __NSProxy;
 
+pub use self::association::{AssociationKey, AssociationPolicy};
 pub use self::bool::Bool;
+#[cfg(all(
+    any(target_arch = "x86_64", target_arch = "aarch64"),
+    target_pointer_width = "64",
+    not(windows)
+))]
+pub use self::dynamic::DynValue;
+#[cfg(feature = "apple")]
+pub use self::image::Image;
 pub use self::nsobject::{NSObject, NSObjectProtocol};
 pub use self::nszone::NSZone;
+pub use self::property_attributes::{PropertyAttributes, PropertyOwnership};
 pub use self::protocol_object::{ImplementedBy, ProtocolObject};
+pub use self::synchronized::{SyncError, SyncGuard};
 pub use crate::verify::VerificationError;
 
 /// Implement PartialEq, Eq and Hash using pointer semantics; there's not
@@ -347,15 +371,18 @@ impl fmt::Debug for Ivar {
     }
 }
 
-#[cfg_attr(not(feature = "malloc"), allow(dead_code))]
-#[derive(Debug, PartialEq, Eq, Hash)]
-pub(crate) struct MethodDescription {
+/// A description of a method declared by a protocol.
+///
+/// This is returned from [`AnyProtocol::method_description`] and
+/// [`AnyProtocol::method_descriptions`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[doc(alias = "objc_method_description")]
+pub struct MethodDescription {
     pub(crate) sel: Sel,
     pub(crate) types: &'static str,
 }
 
 impl MethodDescription {
-    #[cfg_attr(not(feature = "malloc"), allow(dead_code))]
     pub(crate) unsafe fn from_raw(raw: ffi::objc_method_description) -> Option<Self> {
         // SAFETY: Sel::from_ptr checks for NULL, rest is checked by caller.
         let sel = unsafe { Sel::from_ptr(raw.name) }?;
@@ -367,6 +394,55 @@ impl MethodDescription {
         let types = unsafe { CStr::from_ptr(raw.types) }.to_str().unwrap();
         Some(Self { sel, types })
     }
+
+    /// Returns the name of the method.
+    pub fn name(&self) -> Sel {
+        self.sel
+    }
+
+    /// Returns the full type encoding string of the method, as stored by
+    /// the runtime.
+    ///
+    /// This usually also contains the stack layout of the arguments, use
+    /// [`return_type`] and [`argument_types`] to get the parsed encodings.
+    ///
+    /// [`return_type`]: Self::return_type
+    /// [`argument_types`]: Self::argument_types
+    pub fn types(&self) -> &'static str {
+        self.types
+    }
+
+    fn encodings(&self) -> impl Iterator<Item = EncodingBox> + '_ {
+        MethodEncodingIter::new(self.types).map(move |res| match res {
+            Ok((encoding, _stack_layout)) => encoding,
+            Err(err) => panic!("invalid type encoding {:?}: {err}", self.types),
+        })
+    }
+
+    /// Returns the parsed encoding of the method's return type.
+    ///
+    ///
+    /// # Panics
+    ///
+    /// Panics if the runtime's type encoding could not be parsed.
+    pub fn return_type(&self) -> EncodingBox {
+        self.encodings()
+            .next()
+            .unwrap_or_else(|| panic!("missing return type in {:?}", self.types))
+    }
+
+    /// Returns the parsed encodings of the method's arguments.
+    ///
+    /// Like with [`Method::argument_type`], this includes the receiver and
+    /// the selector as the first two arguments.
+    ///
+    ///
+    /// # Panics
+    ///
+    /// Panics if the runtime's type encoding could not be parsed.
+    pub fn argument_types(&self) -> Vec<EncodingBox> {
+        self.encodings().skip(1).collect()
+    }
 }
 
 /// A type that represents a method in a class definition.
@@ -550,6 +626,77 @@ impl fmt::Debug for Method {
     }
 }
 
+/// A type that represents a declared property in a class or protocol.
+///
+/// See [Apple's documentation](https://developer.apple.com/documentation/objectivec/objc_property_t?language=objc).
+#[repr(C)]
+#[doc(alias = "objc_property")]
+#[doc(alias = "objc_property_t")]
+pub struct Property(ffi::objc_property);
+
+// SAFETY: Property is immutable (and can be retrieved from AnyClass anyhow).
+unsafe impl Sync for Property {}
+unsafe impl Send for Property {}
+impl UnwindSafe for Property {}
+impl RefUnwindSafe for Property {}
+
+impl Property {
+    pub(crate) fn as_ptr(&self) -> *const ffi::objc_property {
+        let ptr: *const Self = self;
+        ptr.cast()
+    }
+
+    /// Returns the name of the property.
+    ///
+    /// See [Apple's documentation](https://developer.apple.com/documentation/objectivec/1418903-property_getname?language=objc).
+    #[doc(alias = "property_getName")]
+    pub fn name(&self) -> &str {
+        let name = unsafe { CStr::from_ptr(ffi::property_getName(self.as_ptr())) };
+        str::from_utf8(name.to_bytes()).unwrap()
+    }
+
+    fn attributes_str(&self) -> &str {
+        // SAFETY: The property pointer is valid and non-null
+        let ptr = unsafe { ffi::property_getAttributes(self.as_ptr()) };
+        if ptr.is_null() {
+            return "";
+        }
+        // SAFETY: `property_getAttributes` returns a C-string that lives as
+        // long as the property, and we just checked that it is non-null.
+        let attributes = unsafe { CStr::from_ptr(ptr) };
+        str::from_utf8(attributes.to_bytes()).unwrap()
+    }
+
+    /// Returns the property's `@encode(type)` string.
+    ///
+    /// For object types, this may also contain the class name and protocols
+    /// of the property, for example `@"NSString"`.
+    #[doc(alias = "property_getAttributes")]
+    pub fn type_encoding(&self) -> &str {
+        PropertyAttributes::parse(self.attributes_str()).0
+    }
+
+    /// Returns the parsed attributes of the property.
+    ///
+    /// See [Apple's documentation](https://developer.apple.com/documentation/objectivec/1418675-property_getattributes?language=objc).
+    #[doc(alias = "property_getAttributes")]
+    pub fn attributes(&self) -> PropertyAttributes<'_> {
+        PropertyAttributes::parse(self.attributes_str()).1
+    }
+}
+
+standard_pointer_impls!(Property);
+
+impl fmt::Debug for Property {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Property")
+            .field("name", &self.name())
+            .field("type_encoding", &self.type_encoding())
+            .field("attributes", &self.attributes())
+            .finish_non_exhaustive()
+    }
+}
+
 /// A type that represents an Objective-C class.
 ///
 /// This is an opaque type meant to be used behind a shared reference
@@ -635,6 +782,29 @@ impl AnyClass {
         unsafe { Self::name_raw(self.as_ptr()) }
     }
 
+    /// Returns the image (library or executable) that the class was
+    /// registered by.
+    ///
+    /// Returns [`None`] for classes that were created at runtime.
+    ///
+    /// This is only available on Apple platforms.
+    #[cfg(feature = "apple")]
+    #[doc(alias = "class_getImageName")]
+    pub fn image(&self) -> Option<Image> {
+        // SAFETY: The class is valid, and the returned name lives for as long
+        // as the image is loaded (which it must be, since the class is).
+        unsafe { Image::from_raw(ffi::class_getImageName(self.as_ptr())) }
+    }
+
+    /// Returns the path of the image that the class was registered by.
+    ///
+    /// See [`AnyClass::image`] for details.
+    #[cfg(feature = "apple")]
+    #[doc(alias = "class_getImageName")]
+    pub fn image_name(&self) -> Option<&'static str> {
+        self.image().map(|image| image.name())
+    }
+
     /// # Safety
     ///
     /// 1. The class pointer must be valid.
@@ -777,6 +947,31 @@ impl AnyClass {
         }
     }
 
+    /// Returns the property with the given name declared by self, or
+    /// [`None`] if self has no such property.
+    #[doc(alias = "class_getProperty")]
+    pub fn property(&self, name: &str) -> Option<&Property> {
+        let name = CString::new(name).unwrap();
+        unsafe {
+            let property = ffi::class_getProperty(self.as_ptr(), name.as_ptr());
+            property.cast::<Property>().as_ref()
+        }
+    }
+
+    /// Describes the properties declared by self.
+    ///
+    /// This does not include the properties declared by superclasses.
+    #[cfg(feature = "malloc")]
+    #[doc(alias = "class_copyPropertyList")]
+    pub fn instance_properties(&self) -> Malloc<[&Property]> {
+        unsafe {
+            let mut count: c_uint = 0;
+            let properties: *mut &Property =
+                ffi::class_copyPropertyList(self.as_ptr(), &mut count).cast();
+            Malloc::from_array(properties, count as usize)
+        }
+    }
+
     /// Check whether instances of this class respond to the given selector.
     ///
     /// This doesn't call `respondsToSelector:`, but works entirely within the
@@ -796,9 +991,6 @@ impl AnyClass {
         Bool::from_raw(res).as_bool()
     }
 
-    // fn property(&self, name: &str) -> Option<&Property>;
-    // fn properties(&self) -> Malloc<[&Property]>;
-    // unsafe fn replace_method(&self, name: Sel, imp: Imp, types: &str) -> Imp;
     // unsafe fn replace_property(&self, name: &str, attributes: &[ffi::objc_property_attribute_t]);
     // unsafe fn set_ivar_layout(&mut self, layout: &[u8]);
     // fn method_imp(&self, name: Sel) -> Imp; // + _stret
@@ -806,6 +998,319 @@ impl AnyClass {
     // fn get_version(&self) -> u32;
     // unsafe fn set_version(&mut self, version: u32);
 
+    /// Check the arguments of a method that is about to be added to or
+    /// replaced on this class, and compute its type encoding.
+    fn method_types(&self, sel: Sel, enc_args: &[Encoding], enc_ret: &Encoding) -> CString {
+        // Verify that, if the method is already present on the class or any
+        // of its superclasses, that the encoding is correct.
+        verified_method_type_encoding(
+            sel,
+            enc_args,
+            enc_ret,
+            || self.instance_method(sel),
+            || {
+                let prefix = if self.is_metaclass() { "+" } else { "-" };
+                format!("invalid method {prefix}[{} {sel}]", self.name())
+            },
+        )
+    }
+
+    /// Add an instance method with the given raw implementation to this
+    /// class.
+    ///
+    /// Used by `block2`.
+    #[doc(hidden)]
+    pub unsafe fn __add_method_inner(
+        &self,
+        sel: Sel,
+        enc_args: &[Encoding],
+        enc_ret: &Encoding,
+        func: Imp,
+    ) -> bool {
+        let types = self.method_types(sel, enc_args, enc_ret);
+        let success = unsafe {
+            ffi::class_addMethod(
+                self.as_ptr() as *mut ffi::objc_class,
+                sel.as_ptr(),
+                Some(func),
+                types.as_ptr(),
+            )
+        };
+        Bool::from_raw(success).as_bool()
+    }
+
+    /// Replace the instance method on this class with the given raw
+    /// implementation.
+    ///
+    /// Used by `block2`.
+    #[doc(hidden)]
+    pub unsafe fn __replace_method_inner(
+        &self,
+        sel: Sel,
+        enc_args: &[Encoding],
+        enc_ret: &Encoding,
+        func: Imp,
+    ) -> Option<Imp> {
+        let types = self.method_types(sel, enc_args, enc_ret);
+        unsafe {
+            ffi::class_replaceMethod(
+                self.as_ptr() as *mut ffi::objc_class,
+                sel.as_ptr(),
+                Some(func),
+                types.as_ptr(),
+            )
+        }
+    }
+
+    /// Adds a method with the given name and implementation to an already
+    /// registered class.
+    ///
+    /// Returns `false` if the class itself (not counting its superclasses)
+    /// already contains a method with that name; use
+    /// [`replace_method`][Self::replace_method] to change an existing
+    /// method.
+    ///
+    /// See [`ClassBuilder::add_method`] for adding methods to classes that
+    /// are being declared.
+    ///
+    /// [`ClassBuilder::add_method`]: crate::declare::ClassBuilder::add_method
+    ///
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of arguments of the selector and the function
+    /// does not match.
+    ///
+    /// May also panic if the method was detected to be invalid in some way;
+    /// for example if `debug_assertions` are enabled and the method is
+    /// overriding a method on a superclass, we verify that their encodings
+    /// are equal.
+    ///
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that the types match those that are expected
+    /// when the method is invoked from Objective-C.
+    ///
+    /// Additionally, if the method overrides a method on a superclass, it
+    /// must be at least as safe as that method, see
+    /// [`Method::set_implementation`] for details.
+    #[doc(alias = "class_addMethod")]
+    pub unsafe fn add_method<T, F>(&self, sel: Sel, func: F) -> bool
+    where
+        T: Message + ?Sized,
+        F: MethodImplementation<Callee = T>,
+    {
+        unsafe {
+            self.__add_method_inner(
+                sel,
+                F::Args::ENCODINGS,
+                &F::Ret::ENCODING_RETURN,
+                func.__imp(),
+            )
+        }
+    }
+
+    /// Adds a class method with the given name and implementation to an
+    /// already registered class.
+    ///
+    /// Returns `false` if the class itself already contains a class method
+    /// with that name.
+    ///
+    ///
+    /// # Panics
+    ///
+    /// Panics in the same cases as [`add_method`][Self::add_method].
+    ///
+    ///
+    /// # Safety
+    ///
+    /// Same as [`add_method`][Self::add_method].
+    #[doc(alias = "class_addMethod")]
+    pub unsafe fn add_class_method<F>(&self, sel: Sel, func: F) -> bool
+    where
+        F: MethodImplementation<Callee = AnyClass>,
+    {
+        unsafe {
+            self.metaclass().__add_method_inner(
+                sel,
+                F::Args::ENCODINGS,
+                &F::Ret::ENCODING_RETURN,
+                func.__imp(),
+            )
+        }
+    }
+
+    /// Replaces the implementation of the method with the given name on an
+    /// already registered class.
+    ///
+    /// If the class itself (not counting its superclasses) does not contain
+    /// a method with that name, the method is added as with
+    /// [`add_method`][Self::add_method], and [`None`] is returned. Otherwise,
+    /// the previous implementation is returned, which can be used to call
+    /// the original method.
+    ///
+    /// Note that any thread may at any point be changing method
+    /// implementations, see [`Method::set_implementation`].
+    ///
+    ///
+    /// # Panics
+    ///
+    /// Panics in the same cases as [`add_method`][Self::add_method].
+    ///
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that the types match those that are expected
+    /// when the method is invoked from Objective-C.
+    ///
+    /// Additionally, the new implementation must be at least as safe as the
+    /// method it replaces, see [`Method::set_implementation`] for details.
+    ///
+    ///
+    /// # Example
+    ///
+    /// Replace `-[NSObject hash]` on a subclass.
+    ///
+    /// ```
+    /// use objc2::declare::ClassBuilder;
+    /// use objc2::rc::Id;
+    /// use objc2::runtime::{AnyObject, NSObject, Sel};
+    /// use objc2::{msg_send, msg_send_id, sel, ClassType};
+    ///
+    /// extern "C" fn hash(_this: &AnyObject, _cmd: Sel) -> usize {
+    ///     42
+    /// }
+    ///
+    /// let cls = ClassBuilder::new("ReplaceMethodExample", NSObject::class())
+    ///     .unwrap()
+    ///     .register();
+    ///
+    /// let previous = unsafe {
+    ///     cls.replace_method(sel!(hash), hash as extern "C" fn(_, _) -> _)
+    /// };
+    /// // The method was inherited from `NSObject`, not implemented directly
+    /// assert!(previous.is_none());
+    ///
+    /// let obj: Id<AnyObject> = unsafe { msg_send_id![cls, new] };
+    /// let hash: usize = unsafe { msg_send![&obj, hash] };
+    /// assert_eq!(hash, 42);
+    /// ```
+    #[doc(alias = "class_replaceMethod")]
+    pub unsafe fn replace_method<T, F>(&self, sel: Sel, func: F) -> Option<Imp>
+    where
+        T: Message + ?Sized,
+        F: MethodImplementation<Callee = T>,
+    {
+        unsafe {
+            self.__replace_method_inner(
+                sel,
+                F::Args::ENCODINGS,
+                &F::Ret::ENCODING_RETURN,
+                func.__imp(),
+            )
+        }
+    }
+
+    /// Replaces the implementation of the class method with the given name
+    /// on an already registered class.
+    ///
+    /// See [`replace_method`][Self::replace_method] for details.
+    ///
+    ///
+    /// # Panics
+    ///
+    /// Panics in the same cases as [`add_method`][Self::add_method].
+    ///
+    ///
+    /// # Safety
+    ///
+    /// Same as [`replace_method`][Self::replace_method].
+    #[doc(alias = "class_replaceMethod")]
+    pub unsafe fn replace_class_method<F>(&self, sel: Sel, func: F) -> Option<Imp>
+    where
+        F: MethodImplementation<Callee = AnyClass>,
+    {
+        unsafe {
+            self.metaclass().__replace_method_inner(
+                sel,
+                F::Args::ENCODINGS,
+                &F::Ret::ENCODING_RETURN,
+                func.__imp(),
+            )
+        }
+    }
+
+    /// Exchange the implementations of two instance methods on this class.
+    ///
+    /// If either method is only implemented by a superclass, it is first
+    /// added to this class with the superclass' implementation, so that the
+    /// superclass itself is left untouched (this is the usual way of doing
+    /// "method swizzling").
+    ///
+    /// To exchange class methods, call this on the [metaclass].
+    ///
+    /// [metaclass]: Self::metaclass
+    ///
+    ///
+    /// # Panics
+    ///
+    /// Panics if either method does not exist on the class or its
+    /// superclasses.
+    ///
+    /// If `debug_assertions` are enabled, this also panics if the type
+    /// encodings of the two methods differ.
+    ///
+    ///
+    /// # Safety
+    ///
+    /// The two methods must be perfectly compatible, both in signature, and
+    /// in expected (in terms of safety, not necessarily behaviour) input and
+    /// output. See [`Method::exchange_implementation`] for details.
+    #[doc(alias = "method_exchangeImplementations")]
+    pub unsafe fn exchange_implementations(&self, sel1: Sel, sel2: Sel) {
+        let m1 = self.own_instance_method(sel1);
+        let m2 = self.own_instance_method(sel2);
+
+        #[cfg(debug_assertions)]
+        {
+            let types1 = m1.types().map(|res| res.expect("invalid encoding").0);
+            let types2 = m2.types().map(|res| res.expect("invalid encoding").0);
+            if !types1.eq(types2) {
+                let prefix = if self.is_metaclass() { "+" } else { "-" };
+                panic!(
+                    "cannot exchange {prefix}[{self} {sel1}] and {prefix}[{self} {sel2}], their encodings differ"
+                );
+            }
+        }
+
+        // SAFETY: Upheld by the caller.
+        unsafe { m1.exchange_implementation(m2) }
+    }
+
+    /// Get the method with the given name, copying it to this class first if
+    /// it is only implemented by a superclass.
+    fn own_instance_method(&self, sel: Sel) -> &Method {
+        let method = self
+            .instance_method(sel)
+            .unwrap_or_else(|| panic!("method {sel} not found on class {self}"));
+        // SAFETY: The method pointer is valid.
+        let types = unsafe { ffi::method_getTypeEncoding(method.as_ptr()) };
+        // SAFETY: The implementation and types are taken from a method that
+        // is already valid for this class.
+        //
+        // Does nothing if the method is already present on the class.
+        let _ = unsafe {
+            ffi::class_addMethod(
+                self.as_ptr() as *mut ffi::objc_class,
+                sel.as_ptr(),
+                Some(method.implementation()),
+                types,
+            )
+        };
+        self.instance_method(sel).unwrap()
+    }
+
     /// Verify argument and return types for a given selector.
     ///
     /// This will look up the encoding of the method for the given selector
@@ -932,8 +1437,42 @@ impl AnyProtocol {
         str::from_utf8(name.to_bytes()).unwrap()
     }
 
+    /// Returns the description of the method with the given name declared
+    /// by self, or [`None`] if self has no such method.
+    ///
+    /// `required` selects between required and `@optional` methods, and
+    /// `instance` between instance and class methods.
+    ///
+    /// This does not include methods declared by adopted protocols.
+    #[doc(alias = "protocol_getMethodDescription")]
+    pub fn method_description(
+        &self,
+        sel: Sel,
+        required: bool,
+        instance: bool,
+    ) -> Option<MethodDescription> {
+        let raw = unsafe {
+            ffi::protocol_getMethodDescription(
+                self.as_ptr(),
+                sel.as_ptr(),
+                Bool::new(required).as_raw(),
+                Bool::new(instance).as_raw(),
+            )
+        };
+        // SAFETY: The description is either empty, or valid for as long as
+        // the protocol (which can't be deallocated once registered).
+        unsafe { MethodDescription::from_raw(raw) }
+    }
+
+    /// Describes the methods declared by self.
+    ///
+    /// `required` selects between required and `@optional` methods, and
+    /// `instance` between instance and class methods.
+    ///
+    /// This does not include methods declared by adopted protocols.
     #[cfg(feature = "malloc")]
-    fn method_descriptions_inner(&self, required: bool, instance: bool) -> Vec<MethodDescription> {
+    #[doc(alias = "protocol_copyMethodDescriptionList")]
+    pub fn method_descriptions(&self, required: bool, instance: bool) -> Vec<MethodDescription> {
         let mut count: c_uint = 0;
         let descriptions = unsafe {
             ffi::protocol_copyMethodDescriptionList(
@@ -955,18 +1494,46 @@ impl AnyProtocol {
             .collect()
     }
 
-    #[cfg(feature = "malloc")]
-    #[allow(dead_code)]
-    #[doc(alias = "protocol_copyMethodDescriptionList")]
-    pub(crate) fn method_descriptions(&self, required: bool) -> Vec<MethodDescription> {
-        self.method_descriptions_inner(required, true)
+    /// Returns the property with the given name declared by self, or
+    /// [`None`] if self has no such property.
+    ///
+    /// `required` selects between required and `@optional` properties, and
+    /// `instance` between instance and class properties.
+    #[doc(alias = "protocol_getProperty")]
+    pub fn property(&self, name: &str, required: bool, instance: bool) -> Option<&Property> {
+        let name = CString::new(name).unwrap();
+        unsafe {
+            let property = ffi::protocol_getProperty(
+                self.as_ptr(),
+                name.as_ptr(),
+                Bool::new(required).as_raw(),
+                Bool::new(instance).as_raw(),
+            );
+            property.cast::<Property>().as_ref()
+        }
     }
 
+    /// Describes the properties declared by self.
+    ///
+    /// `required` selects between required and `@optional` properties, and
+    /// `instance` between instance and class properties.
+    ///
+    /// This does not include properties declared by adopted protocols.
     #[cfg(feature = "malloc")]
-    #[allow(dead_code)]
-    #[doc(alias = "protocol_copyMethodDescriptionList")]
-    pub(crate) fn class_method_descriptions(&self, required: bool) -> Vec<MethodDescription> {
-        self.method_descriptions_inner(required, false)
+    #[doc(alias = "protocol_copyPropertyList")]
+    #[doc(alias = "protocol_copyPropertyList2")]
+    pub fn properties(&self, required: bool, instance: bool) -> Malloc<[&Property]> {
+        unsafe {
+            let mut count: c_uint = 0;
+            let properties: *mut &Property = ffi::protocol_copyPropertyList2(
+                self.as_ptr(),
+                &mut count,
+                Bool::new(required).as_raw(),
+                Bool::new(instance).as_raw(),
+            )
+            .cast();
+            Malloc::from_array(properties, count as usize)
+        }
     }
 }
 
@@ -1071,6 +1638,55 @@ impl AnyObject {
         unsafe { ptr.as_ref().unwrap_unchecked() }
     }
 
+    /// Whether the object is an instance of the given class, or one of its
+    /// subclasses.
+    ///
+    /// This walks the superclass chain of the object's actual class instead
+    /// of sending `isKindOfClass:`, since proxies (such as subclasses of
+    /// `NSProxy`) forward that message to the object they wrap.
+    pub(crate) fn is_kind_of_class(&self, cls: &AnyClass) -> bool {
+        let mut current = Some(self.class());
+        while let Some(superclass) = current {
+            if superclass == cls {
+                return true;
+            }
+            current = superclass.superclass();
+        }
+        false
+    }
+
+    /// Attempt to downcast the object to a class of type `T`.
+    ///
+    /// This succeeds if the object is an instance of `T` or one of its
+    /// subclasses, as determined by the object's class (not by sending it
+    /// `isKindOfClass:`, which proxies may forward).
+    ///
+    /// See [`Id::downcast`] for doing the same on an owned object.
+    ///
+    /// [`Id::downcast`]: crate::rc::Id::downcast
+    ///
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use objc2::runtime::{AnyObject, NSObject};
+    ///
+    /// let obj = NSObject::new();
+    /// let obj: &AnyObject = &obj;
+    /// assert!(obj.downcast_ref::<NSObject>().is_some());
+    /// ```
+    pub fn downcast_ref<T: DowncastTarget>(&self) -> Option<&T> {
+        if self.is_kind_of_class(T::class()) {
+            let ptr: *const Self = self;
+            // SAFETY: Just checked that the object is an instance of `T`,
+            // which `DowncastTarget` guarantees is valid to use as `T`. The
+            // lifetime is kept.
+            Some(unsafe { &*ptr.cast::<T>() })
+        } else {
+            None
+        }
+    }
+
     /// Change the class of the object at runtime.
     ///
     /// Returns the object's previous class.
@@ -1266,9 +1882,89 @@ impl AnyObject {
         unsafe { *self.ivar_mut::<T>(name) = value };
     }
 
-    // objc_setAssociatedObject
-    // objc_getAssociatedObject
-    // objc_removeAssociatedObjects
+    /// Remove all associated objects from this object.
+    ///
+    /// Use [`AssociationKey::remove`] to remove a single association.
+    ///
+    /// See [Apple's documentation](https://developer.apple.com/documentation/objectivec/1418683-objc_removeassociatedobjects?language=objc).
+    ///
+    ///
+    /// # Safety
+    ///
+    /// This also removes associations that other code (including the
+    /// frameworks you link to) may have added and rely on being present, so
+    /// you must ensure that no such code exists.
+    ///
+    /// In general, this should only be used to return an object to a
+    /// "pristine state".
+    #[doc(alias = "objc_removeAssociatedObjects")]
+    pub unsafe fn remove_associated_objects(&self) {
+        // SAFETY: The object is valid, and the rest is upheld by the caller.
+        unsafe { ffi::objc_removeAssociatedObjects(self.as_ptr() as *mut ffi::objc_object) }
+    }
+
+    /// Acquire the recursive lock associated with this object.
+    ///
+    /// This is the equivalent of an Objective-C `@synchronized(obj)` block,
+    /// and uses the same lock, so Rust and Objective-C code can synchronize
+    /// on the same object. The lock is released when the returned guard is
+    /// dropped.
+    ///
+    /// See also [`with_synchronized`][Self::with_synchronized] for a
+    /// closure-based version.
+    ///
+    ///
+    /// # Panics
+    ///
+    /// Panics if the runtime fails to acquire the lock. Use
+    /// [`try_synchronized`][Self::try_synchronized] to handle that case.
+    ///
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use objc2::runtime::NSObject;
+    ///
+    /// let obj = NSObject::new();
+    /// let guard = obj.synchronized();
+    /// // Other threads (including Objective-C code using `@synchronized`)
+    /// // can't acquire the lock here.
+    /// drop(guard);
+    /// ```
+    #[doc(alias = "objc_sync_enter")]
+    #[doc(alias = "@synchronized")]
+    pub fn synchronized(&self) -> SyncGuard<'_> {
+        match self.try_synchronized() {
+            Ok(guard) => guard,
+            Err(err) => panic!("failed synchronizing on {self:?}: {err}"),
+        }
+    }
+
+    /// Try to acquire the recursive lock associated with this object.
+    ///
+    /// See [`synchronized`][Self::synchronized] for details.
+    ///
+    /// Modern runtimes never fail here, but older Apple runtimes may return
+    /// an error if the lock could not be initialized or timed out.
+    #[doc(alias = "objc_sync_enter")]
+    pub fn try_synchronized(&self) -> Result<SyncGuard<'_>, SyncError> {
+        SyncGuard::new(self)
+    }
+
+    /// Run the given closure while holding the recursive lock associated
+    /// with this object.
+    ///
+    /// The lock is released afterwards, also if the closure panics.
+    ///
+    ///
+    /// # Panics
+    ///
+    /// Panics in the same cases as [`synchronized`][Self::synchronized].
+    #[doc(alias = "@synchronized")]
+    pub fn with_synchronized<R>(&self, f: impl FnOnce() -> R) -> R {
+        let _guard = self.synchronized();
+        f()
+    }
 }
 
 impl fmt::Debug for AnyObject {
@@ -1283,9 +1979,11 @@ mod tests {
     use alloc::string::ToString;
 
     use super::*;
+    use crate::declare::ClassBuilder;
+    use crate::rc::Id;
     use crate::test_utils;
     use crate::MessageReceiver;
-    use crate::{msg_send, sel};
+    use crate::{msg_send, msg_send_id, sel, ClassType};
 
     #[test]
     fn test_selector() {
@@ -1374,6 +2072,169 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_property() {
+        let cls = test_utils::custom_class();
+        let property = cls.property("foo").unwrap();
+        assert_eq!(property.name(), "foo");
+        assert!(<u32>::ENCODING.equivalent_to_str(property.type_encoding()));
+        let attributes = property.attributes();
+        assert!(!attributes.readonly);
+        assert!(attributes.nonatomic);
+        assert_eq!(attributes.ownership, PropertyOwnership::Assign);
+        assert_eq!(attributes.getter, None);
+        assert_eq!(attributes.ivar, Some("_foo"));
+
+        assert!(cls.property("unknown").is_none());
+
+        #[cfg(feature = "malloc")]
+        assert!(cls.instance_properties().iter().any(|p| *p == property));
+    }
+
+    #[test]
+    fn test_add_and_replace_method() {
+        extern "C" fn get_1(_this: &AnyObject, _cmd: Sel) -> u32 {
+            1
+        }
+        extern "C" fn get_2(_this: &AnyObject, _cmd: Sel) -> u32 {
+            2
+        }
+        extern "C" fn class_get_3(_this: &AnyClass, _cmd: Sel) -> u32 {
+            3
+        }
+
+        let cls = ClassBuilder::new("RuntimeAddMethodTest", NSObject::class())
+            .unwrap()
+            .register();
+        let obj: Id<AnyObject> = unsafe { msg_send_id![cls, new] };
+
+        let added = unsafe { cls.add_method(sel!(getNumber), get_1 as extern "C" fn(_, _) -> _) };
+        assert!(added);
+        let res: u32 = unsafe { msg_send![&obj, getNumber] };
+        assert_eq!(res, 1);
+
+        // Already present on the class
+        let added = unsafe { cls.add_method(sel!(getNumber), get_2 as extern "C" fn(_, _) -> _) };
+        assert!(!added);
+        let res: u32 = unsafe { msg_send![&obj, getNumber] };
+        assert_eq!(res, 1);
+
+        let previous =
+            unsafe { cls.replace_method(sel!(getNumber), get_2 as extern "C" fn(_, _) -> _) };
+        assert!(previous.is_some());
+        let res: u32 = unsafe { msg_send![&obj, getNumber] };
+        assert_eq!(res, 2);
+
+        let added = unsafe {
+            cls.add_class_method(sel!(classNumber), class_get_3 as extern "C" fn(_, _) -> _)
+        };
+        assert!(added);
+        let res: u32 = unsafe { msg_send![cls, classNumber] };
+        assert_eq!(res, 3);
+        assert!(NSObject::class().class_method(sel!(classNumber)).is_none());
+
+        let previous = unsafe {
+            cls.replace_class_method(sel!(classNumber), class_get_3 as extern "C" fn(_, _) -> _)
+        };
+        assert!(previous.is_some());
+    }
+
+    #[test]
+    fn test_replace_inherited_method() {
+        extern "C" fn hash(_this: &AnyObject, _cmd: Sel) -> usize {
+            42
+        }
+
+        let cls = ClassBuilder::new("RuntimeReplaceInheritedTest", NSObject::class())
+            .unwrap()
+            .register();
+        let obj: Id<AnyObject> = unsafe { msg_send_id![cls, new] };
+        let superclass_obj = NSObject::new();
+
+        // The method was only present on the superclass
+        let previous = unsafe { cls.replace_method(sel!(hash), hash as extern "C" fn(_, _) -> _) };
+        assert!(previous.is_none());
+        let res: usize = unsafe { msg_send![&obj, hash] };
+        assert_eq!(res, 42);
+        let res: usize = unsafe { msg_send![&superclass_obj, hash] };
+        assert_ne!(res, 42);
+    }
+
+    #[test]
+    #[should_panic = "selector getNumber accepts 0 arguments, but function accepts 1"]
+    fn test_add_method_wrong_arguments() {
+        extern "C" fn get(_this: &AnyObject, _cmd: Sel, _arg: u32) -> u32 {
+            1
+        }
+
+        let cls = ClassBuilder::new("RuntimeAddMethodArgumentsTest", NSObject::class())
+            .unwrap()
+            .register();
+        let _ = unsafe { cls.add_method(sel!(getNumber), get as extern "C" fn(_, _, _) -> _) };
+    }
+
+    #[test]
+    #[cfg_attr(not(debug_assertions), ignore = "only panics with debug assertions")]
+    #[should_panic = "invalid method -[RuntimeReplaceInvalidTest hash]: expected return to have type code 'Q', but found 'f'"]
+    fn test_replace_method_invalid_encoding() {
+        extern "C" fn hash(_this: &AnyObject, _cmd: Sel) -> f32 {
+            0.0
+        }
+
+        let cls = ClassBuilder::new("RuntimeReplaceInvalidTest", NSObject::class())
+            .unwrap()
+            .register();
+        let _ = unsafe { cls.replace_method(sel!(hash), hash as extern "C" fn(_, _) -> _) };
+    }
+
+    #[test]
+    fn test_exchange_implementations() {
+        extern "C" fn get_1(_this: &AnyObject, _cmd: Sel) -> u32 {
+            1
+        }
+        extern "C" fn get_2(_this: &AnyObject, _cmd: Sel) -> u32 {
+            2
+        }
+
+        let mut builder = ClassBuilder::new("RuntimeExchangeSuperTest", NSObject::class()).unwrap();
+        unsafe {
+            builder.add_method(sel!(first), get_1 as extern "C" fn(_, _) -> _);
+            builder.add_method(sel!(second), get_2 as extern "C" fn(_, _) -> _);
+        }
+        let superclass = builder.register();
+        let cls = ClassBuilder::new("RuntimeExchangeTest", superclass)
+            .unwrap()
+            .register();
+
+        let superclass_obj: Id<AnyObject> = unsafe { msg_send_id![superclass, new] };
+        let obj: Id<AnyObject> = unsafe { msg_send_id![cls, new] };
+
+        unsafe { cls.exchange_implementations(sel!(first), sel!(second)) };
+        let res: u32 = unsafe { msg_send![&obj, first] };
+        assert_eq!(res, 2);
+        let res: u32 = unsafe { msg_send![&obj, second] };
+        assert_eq!(res, 1);
+
+        // The superclass is left untouched
+        let res: u32 = unsafe { msg_send![&superclass_obj, first] };
+        assert_eq!(res, 1);
+        let res: u32 = unsafe { msg_send![&superclass_obj, second] };
+        assert_eq!(res, 2);
+
+        // Exchanging again restores the original implementations
+        unsafe { cls.exchange_implementations(sel!(first), sel!(second)) };
+        let res: u32 = unsafe { msg_send![&obj, first] };
+        assert_eq!(res, 1);
+        let res: u32 = unsafe { msg_send![&obj, second] };
+        assert_eq!(res, 2);
+    }
+
+    #[test]
+    #[should_panic = "method unknownMethod not found on class NSObject"]
+    fn test_exchange_unknown() {
+        unsafe { NSObject::class().exchange_implementations(sel!(hash), sel!(unknownMethod)) };
+    }
+
     #[test]
     fn test_class() {
         let cls = test_utils::custom_class();
@@ -1428,19 +2289,27 @@ mod tests {
                     sel: sel!(setBar:),
                     types: "v@:i",
                 };
-                assert_eq!(&proto.method_descriptions(true), &[desc]);
+                assert_eq!(&proto.method_descriptions(true, true), &[desc]);
                 let desc = MethodDescription {
                     sel: sel!(getName),
                     types: "*@:",
                 };
-                assert_eq!(&proto.method_descriptions(false), &[desc]);
+                assert_eq!(&proto.method_descriptions(false, true), &[desc]);
                 let desc = MethodDescription {
                     sel: sel!(addNumber:toNumber:),
                     types: "i@:ii",
                 };
-                assert_eq!(&proto.class_method_descriptions(true), &[desc]);
+                assert_eq!(&proto.method_descriptions(true, false), &[desc]);
             }
-            assert_eq!(&proto.class_method_descriptions(false), &[]);
+            assert_eq!(&proto.method_descriptions(false, false), &[]);
+
+            let properties = proto.properties(true, true);
+            assert_eq!(properties.len(), 1);
+            assert_eq!(properties[0].name(), "bar");
+            let properties = proto.properties(false, true);
+            assert_eq!(properties.len(), 1);
+            assert_eq!(properties[0].name(), "baz");
+            assert!(proto.properties(true, false).is_empty());
 
             assert!(class.adopted_protocols().iter().any(|p| *p == proto));
         }
@@ -1453,6 +2322,51 @@ mod tests {
         assert_eq!(result, 3);
     }
 
+    #[test]
+    fn test_protocol_method_description() {
+        let proto = test_utils::custom_protocol();
+
+        // The selectors are broken somehow on GNUStep < 2.0
+        if cfg!(any(not(feature = "gnustep-1-7"), feature = "gnustep-2-0")) {
+            let desc = proto.method_description(sel!(setBar:), true, true).unwrap();
+            assert_eq!(desc.name(), sel!(setBar:));
+            assert_eq!(desc.return_type(), EncodingBox::Void);
+            assert_eq!(
+                desc.argument_types(),
+                [EncodingBox::Object, EncodingBox::Sel, EncodingBox::Int]
+            );
+
+            let desc = proto
+                .method_description(sel!(addNumber:toNumber:), true, false)
+                .unwrap();
+            assert_eq!(desc.return_type(), EncodingBox::Int);
+            assert_eq!(desc.argument_types().len(), 4);
+        }
+
+        // Wrong kind of method
+        assert!(proto
+            .method_description(sel!(setBar:), false, true)
+            .is_none());
+        assert!(proto
+            .method_description(sel!(setBar:), true, false)
+            .is_none());
+        assert!(proto
+            .method_description(sel!(unknown), true, true)
+            .is_none());
+    }
+
+    #[test]
+    fn test_protocol_property() {
+        let proto = test_utils::custom_protocol();
+        let property = proto.property("bar", true, true).unwrap();
+        assert_eq!(property.name(), "bar");
+        assert!(<i32>::ENCODING.equivalent_to_str(property.type_encoding()));
+        assert!(property.attributes().nonatomic);
+
+        assert!(proto.property("bar", false, true).is_none());
+        assert!(proto.property("unknown", true, true).is_none());
+    }
+
     #[test]
     fn test_subprotocols() {
         let sub_proto = test_utils::custom_subprotocol();
@@ -1519,6 +2433,7 @@ mod tests {
         assert_send_sync::<AnyClass>();
         assert_send_sync::<Ivar>();
         assert_send_sync::<Method>();
+        assert_send_sync::<Property>();
         assert_send_sync::<AnyProtocol>();
         assert_send_sync::<Sel>();
     }
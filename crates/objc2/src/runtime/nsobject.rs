@@ -5,7 +5,7 @@ use crate::mutability::Root;
 use crate::rc::{DefaultId, Id};
 use crate::runtime::{AnyClass, AnyObject, AnyProtocol, ImplementedBy, ProtocolObject};
 use crate::{extern_methods, msg_send, msg_send_id, Message};
-use crate::{ClassType, ProtocolType};
+use crate::{ClassType, DowncastTarget, ProtocolType};
 
 crate::__emit_struct! {
     (
@@ -81,6 +81,9 @@ unsafe impl ClassType for NSObject {
     }
 }
 
+// SAFETY: Not generic.
+unsafe impl DowncastTarget for NSObject {}
+
 /// The methods that are fundamental to most Objective-C objects.
 ///
 /// This represents the [`NSObject` protocol][proto].
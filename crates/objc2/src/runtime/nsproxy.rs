@@ -3,15 +3,26 @@ use core::hash;
 
 use crate::mutability::Root;
 use crate::runtime::{AnyClass, AnyObject, NSObject, NSObjectProtocol, ProtocolObject};
-use crate::ClassType;
+use crate::{ClassType, DowncastTarget};
 
 crate::__emit_struct! {
     (
         /// An abstract superclass defining an API for objects that act as
         /// stand-ins for other objects or for objects that don’t exist yet.
         ///
+        /// This is a root class, like [`NSObject`]. Subclasses can be
+        /// created with [`declare_class!`], see the documentation there for
+        /// details.
+        ///
+        /// Since this class is only available with the `Foundation` framework,
+        /// `objc2` links to it for you.
+        ///
+        /// This is exported under `icrate::Foundation::NSProxy`, you probably
+        /// want to use that path instead.
+        ///
         /// See [Apple's documentation][apple-doc] for more information.
         ///
+        /// [`declare_class!`]: crate::declare_class
         /// [apple-doc]: https://developer.apple.com/documentation/foundation/nsproxy?language=objc
     )
     (pub)
@@ -73,6 +84,9 @@ unsafe impl ClassType for NSProxy {
     }
 }
 
+// SAFETY: Not generic.
+unsafe impl DowncastTarget for NSProxy {}
+
 unsafe impl NSObjectProtocol for NSProxy {}
 
 impl PartialEq for NSProxy {
@@ -0,0 +1,325 @@
+//! Utility for parsing and creating Objective-C property attribute strings.
+//!
+//! See [Apple's documentation on declared properties][apple] for details on
+//! the format.
+//!
+//! [apple]: https://developer.apple.com/library/archive/documentation/Cocoa/Conceptual/ObjCRuntimeGuide/Articles/ocrtPropertyIntrospection.html
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use std::ffi::CString;
+use std::os::raw::c_char;
+
+use crate::encode::Encoding;
+use crate::ffi;
+
+/// The memory management semantics of a property's setter.
+///
+/// This corresponds to the `assign`, `retain`/`strong`, `copy` and `weak`
+/// property attributes in Objective-C.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PropertyOwnership {
+    /// The setter does a simple assignment.
+    ///
+    /// This is the default, and is what is used for non-object types.
+    Assign,
+    /// The setter retains the new value (`retain` or `strong`).
+    Retain,
+    /// The setter copies the new value (`copy`).
+    Copy,
+    /// The setter stores a weak reference to the new value (`weak`).
+    Weak,
+}
+
+impl Default for PropertyOwnership {
+    #[inline]
+    fn default() -> Self {
+        Self::Assign
+    }
+}
+
+/// The attributes of an Objective-C declared property.
+///
+/// This is returned from [`Property::attributes`], and is also used to
+/// describe the property when declaring it with
+/// [`ClassBuilder::add_property`].
+///
+/// Note that the type of the property is not stored here, see
+/// [`Property::type_encoding`] for that.
+///
+/// [`Property::attributes`]: crate::runtime::Property::attributes
+/// [`Property::type_encoding`]: crate::runtime::Property::type_encoding
+/// [`ClassBuilder::add_property`]: crate::declare::ClassBuilder::add_property
+///
+///
+/// # Example
+///
+/// Describe a property equivalent to
+/// `@property(nonatomic, copy, readonly) NSString *name;`, backed by the
+/// instance variable `_name`.
+///
+/// ```
+/// use objc2::runtime::{PropertyAttributes, PropertyOwnership};
+///
+/// let attributes = PropertyAttributes {
+///     readonly: true,
+///     ownership: PropertyOwnership::Copy,
+///     nonatomic: true,
+///     ivar: Some("_name"),
+///     ..Default::default()
+/// };
+/// # let _ = attributes;
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct PropertyAttributes<'a> {
+    /// Whether the property is `readonly` (attribute `R`).
+    pub readonly: bool,
+    /// The memory management semantics of the setter (attributes `&`, `C`
+    /// and `W`).
+    pub ownership: PropertyOwnership,
+    /// Whether the property is `nonatomic` (attribute `N`).
+    pub nonatomic: bool,
+    /// Whether the property is `@dynamic` (attribute `D`).
+    pub dynamic: bool,
+    /// The name of a custom getter method (attribute `G`).
+    pub getter: Option<&'a str>,
+    /// The name of a custom setter method (attribute `S`).
+    pub setter: Option<&'a str>,
+    /// The name of the instance variable backing the property (attribute
+    /// `V`).
+    pub ivar: Option<&'a str>,
+}
+
+impl<'a> PropertyAttributes<'a> {
+    /// Parse the comma-separated attributes string, as returned by
+    /// `property_getAttributes`.
+    ///
+    /// Returns the type encoding along with the other attributes. Unknown
+    /// attributes are ignored.
+    pub(crate) fn parse(s: &'a str) -> (&'a str, Self) {
+        let mut type_encoding = "";
+        let mut this = Self::default();
+
+        for attribute in s.split(',') {
+            let kind = match attribute.chars().next() {
+                Some(kind) => kind,
+                None => continue,
+            };
+            let value = &attribute[kind.len_utf8()..];
+            match kind {
+                'T' => type_encoding = value,
+                'R' => this.readonly = true,
+                '&' => this.ownership = PropertyOwnership::Retain,
+                'C' => this.ownership = PropertyOwnership::Copy,
+                'W' => this.ownership = PropertyOwnership::Weak,
+                'N' => this.nonatomic = true,
+                'D' => this.dynamic = true,
+                'G' => this.getter = Some(value),
+                'S' => this.setter = Some(value),
+                'V' => this.ivar = Some(value),
+                _ => {}
+            }
+        }
+
+        (type_encoding, this)
+    }
+}
+
+/// An owned list of raw property attributes, suitable for passing to
+/// `class_addProperty` and similar functions.
+pub(crate) struct PropertyAttributeList {
+    // Kept around to keep the pointers in `raw` alive.
+    _values: Vec<CString>,
+    raw: Vec<ffi::objc_property_attribute_t>,
+}
+
+impl PropertyAttributeList {
+    pub(crate) fn new(encoding: &Encoding, attributes: &PropertyAttributes<'_>) -> Self {
+        let mut values = Vec::new();
+        let mut raw = Vec::new();
+
+        let mut push = |name: &'static [u8], value: Option<&str>| {
+            let value = CString::new(value.unwrap_or("")).unwrap();
+            raw.push(ffi::objc_property_attribute_t {
+                name: name.as_ptr().cast::<c_char>(),
+                // Moving the `CString` into `values` doesn't move the heap
+                // allocation, so the pointer stays valid.
+                value: value.as_ptr(),
+            });
+            values.push(value);
+        };
+
+        // The type encoding must always come first.
+        push(b"T\0", Some(&encoding.to_string()));
+        if attributes.readonly {
+            push(b"R\0", None);
+        }
+        match attributes.ownership {
+            PropertyOwnership::Assign => {}
+            PropertyOwnership::Retain => push(b"&\0", None),
+            PropertyOwnership::Copy => push(b"C\0", None),
+            PropertyOwnership::Weak => push(b"W\0", None),
+        }
+        if attributes.nonatomic {
+            push(b"N\0", None);
+        }
+        if attributes.dynamic {
+            push(b"D\0", None);
+        }
+        if let Some(getter) = attributes.getter {
+            push(b"G\0", Some(getter));
+        }
+        if let Some(setter) = attributes.setter {
+            push(b"S\0", Some(setter));
+        }
+        if let Some(ivar) = attributes.ivar {
+            push(b"V\0", Some(ivar));
+        }
+
+        Self {
+            _values: values,
+            raw,
+        }
+    }
+
+    pub(crate) fn as_ptr(&self) -> *const ffi::objc_property_attribute_t {
+        self.raw.as_ptr()
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.raw.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        // Examples from Apple's documentation
+        let cases: &[(&str, &str, PropertyAttributes<'_>)] = &[
+            (
+                "Tc,VcharDefault",
+                "c",
+                PropertyAttributes {
+                    ivar: Some("charDefault"),
+                    ..Default::default()
+                },
+            ),
+            (
+                "Td,N,VdoubleDefault",
+                "d",
+                PropertyAttributes {
+                    nonatomic: true,
+                    ivar: Some("doubleDefault"),
+                    ..Default::default()
+                },
+            ),
+            (
+                "Ti,GintGetFoo,SintSetFoo:,VintSetterGetter",
+                "i",
+                PropertyAttributes {
+                    getter: Some("intGetFoo"),
+                    setter: Some("intSetFoo:"),
+                    ivar: Some("intSetterGetter"),
+                    ..Default::default()
+                },
+            ),
+            (
+                "Ti,R,VintReadonly",
+                "i",
+                PropertyAttributes {
+                    readonly: true,
+                    ivar: Some("intReadonly"),
+                    ..Default::default()
+                },
+            ),
+            (
+                "T@,&,VidRetain",
+                "@",
+                PropertyAttributes {
+                    ownership: PropertyOwnership::Retain,
+                    ivar: Some("idRetain"),
+                    ..Default::default()
+                },
+            ),
+            (
+                "T@,C,VidCopy",
+                "@",
+                PropertyAttributes {
+                    ownership: PropertyOwnership::Copy,
+                    ivar: Some("idCopy"),
+                    ..Default::default()
+                },
+            ),
+            (
+                "T@\"NSString\",W,N",
+                "@\"NSString\"",
+                PropertyAttributes {
+                    ownership: PropertyOwnership::Weak,
+                    nonatomic: true,
+                    ..Default::default()
+                },
+            ),
+            (
+                "Ti,D",
+                "i",
+                PropertyAttributes {
+                    dynamic: true,
+                    ..Default::default()
+                },
+            ),
+            (
+                "T{YorkshireTeaStruct=\"pot\"i\"lady\"c},VstructDefault",
+                "{YorkshireTeaStruct=\"pot\"i\"lady\"c}",
+                PropertyAttributes {
+                    ivar: Some("structDefault"),
+                    ..Default::default()
+                },
+            ),
+            ("", "", PropertyAttributes::default()),
+        ];
+
+        for (s, expected_type, expected) in cases {
+            let (type_encoding, attributes) = PropertyAttributes::parse(s);
+            assert_eq!(type_encoding, *expected_type, "{s}");
+            assert_eq!(attributes, *expected, "{s}");
+        }
+    }
+
+    #[test]
+    fn test_attribute_list() {
+        let attributes = PropertyAttributes {
+            readonly: true,
+            ownership: PropertyOwnership::Copy,
+            nonatomic: true,
+            dynamic: false,
+            getter: Some("getFoo"),
+            setter: None,
+            ivar: Some("_foo"),
+        };
+        let list = PropertyAttributeList::new(&Encoding::Object, &attributes);
+        let raw: Vec<_> = list
+            .raw
+            .iter()
+            .map(|attr| unsafe {
+                (
+                    std::ffi::CStr::from_ptr(attr.name).to_str().unwrap(),
+                    std::ffi::CStr::from_ptr(attr.value).to_str().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            raw,
+            [
+                ("T", "@"),
+                ("R", ""),
+                ("C", ""),
+                ("N", ""),
+                ("G", "getFoo"),
+                ("V", "_foo"),
+            ]
+        );
+        assert_eq!(list.len(), 6);
+    }
+}
@@ -0,0 +1,191 @@
+use core::fmt;
+use core::marker::PhantomData;
+use std::error::Error;
+use std::os::raw::c_int;
+
+use crate::ffi;
+use crate::runtime::AnyObject;
+
+/// An error returned by the runtime when entering or exiting a
+/// synchronized section.
+///
+/// This is returned in the error case of [`AnyObject::try_synchronized`] and
+/// [`SyncGuard::unlock`].
+///
+/// This implements [`Error`], and a description of the error can be
+/// retrieved using [`fmt::Display`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SyncError(c_int);
+
+impl SyncError {
+    fn from_code(code: c_int) -> Result<(), Self> {
+        // OBJC_SYNC_SUCCESS
+        if code == 0 {
+            Ok(())
+        } else {
+            Err(Self(code))
+        }
+    }
+
+    /// The raw error code returned by the runtime.
+    pub fn code(&self) -> c_int {
+        self.0
+    }
+}
+
+impl fmt::Display for SyncError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // The same values are used by all runtimes.
+        match self.0 {
+            // OBJC_SYNC_NOT_OWNING_THREAD_ERROR
+            -1 => write!(f, "the current thread does not own the lock"),
+            // OBJC_SYNC_TIMED_OUT
+            -2 => write!(f, "timed out while waiting for the lock"),
+            // OBJC_SYNC_NOT_INITIALIZED
+            -3 => write!(f, "the lock could not be initialized"),
+            code => write!(f, "unknown synchronization error (code {code})"),
+        }
+    }
+}
+
+impl Error for SyncError {}
+
+/// An RAII guard for the recursive lock associated with an object.
+///
+/// This is the equivalent of an Objective-C `@synchronized` block, and
+/// interoperates with Objective-C code using `@synchronized` on the same
+/// object.
+///
+/// The lock is released when the guard is dropped. Use [`SyncGuard::unlock`]
+/// if you want to observe errors while doing so.
+///
+/// This is created with [`AnyObject::synchronized`] or
+/// [`AnyObject::try_synchronized`].
+#[must_use = "if unused the lock will immediately be released"]
+pub struct SyncGuard<'a> {
+    obj: &'a AnyObject,
+    /// The lock must be released on the same thread that acquired it, so
+    /// the guard is neither `Send` nor `Sync`.
+    p: PhantomData<*const ()>,
+}
+
+impl<'a> SyncGuard<'a> {
+    pub(crate) fn new(obj: &'a AnyObject) -> Result<Self, SyncError> {
+        // SAFETY: The object is valid and non-null.
+        let res = unsafe { ffi::objc_sync_enter(obj.as_ptr() as *mut ffi::objc_object) };
+        SyncError::from_code(res)?;
+        Ok(Self {
+            obj,
+            p: PhantomData,
+        })
+    }
+
+    fn exit(&self) -> Result<(), SyncError> {
+        // SAFETY: The object is valid, and was locked in `new`.
+        let res = unsafe { ffi::objc_sync_exit(self.obj.as_ptr() as *mut ffi::objc_object) };
+        SyncError::from_code(res)
+    }
+
+    /// Release the lock, returning any error that the runtime reported.
+    #[doc(alias = "objc_sync_exit")]
+    pub fn unlock(self) -> Result<(), SyncError> {
+        let res = self.exit();
+        core::mem::forget(self);
+        res
+    }
+}
+
+impl Drop for SyncGuard<'_> {
+    #[doc(alias = "objc_sync_exit")]
+    fn drop(&mut self) {
+        // The guard is not `Send`, so we know that we're on the thread that
+        // owns the lock, and hence this shouldn't fail.
+        let _ = self.exit();
+    }
+}
+
+impl fmt::Debug for SyncGuard<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SyncGuard").field("obj", &self.obj).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+    use std::sync::mpsc;
+    use std::thread;
+    use std::time::Duration;
+
+    use super::*;
+    use crate::rc::Id;
+    use crate::runtime::NSObject;
+
+    #[test]
+    fn test_recursive() {
+        let obj = NSObject::new();
+        let guard1 = obj.synchronized();
+        let guard2 = obj.synchronized();
+        drop(guard2);
+        guard1.unlock().unwrap();
+    }
+
+    #[test]
+    fn test_closure() {
+        let obj = NSObject::new();
+        let res = obj.with_synchronized(|| obj.with_synchronized(|| 42));
+        assert_eq!(res, 42);
+    }
+
+    struct SendObject(Id<NSObject>);
+
+    // SAFETY: `NSObject` is thread safe, it is only not marked as such
+    // because subclasses may not be.
+    unsafe impl Send for SendObject {}
+
+    #[test]
+    fn test_excludes_other_threads() {
+        let obj: Id<NSObject> = NSObject::new();
+        let (locked_tx, locked_rx) = mpsc::channel();
+        let (release_tx, release_rx) = mpsc::channel::<()>();
+        let (done_tx, done_rx) = mpsc::channel();
+
+        let holder = SendObject(obj.clone());
+        let holder = thread::spawn(move || {
+            let holder = holder;
+            let _guard = holder.0.synchronized();
+            locked_tx.send(()).unwrap();
+            release_rx.recv().unwrap();
+        });
+        locked_rx.recv().unwrap();
+
+        let waiter = SendObject(obj);
+        let waiter = thread::spawn(move || {
+            let waiter = waiter;
+            let _guard = waiter.0.synchronized();
+            done_tx.send(()).unwrap();
+        });
+
+        // The other thread holds the lock, so we can't take it yet
+        assert!(done_rx.recv_timeout(Duration::from_millis(50)).is_err());
+
+        release_tx.send(()).unwrap();
+        done_rx.recv().unwrap();
+        holder.join().unwrap();
+        waiter.join().unwrap();
+    }
+
+    #[test]
+    fn test_error_display() {
+        assert_eq!(
+            SyncError(-1).to_string(),
+            "the current thread does not own the lock"
+        );
+        assert_eq!(
+            SyncError(-42).to_string(),
+            "unknown synchronization error (code -42)"
+        );
+        assert_eq!(SyncError(-1).code(), -1);
+        assert_eq!(SyncError::from_code(0), Ok(()));
+    }
+}
@@ -4,7 +4,7 @@ use std::os::raw::c_char;
 use std::sync::Once;
 
 use crate::declare::{ClassBuilder, ProtocolBuilder};
-use crate::runtime::{AnyClass, AnyObject, AnyProtocol, Sel};
+use crate::runtime::{AnyClass, AnyObject, AnyProtocol, PropertyAttributes, Sel};
 use crate::{ffi, Encode, Encoding, MessageReceiver};
 use crate::{msg_send, sel};
 
@@ -108,6 +108,14 @@ pub(crate) fn custom_class() -> &'static AnyClass {
 
         builder.add_protocol(proto);
         builder.add_ivar::<u32>("_foo");
+        builder.add_property::<u32>(
+            "foo",
+            PropertyAttributes {
+                nonatomic: true,
+                ivar: Some("_foo"),
+                ..Default::default()
+            },
+        );
 
         unsafe extern "C" fn custom_obj_release(this: *mut AnyObject, _cmd: Sel) {
             // Drop the value
@@ -219,6 +227,16 @@ pub(crate) fn custom_protocol() -> &'static AnyProtocol {
         builder.add_method_description::<(i32,), ()>(sel!(setBar:), true);
         builder.add_method_description::<(), *const c_char>(sel!(getName), false);
         builder.add_class_method_description::<(i32, i32), i32>(sel!(addNumber:toNumber:), true);
+        builder.add_property::<i32>(
+            "bar",
+            PropertyAttributes {
+                nonatomic: true,
+                ..Default::default()
+            },
+            true,
+            true,
+        );
+        builder.add_property::<i32>("baz", Default::default(), false, true);
 
         builder.register();
     });
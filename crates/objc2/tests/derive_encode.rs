@@ -0,0 +1,85 @@
+#![cfg(feature = "derive")]
+use core::ffi::c_void;
+use core::marker::PhantomData;
+
+use objc2::encode::{Encode, Encoding, RefEncode};
+
+#[repr(C)]
+#[derive(Encode, RefEncode)]
+struct Point {
+    x: f64,
+    y: f64,
+}
+
+#[repr(C)]
+#[derive(Encode, RefEncode)]
+#[encoding(name = "CGRect")]
+struct Rect {
+    origin: Point,
+    /// Doc comments and visibility are allowed.
+    pub size: Point,
+}
+
+#[repr(C)]
+#[derive(Encode, RefEncode)]
+struct Tuple(i32, *const c_void);
+
+#[repr(C)]
+#[derive(Clone, Copy, Encode, RefEncode)]
+union Value {
+    int: i64,
+    float: f64,
+}
+
+#[repr(C)]
+#[derive(Encode, RefEncode)]
+struct Generic<T, const N: usize> {
+    items: [T; N],
+}
+
+#[repr(C)]
+#[derive(Encode, RefEncode)]
+struct Callback<F: PartialEq<extern "C" fn() -> i32> = extern "C" fn() -> i32> {
+    f: F,
+}
+
+#[repr(transparent)]
+#[derive(Encode, RefEncode)]
+struct Newtype(u32);
+
+#[repr(transparent)]
+#[derive(Encode, RefEncode)]
+struct Tagged<'a, T> {
+    value: usize,
+    _p: PhantomData<&'a T>,
+}
+
+#[test]
+fn test_struct() {
+    assert_eq!(
+        Point::ENCODING,
+        Encoding::Struct("Point", &[Encoding::Double, Encoding::Double]),
+    );
+    assert!(Point::ENCODING_REF.equivalent_to_str("^{Point=dd}"));
+    assert!(Rect::ENCODING.equivalent_to_str("{CGRect={Point=dd}{Point=dd}}"));
+    assert!(Tuple::ENCODING.equivalent_to_str("{Tuple=i^v}"));
+}
+
+#[test]
+fn test_union() {
+    assert!(Value::ENCODING.equivalent_to_str("(Value=qd)"));
+    assert!(Value::ENCODING_REF.equivalent_to_str("^(Value=qd)"));
+}
+
+#[test]
+fn test_generic() {
+    assert!(<Generic<u8, 4>>::ENCODING.equivalent_to_str("{Generic=[4C]}"));
+    assert!(<Callback>::ENCODING.equivalent_to_str("{Callback=^?}"));
+}
+
+#[test]
+fn test_transparent() {
+    assert_eq!(Newtype::ENCODING, u32::ENCODING);
+    assert_eq!(Newtype::ENCODING_REF, u32::ENCODING_REF);
+    assert_eq!(<Tagged<'_, i8>>::ENCODING, usize::ENCODING);
+}
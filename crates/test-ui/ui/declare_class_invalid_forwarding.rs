@@ -0,0 +1,39 @@
+//! Test invalid usage of the message forwarding attributes.
+use objc2::runtime::{NSObject, Sel};
+use objc2::{declare_class, mutability, ClassType};
+
+declare_class!(
+    struct CustomObject;
+
+    unsafe impl ClassType for CustomObject {
+        type Super = NSObject;
+        type Mutability = mutability::InteriorMutable;
+        const NAME: &'static str = "CustomObject";
+    }
+
+    unsafe impl CustomObject {
+        #[resolve_instance_method]
+        fn resolve_instance_method(&self, _sel: Sel) -> bool {
+            unimplemented!()
+        }
+
+        #[resolve_class_method]
+        fn resolve_class_method(&self, _sel: Sel) -> bool {
+            unimplemented!()
+        }
+
+        #[forwarding_target]
+        #[method(foo)]
+        fn forwarding_target(&self, _sel: Sel) -> bool {
+            unimplemented!()
+        }
+
+        #[method(bar)]
+        #[resolve_class_method]
+        fn bar(_sel: Sel) -> bool {
+            unimplemented!()
+        }
+    }
+);
+
+fn main() {}
@@ -0,0 +1,30 @@
+//! Test invalid properties in `declare_class!`.
+use core::ptr::NonNull;
+
+use objc2::declare::{IvarBool, IvarDrop, IvarEncode};
+use objc2::rc::Id;
+use objc2::runtime::NSObject;
+use objc2::{declare_class, mutability, ClassType};
+
+declare_class!(
+    struct CustomObject {
+        #[property(number, retain)]
+        number: IvarEncode<i32, "_number">,
+        #[property(flag, retain, nonatomic)]
+        flag: IvarBool<"_flag">,
+        #[property(object, copy)]
+        object: IvarDrop<Option<Id<NSObject>>, "_object">,
+        #[property(pointer)]
+        pointer: IvarEncode<NonNull<NSObject>, "_pointer">,
+    }
+
+    mod ivars;
+
+    unsafe impl ClassType for CustomObject {
+        type Super = NSObject;
+        type Mutability = mutability::InteriorMutable;
+        const NAME: &'static str = "CustomObject";
+    }
+);
+
+fn main() {}
@@ -0,0 +1,37 @@
+//! Test invalid uses of `#[derive(Encode)]`.
+use objc2::encode::Encode;
+
+#[derive(Encode)]
+struct NoRepr {
+    a: i32,
+}
+
+#[repr(u8)]
+#[derive(Encode)]
+enum Enum {
+    A,
+    B,
+}
+
+#[repr(C, packed)]
+#[derive(Encode)]
+struct Packed {
+    a: u8,
+    b: u32,
+}
+
+#[repr(C)]
+#[derive(Encode)]
+#[encoding(name)]
+struct MissingName {
+    a: i32,
+}
+
+#[repr(C)]
+#[derive(Encode)]
+#[encoding(name = 42)]
+struct NotAString {
+    a: i32,
+}
+
+fn main() {}
@@ -1,6 +1,7 @@
+//! Test that variadic methods are rejected unless specified correctly.
 use objc2::rc::Id;
 use objc2::runtime::NSObject;
-use objc2::{extern_class, extern_methods, mutability, ClassType};
+use objc2::{extern_class, extern_methods, mutability, ClassType, VariadicArguments};
 
 extern_class!(
     pub struct MyObject;
@@ -39,4 +40,11 @@ extern_methods!(
     }
 );
 
+extern_methods!(
+    unsafe impl MyObject {
+        #[method(a:_)]
+        fn variadic_error_impl(args: impl VariadicArguments) -> Result<(), Id<NSObject>>;
+    }
+);
+
 fn main() {}
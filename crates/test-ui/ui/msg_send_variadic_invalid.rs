@@ -0,0 +1,14 @@
+//! Test that types that are affected by C's default argument promotions are
+//! not accepted as variadic arguments.
+use objc2::runtime::NSObject;
+use objc2::{class, msg_send};
+
+fn main() {
+    let cls = class!(NSString);
+    let format: &NSObject;
+    unsafe {
+        let _: *mut NSObject = msg_send![cls, stringWithFormat: format, ...(1.0f32,)];
+        let _: *mut NSObject = msg_send![cls, stringWithFormat: format, ...(true,)];
+        let _: *mut NSObject = msg_send![cls, stringWithFormat: format, ...(1i32, 2u8)];
+    }
+}
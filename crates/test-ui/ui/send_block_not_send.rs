@@ -0,0 +1,13 @@
+//! Test that blocks with non-thread-safe closures cannot be made `Send`.
+use std::cell::Cell;
+use std::rc::Rc;
+
+use block2::ConcreteBlock;
+
+fn main() {
+    let rc = Rc::new(0);
+    let _ = ConcreteBlock::new(move || *rc).copy_send();
+
+    let cell = Cell::new(0);
+    let _ = ConcreteBlock::new(move || cell.get()).copy_send();
+}
@@ -0,0 +1,87 @@
+use block2::{Block, BlockWeak, ConcreteBlock, RcBlock};
+use objc2::declare::IvarDrop;
+use objc2::rc::Id;
+use objc2::runtime::{NSObject, PropertyOwnership};
+use objc2::{declare_class, msg_send, msg_send_id, mutability, ClassType};
+
+use crate::invoke_int_block;
+
+declare_class!(
+    struct BlockHolder {
+        #[property(handler, nonatomic)]
+        handler: IvarDrop<Option<Id<Block<(), i32>>>, "_handler">,
+    }
+
+    mod ivars;
+
+    unsafe impl ClassType for BlockHolder {
+        type Super = NSObject;
+        type Mutability = mutability::InteriorMutable;
+        const NAME: &'static str = "BlockHolder";
+    }
+);
+
+#[test]
+fn test_id_conversion() {
+    let block = ConcreteBlock::new(|| 5).copy();
+    let obj: Id<Block<(), i32>> = block.into();
+    assert_eq!(invoke_int_block(&obj), 5);
+
+    // Blocks respond to messages like other objects
+    let copy: Id<Block<(), i32>> = unsafe { msg_send_id![&*obj, copy] };
+    assert_eq!(invoke_int_block(&copy), 5);
+
+    let block = RcBlock::from(obj);
+    assert_eq!(invoke_int_block(&block), 5);
+}
+
+#[test]
+fn test_stack_block_id_conversion() {
+    let x = 6;
+    let block = {
+        let stack = ConcreteBlock::new(move || x);
+        let ptr: *const Block<(), i32> = &*stack;
+        // Retaining a block on the stack doesn't move it to the heap
+        let obj: Id<Block<(), i32>> = unsafe { Id::retain(ptr as *mut _) }.unwrap();
+        RcBlock::from(obj)
+    };
+    assert_eq!(invoke_int_block(&block), 6);
+}
+
+#[test]
+fn test_block_property() {
+    let cls = BlockHolder::class();
+    let attributes = cls.property("handler").unwrap().attributes();
+    assert_eq!(attributes.ownership, PropertyOwnership::Copy);
+
+    let obj: Id<BlockHolder> = unsafe { msg_send_id![cls, new] };
+    assert!(obj.handler.is_none());
+
+    let x = 7;
+    let block = ConcreteBlock::new(move || x).copy();
+    let _: () = unsafe { msg_send![&obj, setHandler: &*block] };
+    drop(block);
+
+    let handler: Option<Id<Block<(), i32>>> = unsafe { msg_send_id![&obj, handler] };
+    assert_eq!(invoke_int_block(&handler.unwrap()), 7);
+    assert_eq!(invoke_int_block(obj.handler.as_deref().unwrap()), 7);
+}
+
+#[test]
+fn test_weak_capture() {
+    let obj = NSObject::new();
+    let weak = BlockWeak::from_id(&obj);
+    let block = ConcreteBlock::new({
+        let weak = weak.clone();
+        move || weak.load().is_some() as i32
+    });
+    let block = block.copy();
+    let copied = block.clone();
+
+    assert_eq!(invoke_int_block(&block), 1);
+    assert!(weak.load().is_some());
+    drop(obj);
+    assert_eq!(invoke_int_block(&block), 0);
+    assert_eq!(invoke_int_block(&copied), 0);
+    assert!(weak.load().is_none());
+}
@@ -1,7 +1,7 @@
 use alloc::format;
 
 use icrate::Foundation::{NSArray, NSException, NSString};
-use objc2::exception::{catch, throw};
+use objc2::exception::{catch, catch_as, throw};
 use objc2::msg_send;
 use objc2::rc::{autoreleasepool, Id};
 use objc2::runtime::{AnyObject, NSObject};
@@ -61,6 +61,25 @@ fn throw_catch_raise_catch() {
     assert!(exc.userInfo().is_none());
 }
 
+#[test]
+#[cfg_attr(
+    feature = "catch-all",
+    ignore = "Panics inside `catch` when catch-all is enabled"
+)]
+fn catch_as_accessors() {
+    let name = NSString::from_str("abc");
+    let reason = NSString::from_str("def");
+    let exc = NSException::new(&name, Some(&reason), None).unwrap();
+    let exc = NSException::into_exception(exc);
+
+    let res = unsafe { catch_as::<NSException, (), _>(|| throw(exc)) };
+    let exc = NSException::into_exception(res.unwrap_err());
+
+    assert_eq!(exc.name().as_deref(), Some("abc"));
+    assert_eq!(exc.reason().as_deref(), Some("def"));
+    assert!(exc.user_info().is_none());
+}
+
 #[test]
 #[cfg(feature = "catch-all")]
 #[should_panic = "uncaught exception <NSException: 0x"]
@@ -83,6 +102,40 @@ fn raise_catch_all2() {
     unsafe { exc.raise() };
 }
 
+#[test]
+#[cfg(feature = "catch-all")]
+#[should_panic = "thrown by -[NSException raise] at "]
+fn raise_catch_all_context() {
+    let name = NSString::from_str("abc");
+
+    let exc = NSException::new(&name, None, None).unwrap();
+    unsafe { exc.raise() };
+}
+
+#[test]
+#[cfg_attr(
+    feature = "catch-all",
+    ignore = "Panics inside `catch` when catch-all is enabled"
+)]
+fn call_stack() {
+    let name = NSString::from_str("abc");
+    let exc = NSException::new(&name, None, None).unwrap();
+    let exc = NSException::into_exception(exc);
+
+    let res: Result<(), _> = unsafe { catch(|| throw(exc)) };
+    let exc = res.unwrap_err().unwrap();
+
+    let addresses = exc.call_stack_return_addresses();
+    let symbols = exc.call_stack_symbols();
+    if cfg!(feature = "apple") {
+        let addresses = addresses.unwrap();
+        assert!(!addresses.is_empty());
+        assert_eq!(symbols.unwrap().len(), addresses.len());
+    }
+
+    assert!(format!("{exc:#?}").starts_with(&format!("{exc:?}")));
+}
+
 #[test]
 #[cfg_attr(
     feature = "catch-all",
@@ -160,3 +213,44 @@ fn catch_actual() {
         assert!(user_info.is_none());
     }
 }
+
+#[test]
+#[cfg(feature = "panic-to-exception")]
+#[cfg_attr(
+    feature = "catch-all",
+    ignore = "Panics inside `catch` when catch-all is enabled"
+)]
+fn panic_to_exception() {
+    use core::panic::AssertUnwindSafe;
+    use objc2::{declare_class, msg_send_id, mutability, ClassType};
+
+    declare_class!(
+        struct Panicker;
+
+        unsafe impl ClassType for Panicker {
+            type Super = NSObject;
+            type Mutability = mutability::InteriorMutable;
+            const NAME: &'static str = "PanicToException";
+        }
+
+        unsafe impl Panicker {
+            #[method(panicWithValue:)]
+            fn panic_with_value(&self, value: i32) -> i32 {
+                panic!("got {value}");
+            }
+        }
+    );
+
+    let obj: Id<Panicker> = unsafe { msg_send_id![Panicker::class(), new] };
+
+    let res = unsafe {
+        catch(AssertUnwindSafe(|| {
+            let _: i32 = msg_send![&obj, panicWithValue: 42];
+        }))
+    };
+    let exc = res.unwrap_err().unwrap();
+
+    assert_eq!(exc.name().as_deref(), Some("RustPanic"));
+    assert_eq!(exc.reason().as_deref(), Some("got 42"));
+    assert!(NSException::from_exception(exc).is_ok());
+}
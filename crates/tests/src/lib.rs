@@ -7,10 +7,14 @@ use block2::{Block, RcBlock};
 extern crate alloc;
 extern crate std;
 
+#[cfg(test)]
+mod block_object;
 #[cfg(all(test, feature = "exception"))]
 mod exception;
 pub mod ffi;
 #[cfg(test)]
+mod method;
+#[cfg(test)]
 mod test_encode_utils;
 #[cfg(test)]
 mod test_object;
@@ -53,7 +57,7 @@ pub fn invoke_large_struct_block(
 mod tests {
     use super::*;
     use alloc::string::ToString;
-    use block2::{global_block, ConcreteBlock, RcBlock};
+    use block2::{global_block, ConcreteBlock, RcBlock, StackBlock};
 
     global_block! {
         /// Test `global_block` in an external crate
@@ -108,6 +112,113 @@ mod tests {
         assert_eq!(invoke_int_block(&copied), expected_len);
     }
 
+    #[test]
+    fn test_concrete_block_mut() {
+        let mut counter = 0;
+        let block = ConcreteBlock::new_mut(move |a: i32| {
+            counter += a;
+            counter
+        });
+        assert_eq!(invoke_add_block(&block, 2), 2);
+        assert_eq!(invoke_add_block(&block, 3), 5);
+
+        let block = block.copy();
+        assert_eq!(invoke_add_block(&block, 4), 9);
+        let cloned = block.clone();
+        assert_eq!(invoke_add_block(&cloned, 1), 10);
+    }
+
+    #[test]
+    fn test_concrete_block_once() {
+        let s = "Hello!".to_string();
+        let block = ConcreteBlock::new_once(move || {
+            let s = s;
+            s.len() as i32
+        });
+        let block = block.copy();
+        assert_eq!(invoke_int_block(&block), 6);
+    }
+
+    /// Run the given test in a child process with `ABORT_TEST` set, and
+    /// check that it aborted with the given message.
+    ///
+    /// Aborting can't be caught in-process, so this is the only way to test
+    /// it.
+    fn assert_aborts_in_child(test: &str, msg: &str) {
+        let output = std::process::Command::new(std::env::current_exe().unwrap())
+            .args([test, "--exact", "--nocapture", "--test-threads=1"])
+            .env("ABORT_TEST", test)
+            .output()
+            .unwrap();
+        assert!(!output.status.success(), "child process did not abort");
+        let stderr = std::string::String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains(msg), "unexpected stderr: {stderr}");
+    }
+
+    #[test]
+    fn test_concrete_block_mut_reentrant() {
+        const NAME: &str = "tests::test_concrete_block_mut_reentrant";
+        if std::env::var("ABORT_TEST").as_deref() != Ok(NAME) {
+            assert_aborts_in_child(
+                NAME,
+                "re-entrant call of block created with `ConcreteBlock::new_mut`",
+            );
+            return;
+        }
+
+        let this: core::cell::Cell<*const Block<(i32,), i32>> =
+            core::cell::Cell::new(core::ptr::null());
+        let block = ConcreteBlock::new_mut(|a: i32| {
+            if a == 0 {
+                0
+            } else {
+                invoke_add_block(unsafe { &*this.get() }, a - 1)
+            }
+        });
+        this.set(&*block);
+        invoke_add_block(&block, 1);
+    }
+
+    #[test]
+    fn test_concrete_block_once_called_twice() {
+        const NAME: &str = "tests::test_concrete_block_once_called_twice";
+        if std::env::var("ABORT_TEST").as_deref() != Ok(NAME) {
+            assert_aborts_in_child(
+                NAME,
+                "block created with `ConcreteBlock::new_once` was called more than once",
+            );
+            return;
+        }
+
+        let s = "Hello!".to_string();
+        let block = ConcreteBlock::new_once(move || s.len() as i32).copy();
+        assert_eq!(invoke_int_block(&block), 6);
+        invoke_int_block(&block);
+    }
+
+    #[test]
+    fn test_send_block() {
+        let s = alloc::sync::Arc::new("Hello!".to_string());
+        let block = ConcreteBlock::new(move || s.len() as i32).copy_send();
+        let thread_block = block.clone();
+        let res = std::thread::spawn(move || invoke_int_block(&thread_block))
+            .join()
+            .unwrap();
+        assert_eq!(res, 6);
+        assert_eq!(invoke_int_block(&block), 6);
+    }
+
+    #[test]
+    fn test_stack_block() {
+        let s = "Hello!".to_string();
+        let closure = || s.len() as i32;
+        let block = StackBlock::new(&closure);
+        assert_eq!(invoke_int_block(&block), 6);
+
+        let closure = |a: i32| a + s.len() as i32;
+        assert_eq!(invoke_add_block(&StackBlock::new(&closure), 2), 8);
+    }
+
     #[test]
     fn test_concrete_block_stack_copy() {
         fn make_block() -> RcBlock<(), i32> {
@@ -145,4 +256,32 @@ mod tests {
         let block = block.copy();
         assert_eq!(invoke_large_struct_block(&block, data), new_data);
     }
+
+    #[test]
+    fn test_verify_block() {
+        // Blocks created by Clang
+        let block = get_int_block_with(13);
+        block.verify().unwrap();
+        let block = get_add_block_with(13);
+        block.verify().unwrap();
+
+        let ptr: *const Block<(i32,), i32> = &*block;
+        let wrong: &Block<(f32,), i32> = unsafe { &*ptr.cast() };
+        assert!(wrong.verify().is_err());
+        let wrong: &Block<(), i32> = unsafe { &*ptr.cast() };
+        assert!(wrong.verify().is_err());
+
+        // Blocks created by us
+        let block = ConcreteBlock::new(|a: i32| a + 5);
+        assert_eq!(block.signature(), Some("i@?i"));
+        block.verify().unwrap();
+        let block = block.copy();
+        assert_eq!(block.signature(), Some("i@?i"));
+        block.verify().unwrap();
+
+        let s = "Hello!".to_string();
+        let block = ConcreteBlock::new(move || s.len() as i32);
+        assert_eq!(block.signature(), Some("i@?"));
+        block.verify().unwrap();
+    }
 }
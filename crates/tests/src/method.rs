@@ -0,0 +1,151 @@
+use alloc::sync::Arc;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use block2::{AnyClassExt, ClassBuilderExt};
+use objc2::declare::ClassBuilder;
+use objc2::encode::{Encode, Encoding};
+use objc2::rc::Id;
+use objc2::runtime::{AnyClass, AnyObject, NSObject};
+use objc2::{msg_send, msg_send_id, sel, ClassType};
+
+#[test]
+fn test_closure_method() {
+    let counter = Arc::new(AtomicUsize::new(0));
+
+    let mut builder = ClassBuilder::new("ClosureMethodTest", NSObject::class()).unwrap();
+    let captured = counter.clone();
+    unsafe {
+        builder.add_method_with_closure(sel!(incrementBy:), move |_this: &AnyObject, n: usize| {
+            captured.fetch_add(n, Ordering::Relaxed)
+        });
+        builder.add_method_with_closure(sel!(addA:b:), |_this: &AnyObject, a: i32, b: i32| a + b);
+        builder.add_class_method_with_closure(sel!(classValue), |_cls: &AnyClass| 7u32);
+    }
+    let cls = builder.register();
+
+    let obj: Id<AnyObject> = unsafe { msg_send_id![cls, new] };
+    let previous: usize = unsafe { msg_send![&obj, incrementBy: 2usize] };
+    assert_eq!(previous, 0);
+    let previous: usize = unsafe { msg_send![&obj, incrementBy: 3usize] };
+    assert_eq!(previous, 2);
+    assert_eq!(counter.load(Ordering::Relaxed), 5);
+
+    let res: i32 = unsafe { msg_send![&obj, addA: 1i32, b: 2i32] };
+    assert_eq!(res, 3);
+
+    let res: u32 = unsafe { msg_send![cls, classValue] };
+    assert_eq!(res, 7);
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Large {
+    a: u64,
+    b: u64,
+    c: u64,
+    d: u64,
+}
+
+unsafe impl Encode for Large {
+    const ENCODING: Encoding = Encoding::Struct(
+        "Large",
+        &[u64::ENCODING, u64::ENCODING, u64::ENCODING, u64::ENCODING],
+    );
+}
+
+#[test]
+fn test_closure_struct_return() {
+    let mut builder = ClassBuilder::new("ClosureMethodStructTest", NSObject::class()).unwrap();
+    unsafe {
+        builder.add_method_with_closure(sel!(largeWith:), |_this: &AnyObject, a: u64| Large {
+            a,
+            b: 2,
+            c: 3,
+            d: 4,
+        });
+    }
+    let cls = builder.register();
+
+    let obj: Id<AnyObject> = unsafe { msg_send_id![cls, new] };
+    let res: Large = unsafe { msg_send![&obj, largeWith: 1u64] };
+    assert_eq!(
+        res,
+        Large {
+            a: 1,
+            b: 2,
+            c: 3,
+            d: 4
+        }
+    );
+}
+
+#[test]
+fn test_closure_receiver() {
+    let mut builder = ClassBuilder::new("ClosureMethodReceiverTest", NSObject::class()).unwrap();
+    unsafe {
+        builder.add_method_with_closure(sel!(selfPointer), |this: &AnyObject| {
+            this as *const AnyObject as usize
+        });
+    }
+    let cls = builder.register();
+
+    let obj: Id<AnyObject> = unsafe { msg_send_id![cls, new] };
+    let res: usize = unsafe { msg_send![&obj, selfPointer] };
+    assert_eq!(res, Id::as_ptr(&obj) as usize);
+}
+
+#[test]
+fn test_closure_registered_class() {
+    let cls = ClassBuilder::new("ClosureMethodRegisteredTest", NSObject::class())
+        .unwrap()
+        .register();
+    let obj: Id<AnyObject> = unsafe { msg_send_id![cls, new] };
+
+    let added = unsafe { cls.add_method_with_closure(sel!(getNumber), |_this: &AnyObject| 1u32) };
+    assert!(added);
+    let res: u32 = unsafe { msg_send![&obj, getNumber] };
+    assert_eq!(res, 1);
+
+    // The closure is dropped if the method could not be added
+    let value = Arc::new(2u32);
+    let captured = value.clone();
+    let added =
+        unsafe { cls.add_method_with_closure(sel!(getNumber), move |_this: &AnyObject| *captured) };
+    assert!(!added);
+    assert_eq!(Arc::strong_count(&value), 1);
+    let res: u32 = unsafe { msg_send![&obj, getNumber] };
+    assert_eq!(res, 1);
+
+    let previous =
+        unsafe { cls.replace_method_with_closure(sel!(getNumber), |_this: &AnyObject| 3u32) };
+    assert!(previous.is_some());
+    let res: u32 = unsafe { msg_send![&obj, getNumber] };
+    assert_eq!(res, 3);
+
+    let added =
+        unsafe { cls.add_class_method_with_closure(sel!(classNumber), |_cls: &AnyClass| 4u32) };
+    assert!(added);
+    let res: u32 = unsafe { msg_send![cls, classNumber] };
+    assert_eq!(res, 4);
+
+    let previous =
+        unsafe { cls.replace_class_method_with_closure(sel!(classNumber), |_cls: &AnyClass| 5u32) };
+    assert!(previous.is_some());
+    let res: u32 = unsafe { msg_send![cls, classNumber] };
+    assert_eq!(res, 5);
+}
+
+#[test]
+#[should_panic = "selector incrementBy: accepts 1 arguments, but function accepts 0"]
+fn test_closure_wrong_arguments() {
+    let mut builder = ClassBuilder::new("ClosureMethodArgumentsTest", NSObject::class()).unwrap();
+    unsafe { builder.add_method_with_closure(sel!(incrementBy:), |_this: &AnyObject| 0usize) };
+}
+
+#[test]
+#[cfg_attr(not(debug_assertions), ignore = "only panics with debug assertions")]
+#[should_panic = "declared invalid method -[ClosureMethodInvalidTest hash]"]
+fn test_closure_invalid_override() {
+    let mut builder = ClassBuilder::new("ClosureMethodInvalidTest", NSObject::class()).unwrap();
+    unsafe { builder.add_method_with_closure(sel!(hash), |_this: &AnyObject| 0.0f32) };
+}